@@ -0,0 +1,39 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::process;
+
+use chesslib::cli;
+
+const USAGE: &str = "Usage: chess [--batch [file]]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => cli::start(),
+        ["--batch"] | ["--batch", "-"] => exit_batch(cli::run_batch(io::stdin().lock(), io::stdout().lock())),
+        ["--batch", path] => match File::open(path) {
+            Ok(file) => exit_batch(cli::run_batch(BufReader::new(file), io::stdout().lock())),
+            Err(error) => {
+                eprintln!("Could not read {}: {}", path, error);
+                process::exit(2);
+            },
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        },
+    }
+}
+
+/// Exit with 1 if a command of the batch failed
+fn exit_batch(failed: io::Result<usize>) {
+    match failed {
+        Ok(0) => {},
+        Ok(_) => process::exit(1),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        },
+    }
+}
@@ -0,0 +1,377 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chesslib::pgn;
+use chesslib::stats::{MatchScore, Sprt, SprtDecision};
+use chesslib::uci::{GoLimits, UciEngine};
+use chesslib::{Chess, GameResult, Outcome, Side, Termination};
+
+// how long an engine may go over its clock before the move is taken as a time forfeit
+const TIME_MARGIN: Duration = Duration::from_millis(100);
+
+const USAGE: &str = "Usage: matchrunner --engine1 <path> --engine2 <path> [options]
+
+Options:
+    --games <n>             Number of games, played in pairs with colors reversed (default 2)
+    --concurrency <n>       Number of games played at the same time (default 1)
+    --tc <base>+<inc>       Time control in seconds per game plus increment (default 10+0.1)
+    --movetime <ms>         Fixed time per move instead of a time control
+    --depth <n>             Fixed depth per move instead of a time control
+    --openings <file>       EPD or PGN file of start positions, used in order
+    --pgn <file>            Write the finished games to this file
+    --sprt <elo0> <elo1>    Stop as soon as the SPRT decides, alpha and beta are 0.05
+    --repetition <n>        Adjudicate a draw when a position occurs n times, 0 never does (default 3)
+    --name1, --name2 <name> Names used in the PGN, the engine names by default";
+
+#[derive(Copy, Clone)]
+/// How long the engines get to think
+enum TimeControl {
+    Clock { base: Duration, increment: Duration },
+    MoveTime(Duration),
+    Depth(u32),
+}
+
+struct Options {
+    engines: [String; 2],
+    names: [Option<String>; 2],
+    games: usize,
+    concurrency: usize,
+    time_control: TimeControl,
+    openings: Vec<Chess>,
+    pgn: Option<String>,
+    sprt: Option<Sprt>,
+    repetition: Option<usize>,
+}
+
+/// A finished game, engine 0 is the first engine
+struct GameRecord {
+    number: usize,
+    white: usize,
+    chess: Chess,
+    outcome: Outcome,
+    names: [String; 2],
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        engines: [String::new(), String::new()],
+        names: [None, None],
+        games: 2,
+        concurrency: 1,
+        time_control: TimeControl::Clock { base: Duration::from_secs(10), increment: Duration::from_millis(100) },
+        openings: vec![],
+        pgn: None,
+        sprt: None,
+        repetition: Some(3),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--engine1" => options.engines[0] = value()?,
+            "--engine2" => options.engines[1] = value()?,
+            "--name1" => options.names[0] = Some(value()?),
+            "--name2" => options.names[1] = Some(value()?),
+            "--games" => options.games = parse_number(&value()?)?,
+            "--concurrency" => options.concurrency = parse_number::<usize>(&value()?)?.max(1),
+            "--tc" => options.time_control = parse_clock(&value()?)?,
+            "--movetime" => options.time_control = TimeControl::MoveTime(Duration::from_millis(parse_number(&value()?)?)),
+            "--depth" => options.time_control = TimeControl::Depth(parse_number(&value()?)?),
+            "--openings" => {
+                let path = value()?;
+                let text = fs::read_to_string(&path).map_err(|error| format!("Unable to read {}: {}", path, error))?;
+                options.openings = match path.ends_with(".pgn") {
+                    true => pgn::parse_pgn(&text)?.into_iter().map(|game| game.chess).collect(),
+                    false => pgn::parse_epd(&text)?,
+                };
+            },
+            "--pgn" => options.pgn = Some(value()?),
+            "--sprt" => {
+                let elo0 = parse_number(&value()?)?;
+                let elo1 = parse_number(&value()?)?;
+                options.sprt = Some(Sprt { elo0, elo1, ..Default::default() });
+            },
+            "--repetition" => options.repetition = match parse_number(&value()?)? {
+                0 => None,
+                count => Some(count),
+            },
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            _ => return Err(format!("Unknown argument: {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    if options.engines.iter().any(|engine| engine.is_empty()) {
+        return Err(USAGE.to_string());
+    }
+    if options.openings.is_empty() {
+        options.openings.push(Chess::new());
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid number: {}", value))
+}
+
+fn parse_clock(value: &str) -> Result<TimeControl, String> {
+    let (base, increment) = value.split_once('+').unwrap_or((value, "0"));
+    let base: f64 = parse_number(base)?;
+    let increment: f64 = parse_number(increment)?;
+    if base <= 0.0 || increment < 0.0 {
+        return Err(format!("Invalid time control: {}", value));
+    }
+
+    Ok(TimeControl::Clock { base: Duration::from_secs_f64(base), increment: Duration::from_secs_f64(increment) })
+}
+
+/// Play one game between the engines, white is the index of the engine that plays white
+///
+/// The game is adjudicated as a draw once a position occurred repetition times.
+fn play_game(engines: &mut [UciEngine; 2], white: usize, opening: &Chess, time_control: TimeControl, repetition: Option<usize>) -> (Chess, Outcome) {
+    let mut chess = opening.clone();
+    let mut clocks = match time_control {
+        TimeControl::Clock { base, .. } => [base, base],
+        _ => [Duration::ZERO; 2],
+    };
+
+    for engine in engines.iter_mut() {
+        if let Err(error) = engine.new_game() {
+            eprintln!("{}", error);
+        }
+    }
+
+    loop {
+        if let Some(outcome) = chess.get_outcome() {
+            return (chess, outcome);
+        }
+        if repetition.is_some_and(|count| chess.get_repetition_count() >= count) {
+            return (chess, Outcome::draw(Termination::Repetition));
+        }
+
+        let side = chess.get_playing_side();
+        let (index, clock) = match side {
+            Side::White => (white, 0),
+            Side::Black => (1 - white, 1),
+        };
+
+        let limits = match time_control {
+            TimeControl::Clock { increment, .. } => GoLimits {
+                wtime: Some(clocks[0]),
+                btime: Some(clocks[1]),
+                winc: Some(increment),
+                binc: Some(increment),
+                ..Default::default()
+            },
+            TimeControl::MoveTime(movetime) => GoLimits { movetime: Some(movetime), ..Default::default() },
+            TimeControl::Depth(depth) => GoLimits { depth: Some(depth), ..Default::default() },
+        };
+
+        let engine = &mut engines[index];
+        let start = Instant::now();
+        let result = engine.set_position(&chess).and_then(|_| engine.go(limits));
+        let elapsed = start.elapsed();
+
+        let best_move = match result {
+            Ok(result) => result.best_move,
+            Err(error) => {
+                eprintln!("Engine {} failed: {}", index + 1, error);
+                return (chess, Outcome::loss(side, Termination::Forfeit));
+            },
+        };
+
+        if let TimeControl::Clock { increment, .. } = time_control {
+            if elapsed > clocks[clock] + TIME_MARGIN {
+                // a flag fall is only a loss if the opponent is able to win at all
+                let outcome = match chess.has_insufficient_material(side.get_opposite()) {
+                    true => Outcome::draw(Termination::TimeForfeit),
+                    false => Outcome::loss(side, Termination::TimeForfeit),
+                };
+                return (chess, outcome);
+            }
+            clocks[clock] = clocks[clock].saturating_sub(elapsed) + increment;
+        }
+
+        match best_move {
+            Some(best_move) if chess.play_move(best_move) => {},
+            _ => return (chess, Outcome::loss(side, Termination::Forfeit)),
+        }
+    }
+}
+
+fn spawn_engines(options: &Options) -> Result<[UciEngine; 2], String> {
+    let mut engines = [UciEngine::spawn(&options.engines[0], &[])?, UciEngine::spawn(&options.engines[1], &[])?];
+    for engine in engines.iter_mut() {
+        engine.set_timeout(Duration::from_secs(5));
+    }
+
+    Ok(engines)
+}
+
+fn get_names(options: &Options, engines: &[UciEngine; 2]) -> [String; 2] {
+    [0, 1].map(|index| {
+        options.names[index].clone()
+            .or_else(|| engines[index].get_name().map(|name| name.to_string()))
+            .unwrap_or_else(|| options.engines[index].clone())
+    })
+}
+
+fn write_record(file: &mut File, record: &GameRecord, time_control: TimeControl) -> std::io::Result<()> {
+    let termination = match record.outcome.termination {
+        Termination::TimeForfeit => "time forfeit",
+        Termination::Forfeit => "rules infraction",
+        Termination::Repetition => "adjudication",
+        _ => "normal",
+    };
+    let time_control = match time_control {
+        TimeControl::Clock { base, increment } => format!("{}+{}", base.as_secs_f64(), increment.as_secs_f64()),
+        TimeControl::MoveTime(movetime) => format!("{}/move", movetime.as_secs_f64()),
+        TimeControl::Depth(_) => "-".to_string(),
+    };
+
+    let headers = vec![
+        ("Event".to_string(), "Engine match".to_string()),
+        ("Site".to_string(), "?".to_string()),
+        ("Round".to_string(), (record.number + 1).to_string()),
+        ("White".to_string(), record.names[record.white].clone()),
+        ("Black".to_string(), record.names[1 - record.white].clone()),
+        ("Result".to_string(), record.outcome.result.to_pgn().to_string()),
+        ("TimeControl".to_string(), time_control),
+        ("Termination".to_string(), termination.to_string()),
+    ];
+
+    write!(file, "{}", pgn::write_pgn(&headers, &record.chess, record.outcome.result.to_pgn()))
+}
+
+fn print_score(score: &MatchScore, sprt: Option<Sprt>) {
+    let elo = match score.get_elo() {
+        Some((elo, error)) => format!("{:+.1} +/- {:.1}", elo, error),
+        None => "-".to_string(),
+    };
+    print!("Games {}: +{} ={} -{}, score {:.1}%, Elo {}",
+        score.get_games(), score.wins, score.draws, score.losses, score.get_score() * 100.0, elo);
+
+    if let Some(sprt) = sprt {
+        let (lower, upper) = sprt.get_bounds();
+        print!(", LLR {:.2} ({:.2}, {:.2})", sprt.get_llr(score), lower, upper);
+    }
+    println!();
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_options(&args) {
+        Ok(options) => Arc::new(options),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        },
+    };
+
+    let mut pgn_file = match &options.pgn {
+        Some(path) => match File::create(path) {
+            Ok(file) => Some(file),
+            Err(error) => {
+                eprintln!("Unable to create {}: {}", path, error);
+                process::exit(1);
+            },
+        },
+        None => None,
+    };
+
+    let next_game = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..options.concurrency.min(options.games)).map(|_| {
+        let (options, next_game, stop, sender) = (options.clone(), next_game.clone(), stop.clone(), sender.clone());
+        thread::spawn(move || {
+            let mut engines = None;
+            loop {
+                let number = next_game.fetch_add(1, Ordering::Relaxed);
+                if number >= options.games || stop.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                // engines that failed are started again for the next game
+                let mut playing = match engines.take() {
+                    Some(playing) => playing,
+                    None => match spawn_engines(&options) {
+                        Ok(playing) => playing,
+                        Err(error) => {
+                            let _ = sender.send(Err(error));
+                            return;
+                        },
+                    },
+                };
+
+                // every opening is played twice, once with each engine as white
+                let opening = &options.openings[(number / 2) % options.openings.len()];
+                let white = number % 2;
+                let (chess, outcome) = play_game(&mut playing, white, opening, options.time_control, options.repetition);
+                let names = get_names(&options, &playing);
+
+                if outcome.termination != Termination::Forfeit {
+                    engines = Some(playing);
+                }
+                if sender.send(Ok(GameRecord { number, white, chess, outcome, names })).is_err() {
+                    return;
+                }
+            }
+        })
+    }).collect();
+    drop(sender);
+
+    let mut score = MatchScore::default();
+    for record in receiver {
+        let record = match record {
+            Ok(record) => record,
+            Err(error) => {
+                eprintln!("{}", error);
+                stop.store(true, Ordering::Relaxed);
+                continue;
+            },
+        };
+
+        let first_side = match record.white {
+            0 => Side::White,
+            _ => Side::Black,
+        };
+        score.add(match record.outcome.result {
+            GameResult::Draw => None,
+            result => Some(result.get_winner() == Some(first_side)),
+        });
+
+        println!("Game {} ({} vs {}): {} {{{:?}}}", record.number + 1, record.names[record.white],
+            record.names[1 - record.white], record.outcome.result.to_pgn(), record.outcome.termination);
+        print_score(&score, options.sprt);
+
+        if let Some(file) = pgn_file.as_mut() {
+            if let Err(error) = write_record(file, &record, options.time_control) {
+                eprintln!("Unable to write PGN: {}", error);
+            }
+        }
+
+        if let Some(sprt) = options.sprt {
+            match sprt.get_decision(&score) {
+                SprtDecision::AcceptH0 => println!("SPRT: H0 accepted"),
+                SprtDecision::AcceptH1 => println!("SPRT: H1 accepted"),
+                SprtDecision::Continue => continue,
+            }
+            stop.store(true, Ordering::Relaxed);
+            break;
+        }
+    }
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
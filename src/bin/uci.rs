@@ -0,0 +1,281 @@
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chesslib::search::{self, Search, SearchLimits, SearchResult};
+use chesslib::transposition::{TranspositionTable, DEFAULT_HASH_MB};
+use chesslib::{Chess, Dimensions, Move, PieceType, Side};
+
+const NAME: &str = "chesslib";
+const AUTHOR: &str = "moudi";
+const MAX_HASH_MB: usize = 4096;
+const MAX_THREADS: usize = 64;
+const MAX_MULTIPV: usize = 64;
+
+/// UCI front end for the search, reads commands from stdin and writes replies to stdout
+struct Engine {
+    chess: Chess,
+    table: Arc<TranspositionTable>,
+    threads: usize,
+    multipv: usize,
+    chess960: bool,
+    stop: Arc<AtomicBool>,
+    pondering: Arc<AtomicBool>, // the search waits for ponderhit or stop before it sends its move
+    ponder_time: Option<Duration>, // time for the move once the ponder move was played
+    search_thread: Option<JoinHandle<()>>,
+}
+
+impl Engine {
+    fn new() -> Engine {
+        Engine {
+            chess: Chess::new(),
+            table: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            threads: 1,
+            multipv: 1,
+            chess960: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            pondering: Arc::new(AtomicBool::new(false)),
+            ponder_time: None,
+            search_thread: None,
+        }
+    }
+
+    /// Handle one line of input
+    ///
+    /// # Return: If the engine should keep running
+    fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            return true;
+        }
+
+        match tokens[0] {
+            "uci" => {
+                println!("id name {}", NAME);
+                println!("id author {}", AUTHOR);
+                println!("option name Hash type spin default {} min 1 max {}", DEFAULT_HASH_MB, MAX_HASH_MB);
+                println!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS);
+                println!("option name MultiPV type spin default 1 min 1 max {}", MAX_MULTIPV);
+                println!("option name Ponder type check default false");
+                println!("option name UCI_Chess960 type check default false");
+                println!("uciok");
+            },
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.table.clear();
+                self.chess = Chess::new();
+            },
+            "setoption" => {
+                self.stop_search();
+                self.set_option(&tokens[1..]);
+            },
+            "position" => {
+                self.stop_search();
+                if let Err(error) = self.set_position(&tokens[1..]) {
+                    println!("info string {}", error);
+                }
+            },
+            "go" => {
+                self.stop_search();
+                self.go(&tokens[1..]);
+            },
+            "stop" => self.stop_search(),
+            "ponderhit" => self.ponder_hit(),
+            "quit" => {
+                self.stop_search();
+                return false;
+            },
+            "debug" | "register" => {},
+            _ => println!("info string Unknown command: {}", tokens[0]),
+        }
+
+        true
+    }
+
+    fn set_option(&mut self, tokens: &[&str]) {
+        // setoption name <name> value <value>
+        let name_end = tokens.iter().position(|token| *token == "value").unwrap_or(tokens.len());
+        let name = tokens.get(1..name_end).map(|name| name.join(" ")).unwrap_or_default();
+        let value = tokens.get(name_end + 1..).map(|value| value.join(" ")).unwrap_or_default();
+
+        if name.eq_ignore_ascii_case("UCI_Chess960") {
+            self.chess960 = value == "true";
+            return;
+        }
+        // pondering is started by go ponder, the option only tells the interface that it works
+        if name.eq_ignore_ascii_case("Ponder") {
+            return;
+        }
+
+        match (name.to_lowercase().as_str(), value.parse::<usize>()) {
+            ("hash", Ok(mb)) => self.table.resize(mb.clamp(1, MAX_HASH_MB)),
+            ("threads", Ok(threads)) => self.threads = threads.clamp(1, MAX_THREADS),
+            ("multipv", Ok(multipv)) => self.multipv = multipv.clamp(1, MAX_MULTIPV),
+            _ => println!("info string Unknown option or value: {} {}", name, value),
+        }
+    }
+
+    fn set_position(&mut self, tokens: &[&str]) -> Result<(), String> {
+        let moves_start = tokens.iter().position(|token| *token == "moves").unwrap_or(tokens.len());
+
+        let mut chess = match tokens.first() {
+            Some(&"startpos") => Chess::new(),
+            Some(&"fen") => Chess::try_from_fen(tokens[1..moves_start].join(" "))?,
+            _ => return Err("Expected startpos or fen".to_string()),
+        };
+        chess.set_chess960(self.chess960);
+
+        // the search needs exactly one king per side
+        for side in [Side::White, Side::Black] {
+            let kings = chess.get_all_pieces().iter()
+                .filter(|piece| piece.get_piece_type() == PieceType::King && piece.get_color() == side)
+                .count();
+            if kings != 1 {
+                return Err(format!("Position needs one {:?} king", side));
+            }
+        }
+
+        for notation in tokens.iter().skip(moves_start + 1) {
+            let chess_move = Move::from_uci_with(notation, chess.board.get_dimensions())?;
+            if !chess.play_move(chess_move) {
+                return Err(format!("Illegal move: {}", notation));
+            }
+        }
+
+        self.chess = chess;
+        Ok(())
+    }
+
+    fn go(&mut self, tokens: &[&str]) {
+        let mut limits = SearchLimits::default();
+        let (mut infinite, mut ponder) = (false, false);
+        let (mut time_left, mut increment, mut moves_to_go) = (None, Duration::ZERO, None);
+        let (time_key, increment_key) = match self.chess.get_playing_side() {
+            Side::White => ("wtime", "winc"),
+            Side::Black => ("btime", "binc"),
+        };
+
+        let mut iter = tokens.iter();
+        while let Some(token) = iter.next() {
+            let mut value = || iter.next().and_then(|value| value.parse::<i64>().ok()).unwrap_or(0).max(0) as u64;
+            match *token {
+                "depth" => limits.depth = Some(value() as u32),
+                "nodes" => limits.nodes = Some(value()),
+                "movetime" => limits.movetime = Some(Duration::from_millis(value())),
+                "movestogo" => moves_to_go = Some(value() as u32),
+                "infinite" => infinite = true,
+                "ponder" => ponder = true,
+                key if key == time_key => time_left = Some(Duration::from_millis(value())),
+                key if key == increment_key => increment = Duration::from_millis(value()),
+                "wtime" | "btime" | "winc" | "binc" => { value(); },
+                _ => {},
+            }
+        }
+
+        if let (None, Some(time_left)) = (limits.movetime, time_left) {
+            limits.movetime = Some(search::allocate_time(time_left, increment, moves_to_go));
+        }
+
+        // a ponder search has no time limit until ponderhit starts the clock for the move
+        self.ponder_time = match ponder {
+            true => limits.movetime.take(),
+            false => None,
+        };
+
+        // every search gets its own flags, so that a late ponderhit timer does not stop the next one
+        self.stop = Arc::new(AtomicBool::new(false));
+        self.pondering = Arc::new(AtomicBool::new(ponder));
+        let board = self.chess.board.clone();
+        let table = self.table.clone();
+        let (stop, pondering) = (self.stop.clone(), self.pondering.clone());
+        let (threads, multipv) = (self.threads, self.multipv);
+
+        self.search_thread = Some(thread::spawn(move || {
+            // helper threads search the same position and share their results through the table
+            let helper_stop = Arc::new(AtomicBool::new(false));
+            let helpers: Vec<_> = (1..threads).map(|_| {
+                let (board, table, helper_stop) = (board.clone(), table.clone(), helper_stop.clone());
+                thread::spawn(move || {
+                    Search::with_table(limits, helper_stop, table).run(&board);
+                })
+            }).collect();
+
+            let dimensions = board.get_dimensions();
+            let mut search = Search::with_table(limits, stop.clone(), table.clone());
+            search.set_multipv(multipv);
+            let result = search.run_with_info(&board, |info| {
+                println!("{}", format_info(info, table.hashfull(), dimensions));
+            });
+
+            helper_stop.store(true, Ordering::Relaxed);
+            for helper in helpers {
+                let _ = helper.join();
+            }
+
+            // an infinite or ponder search only reports its move once it has been told to stop, or
+            // once the ponder move was played
+            while (infinite || pondering.load(Ordering::Relaxed)) && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            match (result.best_move, result.pv.get(1)) {
+                (Some(best_move), Some(ponder)) => {
+                    println!("bestmove {} ponder {}", best_move.to_uci_with(dimensions), ponder.to_uci_with(dimensions));
+                },
+                (Some(best_move), None) => println!("bestmove {}", best_move.to_uci_with(dimensions)),
+                (None, _) => println!("bestmove 0000"),
+            }
+        }));
+    }
+
+    /// The opponent played the ponder move, so the search goes on as a normal search from now on
+    fn ponder_hit(&mut self) {
+        self.pondering.store(false, Ordering::Relaxed);
+        if let Some(movetime) = self.ponder_time.take() {
+            let stop = self.stop.clone();
+            thread::spawn(move || {
+                thread::sleep(movetime);
+                stop.store(true, Ordering::Relaxed);
+            });
+        }
+    }
+
+    fn stop_search(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(search_thread) = self.search_thread.take() {
+            let _ = search_thread.join();
+        }
+    }
+}
+
+fn format_info(info: &SearchResult, hashfull: usize, dimensions: Dimensions) -> String {
+    let score = match search::mate_in(info.score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", info.score),
+    };
+    let millis = info.time.as_millis().max(1);
+    let pv: Vec<_> = info.pv.iter().map(|chess_move| chess_move.to_uci_with(dimensions)).collect();
+
+    format!("info depth {} multipv {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+        info.depth, info.multipv, score, info.nodes, info.nodes as u128 * 1000 / millis, hashfull, info.time.as_millis(), pv.join(" "))
+}
+
+fn main() {
+    let mut engine = Engine::new();
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        if !engine.handle(&line) {
+            return;
+        }
+    }
+
+    engine.stop_search();
+}
@@ -0,0 +1,303 @@
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chesslib::search::{self, Search, SearchLimits, SearchResult};
+use chesslib::transposition::{TranspositionTable, DEFAULT_HASH_MB};
+use chesslib::{Chess, Dimensions, Move, PieceType, Side, State};
+
+const NAME: &str = "chesslib";
+
+// xboard reports a mate in n moves as 100000 + n
+const XBOARD_MATE: i32 = 100000;
+
+/// Input of the main loop, lines from stdin and finished searches
+enum Event {
+    Line(String),
+    SearchDone(u64, SearchResult),
+}
+
+/// Time control set by level or st
+#[derive(Copy, Clone)]
+enum TimeControl {
+    Conventional { moves: u32, increment: Duration },
+    MoveTime(Duration),
+}
+
+/// XBoard/CECP front end for the search, reads commands from stdin and writes replies to stdout
+struct Engine {
+    chess: Chess,
+    table: Arc<TranspositionTable>,
+    events: Sender<Event>,
+    engine_side: Option<Side>, // None in force mode
+    time_control: TimeControl,
+    depth: Option<u32>,
+    time_left: Duration,
+    post: bool,
+    stop: Arc<AtomicBool>,
+    search_id: u64, // results of older searches are thrown away
+    search_thread: Option<JoinHandle<()>>,
+}
+
+impl Engine {
+    fn new(events: Sender<Event>) -> Engine {
+        Engine {
+            chess: Chess::new(),
+            table: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            events,
+            engine_side: Some(Side::Black),
+            time_control: TimeControl::Conventional { moves: 40, increment: Duration::ZERO },
+            depth: None,
+            time_left: Duration::from_secs(300),
+            post: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            search_id: 0,
+            search_thread: None,
+        }
+    }
+
+    /// Handle one line of input
+    ///
+    /// # Return: If the engine should keep running
+    fn handle(&mut self, line: &str) -> bool {
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            return true;
+        }
+
+        match tokens[0] {
+            "xboard" | "accepted" | "rejected" => {},
+            "protover" => {
+                println!("feature myname=\"{}\" setboard=1 usermove=1 ping=1 sigint=0 sigterm=0 colors=0 done=1", NAME);
+            },
+            "new" => {
+                self.cancel_search();
+                self.table.clear();
+                self.chess = Chess::new();
+                self.engine_side = Some(Side::Black);
+                self.depth = None;
+            },
+            "force" | "result" => {
+                self.cancel_search();
+                self.engine_side = None;
+            },
+            "go" => {
+                self.cancel_search();
+                self.engine_side = Some(self.chess.get_playing_side());
+                self.think();
+            },
+            "usermove" => {
+                self.cancel_search();
+                self.user_move(tokens.get(1).copied().unwrap_or(""));
+            },
+            "setboard" => {
+                self.cancel_search();
+                match parse_position(&tokens[1..].join(" ")) {
+                    Ok(chess) => self.chess = chess,
+                    Err(_) => println!("tellusererror Illegal position"),
+                }
+            },
+            "level" => self.set_level(&tokens[1..]),
+            "st" => {
+                let seconds = tokens.get(1).and_then(|seconds| seconds.parse::<f64>().ok()).unwrap_or(0.0).max(0.0);
+                self.time_control = TimeControl::MoveTime(Duration::from_secs_f64(seconds));
+            },
+            "sd" => self.depth = tokens.get(1).and_then(|depth| depth.parse().ok()),
+            "time" => {
+                // centiseconds left on the engine clock
+                let centiseconds = tokens.get(1).and_then(|time| time.parse::<i64>().ok()).unwrap_or(0).max(0) as u64;
+                self.time_left = Duration::from_millis(centiseconds * 10);
+            },
+            "undo" => {
+                self.cancel_search();
+                self.chess.undo();
+            },
+            "remove" => {
+                self.cancel_search();
+                self.chess.undo();
+                self.chess.undo();
+            },
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "ping" => println!("pong {}", tokens.get(1).unwrap_or(&"")),
+            "?" => self.stop.store(true, Ordering::Relaxed),
+            "quit" => {
+                self.cancel_search();
+                return false;
+            },
+            "otim" | "hard" | "easy" | "random" | "computer" | "name" | "rating" | "ics" | "white" | "black" => {},
+            // protocol version 1 interfaces send moves without the usermove prefix
+            notation if Move::from_uci_with(notation, self.chess.board.get_dimensions()).is_ok() => {
+                self.cancel_search();
+                self.user_move(notation);
+            },
+            _ => println!("Error (unknown command): {}", tokens[0]),
+        }
+
+        true
+    }
+
+    fn user_move(&mut self, notation: &str) {
+        let legal = Move::from_uci_with(notation, self.chess.board.get_dimensions()).is_ok_and(|chess_move| self.chess.play_move(chess_move));
+        if !legal {
+            println!("Illegal move: {}", notation);
+            return;
+        }
+
+        if !self.report_result() && self.engine_side == Some(self.chess.get_playing_side()) {
+            self.think();
+        }
+    }
+
+    fn set_level(&mut self, tokens: &[&str]) {
+        // level MPS BASE INC, where BASE is minutes or minutes:seconds
+        if tokens.len() < 3 {
+            println!("Error (expected level MPS BASE INC): level");
+            return;
+        }
+
+        let moves = tokens[0].parse().unwrap_or(0);
+        let base = match tokens[1].split_once(':') {
+            Some((minutes, seconds)) => minutes.parse::<u64>().unwrap_or(0) * 60 + seconds.parse::<u64>().unwrap_or(0),
+            None => tokens[1].parse::<u64>().unwrap_or(0) * 60,
+        };
+        let increment = tokens[2].parse::<f64>().unwrap_or(0.0).max(0.0);
+
+        self.time_control = TimeControl::Conventional { moves, increment: Duration::from_secs_f64(increment) };
+        self.time_left = Duration::from_secs(base);
+    }
+
+    fn think(&mut self) {
+        let limits = SearchLimits {
+            depth: self.depth,
+            nodes: None,
+            movetime: Some(match self.time_control {
+                TimeControl::MoveTime(movetime) => movetime,
+                TimeControl::Conventional { moves, increment } => {
+                    // the moves of the period are counted from the fullmove number, which a position
+                    // set with setboard does not start at 1
+                    let moves_to_go = match moves {
+                        0 => None,
+                        moves => Some(moves - (self.chess.get_fullmove_number().max(1) - 1) % moves),
+                    };
+                    search::allocate_time(self.time_left, increment, moves_to_go)
+                },
+            }),
+        };
+
+        self.search_id += 1;
+        self.stop = Arc::new(AtomicBool::new(false));
+        let (id, board, table, stop, events, post) =
+            (self.search_id, self.chess.board.clone(), self.table.clone(), self.stop.clone(), self.events.clone(), self.post);
+
+        self.search_thread = Some(thread::spawn(move || {
+            let mut search = Search::with_table(limits, stop, table);
+            let result = search.run_with_info(&board, |info| {
+                if post {
+                    println!("{}", format_thinking(info, board.get_dimensions()));
+                }
+            });
+            let _ = events.send(Event::SearchDone(id, result));
+        }));
+    }
+
+    fn search_done(&mut self, id: u64, result: SearchResult) {
+        if id != self.search_id {
+            return;
+        }
+        if let Some(search_thread) = self.search_thread.take() {
+            let _ = search_thread.join();
+        }
+
+        match result.best_move {
+            Some(best_move) if self.chess.play_move(best_move) => {
+                println!("move {}", best_move.to_uci_with(self.chess.board.get_dimensions()));
+                self.report_result();
+            },
+            _ => { self.report_result(); },
+        }
+    }
+
+    /// Print the result if the game is over
+    ///
+    /// # Return: If the game is over
+    fn report_result(&self) -> bool {
+        let result = match (self.chess.get_state(), self.chess.get_playing_side()) {
+            (State::Checkmate, Side::White) => "0-1 {Black mates}",
+            (State::Checkmate, Side::Black) => "1-0 {White mates}",
+            (State::Stalemate, _) => "1/2-1/2 {Stalemate}",
+            (State::Draw, _) => "1/2-1/2 {Draw}",
+            _ => return false,
+        };
+
+        println!("{}", result);
+        true
+    }
+
+    /// Stop the running search and throw its result away
+    fn cancel_search(&mut self) {
+        self.search_id += 1;
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(search_thread) = self.search_thread.take() {
+            let _ = search_thread.join();
+        }
+    }
+}
+
+fn parse_position(fen: &str) -> Result<Chess, String> {
+    let chess = Chess::try_from_fen(fen.to_string())?;
+
+    // the search needs exactly one king per side
+    for side in [Side::White, Side::Black] {
+        let kings = chess.get_all_pieces().iter()
+            .filter(|piece| piece.get_piece_type() == PieceType::King && piece.get_color() == side)
+            .count();
+        if kings != 1 {
+            return Err(format!("Position needs one {:?} king", side));
+        }
+    }
+
+    Ok(chess)
+}
+
+fn format_thinking(info: &SearchResult, dimensions: Dimensions) -> String {
+    let score = match search::mate_in(info.score) {
+        Some(moves) if moves > 0 => XBOARD_MATE + moves,
+        Some(moves) => -XBOARD_MATE + moves,
+        None => info.score,
+    };
+    let pv: Vec<_> = info.pv.iter().map(|chess_move| chess_move.to_uci_with(dimensions)).collect();
+
+    // ply score time nodes pv, with the time in centiseconds
+    format!("{} {} {} {} {}", info.depth, score, info.time.as_millis() / 10, info.nodes, pv.join(" "))
+}
+
+fn main() {
+    let (sender, receiver) = mpsc::channel();
+    let mut engine = Engine::new(sender.clone());
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            match line {
+                Ok(line) => if sender.send(Event::Line(line)).is_err() { break },
+                Err(_) => break,
+            }
+        }
+        let _ = sender.send(Event::Line("quit".to_string()));
+    });
+
+    for event in receiver {
+        match event {
+            Event::Line(line) => {
+                if !engine.handle(&line) {
+                    break;
+                }
+            },
+            Event::SearchDone(id, result) => engine.search_done(id, result),
+        }
+        let _ = io::stdout().flush();
+    }
+}
@@ -1,28 +1,39 @@
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Mul, Not};
 use std::fmt::Display;
 
-use crate::{Board, NUM_INDECES};
+use crate::{Board, Dimensions, NUM_INDECES};
 
 #[derive(Copy, Clone)]
-pub struct BitBoard (u64);
+// one width for every board, so that boards of all dimensions are the same type behind a variant
+pub struct BitBoard (u128);
 
 pub const EMPTY : BitBoard = BitBoard(0);
 
+// shifting a 64 bit word and picking the half is much cheaper than a 128 bit shift by a variable
+#[inline]
+fn square_mask(index: usize) -> u128 {
+    let bit = (1u64 << (index & 63)) as u128;
+    match index < 64 {
+        true => bit,
+        false => bit << 64,
+    }
+}
+
 impl BitBoard {
     #[inline]
     pub fn set(&mut self, index: usize, value: bool) {
         if value {
-            self.0 |= 1u64 << index;
+            self.0 |= square_mask(index);
         } else {
-            self.0 &= !(1u64 << index);
+            self.0 &= !square_mask(index);
         }
     }
 
-    pub fn predicate_and_set<F>(&mut self, x: usize, y: usize, predicate: F) -> bool
+    pub fn predicate_and_set<F>(&mut self, board: &Board, x: usize, y: usize, predicate: F) -> bool
         where F: Fn(usize, usize) -> bool
     {
-        if Board::is_inbounds(x, y) && predicate(x, y) {
-            self.set((y * 8) + x, true);
+        if board.is_inbounds(x, y) && predicate(x, y) {
+            self.set(board.get_index(x, y), true);
             return true;
         }
 
@@ -30,23 +41,50 @@ impl BitBoard {
     }
 
     pub fn is_empty_on_board_and_set(&mut self, board: &Board, x: usize, y: usize) -> bool {
-        self.predicate_and_set(x, y, |x, y| board.is_empty(y * 8 + x))
+        self.predicate_and_set(board, x, y, |x, y| board.is_empty(board.get_index(x, y)))
     }
 
-    pub fn compare_and_set(&mut self, compare: Self, compare_value: bool, x: usize, y: usize) -> bool {
-        self.predicate_and_set(x, y, |x, y| compare.get(y * 8 + x) == compare_value)
-    }
-    
     #[inline]
     pub fn get(&self, index: usize) -> bool {
-        debug_assert!(index < 64);
-        (self.0 & (1u64 << index)) != 0
+        debug_assert!(index < NUM_INDECES);
+        (self.0 & square_mask(index)) != 0
     }
 
-    pub fn to_number(&self) -> u64 {
+    pub fn to_number(self) -> u128 {
         self.0
     }
 
+    pub fn from_number(number: u128) -> Self {
+        BitBoard(number)
+    }
+
+    #[inline]
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Draw the squares as a board of dimensions, one rank per line with the files below
+    ///
+    /// # Example:
+    /// ```
+    /// let board = BitBoard::from_number(1 << 79);
+    /// assert!(board.to_string_with(Dimensions::new(10, 8).unwrap()).starts_with("8|0000000001\n"));
+    /// ```
+    pub fn to_string_with(self, dimensions: Dimensions) -> String {
+        let (width, height) = (dimensions.get_width(), dimensions.get_height());
+        let label_width = height.to_string().len();
+
+        let mut text = String::new();
+        for y in (0..height).rev() {
+            let squares: String = (0..width).map(|x| if self.get(dimensions.get_index(x, y)) { '1' } else { '0' }).collect();
+            text.push_str(&format!("{:>1$}|{2}\n", y + 1, label_width, squares));
+        }
+
+        let files: String = (0..width).map(|x| (x as u8 + b'A') as char).collect();
+        text.push_str(&format!("{}{}\n", " ".repeat(label_width + 1), files));
+        text
+    }
+
     pub fn filter_on<F>(&mut self, predicate: F) -> Self 
         where F: Fn(usize) -> bool
     {
@@ -65,7 +103,7 @@ impl Iterator for BitBoard {
 
     fn next(&mut self) -> Option<Self::Item> {
         let index = self.0.leading_zeros() as usize;
-        if index == 64 {
+        if index == NUM_INDECES {
             return None;
         }
 
@@ -75,12 +113,9 @@ impl Iterator for BitBoard {
 }
 
 impl Display for BitBoard {
+    /// Shown as a standard board, use to_string_with for boards of other dimensions
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, byte) in self.0.to_be_bytes().iter().enumerate() {
-            write!(f, "{}|{:08b}\n", 8 - i, byte.reverse_bits());
-        }
-
-        write!(f, "  ABCDEFGH\n")
+        write!(f, "{}", self.to_string_with(Dimensions::STANDARD))
     }
 }
 
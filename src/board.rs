@@ -1,17 +1,23 @@
-use core::panic;
-use std::convert::From;
+use std::any::Any;
+use std::sync::Arc;
 
 use crate::pieces::*;
 use crate::bitboard::{self, *};
-use crate::file::*;
+use crate::variant::{self, Standard, Variant, VariantState};
+use crate::Dimensions;
 
 mod piece;
 mod analysis;
 mod bitboards;
 mod state;
+mod movegen;
+mod zobrist;
+pub(crate) use zobrist::{get_checks_key, get_pocket_key, get_promoted_key};
+mod see;
 
-const NUM_PIECES: usize = 6;
-pub(crate) const NUM_INDECES: usize = 64;
+const NUM_PIECES: usize = 11;
+// the most squares of any board, see Dimensions
+pub(crate) const NUM_INDECES: usize = 128;
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 /// The different player sides
@@ -27,59 +33,75 @@ impl Side {
             Side::Black => Side::White,
         }
     }
+
+    /// Get the index of the side in arrays that hold something per side, 0 for white and 1 for black
+    pub fn to_index(self) -> usize {
+        match self {
+            Side::White => 0,
+            Side::Black => 1,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-pub(crate) enum CastlingAbility {
-    None,
+/// The two ways to castle, towards the h-file or towards the a-file
+pub(crate) enum CastlingSide {
     King,
     Queen,
-    Both,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+/// Castling rights of one side, stored as the file of the rook that is allowed to castle
+pub(crate) struct CastlingAbility {
+    king: Option<usize>,
+    queen: Option<usize>,
 }
 
 impl CastlingAbility {
-    fn add(&mut self, rhs: Self) {
-        *self = match *self {
-            CastlingAbility::None => rhs,
-            _ => CastlingAbility::Both
-        };
+    fn add(&mut self, castling_side: CastlingSide, rook_file: usize) {
+        match castling_side {
+            CastlingSide::King => self.king = Some(rook_file),
+            CastlingSide::Queen => self.queen = Some(rook_file),
+        }
     }
 
-    fn inverse(&self) -> CastlingAbility {
-        match *self {
-            CastlingAbility::King => CastlingAbility::Queen,
-            CastlingAbility::Queen => CastlingAbility::King,
-            _ => panic!("Unable to inverse none or both castling ability"),
+    /// Remove the right that belongs to the rook on rook_file
+    pub fn remove_file(&mut self, rook_file: usize) {
+        if self.king == Some(rook_file) {
+            self.king = None;
+        }
+        if self.queen == Some(rook_file) {
+            self.queen = None;
         }
     }
 
-    pub fn remove(&mut self, castling_side: CastlingAbility) {
-        match *self {
-            CastlingAbility::None => *self = CastlingAbility::None,
-            CastlingAbility::Both => *self = castling_side.inverse(),
-            _ => {
-                if castling_side == *self {
-                    *self = CastlingAbility::None;
-                }
-            }
-        }
+    pub fn has(&self, castling_side: CastlingSide) -> bool {
+        self.get_rook_file(castling_side).is_some()
     }
 
-    pub fn has(&self, castling: CastlingAbility) -> bool {
-        *self == CastlingAbility::Both || *self == castling
+    pub fn get_rook_file(&self, castling_side: CastlingSide) -> Option<usize> {
+        match castling_side {
+            CastlingSide::King => self.king,
+            CastlingSide::Queen => self.queen,
+        }
     }
 }
 
+#[derive(Clone)]
 /// All information about a chess board
 pub struct Board {
     pieces: [ BitBoard; NUM_PIECES ], // piece placement
     white: BitBoard,    // placement of all white pieces
     black: BitBoard,    // placement of all black pieces
+    dimensions: Dimensions, // number of files and ranks
     side: Side,         // side to move
     castling: [CastlingAbility; 2], // castling rights [0: white, 1: black]
+    chess960: bool,     // castling moves are written as the king taking its own rook
     ep_target: Option<i8>, // en passant target square
     moves_to_50: i8,    // halfmove clock
     move_counter: u32,  // fullmove clock
+    variant: Arc<dyn Variant>, // rules the board is played with
+    variant_state: Option<Box<dyn VariantState>>, // state of the position that only the variant uses
 
     white_attacking_king: Vec<Piece>, // all black pieces attacking white king
     black_attacking_king: Vec<Piece>, // all white pieces attacking black king
@@ -92,7 +114,7 @@ pub struct Board {
 impl Default for Board {
     /// Get the default chess board layout for your chess board
     fn default() -> Board {
-        Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()).unwrap()
+        Board::from_fen(variant::STANDARD_FEN.to_string()).unwrap()
     }
 }
 
@@ -102,11 +124,15 @@ impl Board {
             pieces: [bitboard::EMPTY; NUM_PIECES],
             white: bitboard::EMPTY,
             black: bitboard::EMPTY,
+            dimensions: Dimensions::STANDARD,
             side: Side::White,
-            castling: [CastlingAbility::None, CastlingAbility::None],
+            castling: [CastlingAbility::default(); 2],
+            chess960: false,
             ep_target: None,
             moves_to_50: 0,
             move_counter: 0,
+            variant: Arc::new(Standard),
+            variant_state: None,
 
             white_attacking_king: vec![],
             black_attacking_king: vec![],
@@ -118,29 +144,53 @@ impl Board {
     }
 
     pub(crate) fn from_fen(fen: String) -> Result<Board, String> {
+        Board::from_fen_variant(fen, Arc::new(Standard))
+    }
+
+    /// Create a board of a variant from a FEN, the variant reads the state it adds to the FEN
+    pub(crate) fn from_fen_variant(fen: String, variant: Arc<dyn Variant>) -> Result<Board, String> {
         let mut board = Board::new();
+        let fen = variant.parse_fen(&fen, &mut board.variant_state)?;
+        board.dimensions = variant.get_dimensions();
+        board.variant = variant;
         let parts : Vec<_> = fen.split_whitespace().collect();
+        if parts.len() < 4 {
+            return Err(format!("Invalid FEN, expected at least 4 fields: {}", fen));
+        }
  
+        let (width, height) = (board.dimensions.get_width(), board.dimensions.get_height());
+        if parts[0].split('/').count() != height {
+            return Err(format!("Invalid FEN, expected {} ranks: {}", height, parts[0]));
+        }
+
         for (rank, line) in parts[0].split('/').rev().enumerate() {
             let mut file = 0;
-            for c in line.chars() {
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                // empty squares are counted with as many digits as needed, like the 10 of a 10 file board
+                let squares = match c {
+                    '1'..='9' => {
+                        let mut empty = c.to_digit(10).unwrap() as usize;
+                        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                            empty = empty * 10 + digit as usize;
+                            chars.next();
+                        }
+                        empty
+                    },
+                    _ => 1,
+                };
+                if file + squares > width {
+                    return Err(format!("Invalid FEN, too many squares on rank {}", rank + 1));
+                }
                 match c {
-                    'p' => board.set_piece(rank * 8 + file, PieceType::Pawn, Side::Black, true),
-                    'n' => board.set_piece(rank * 8 + file, PieceType::Knight, Side::Black, true),
-                    'b' => board.set_piece(rank * 8 + file, PieceType::Bishop, Side::Black, true),
-                    'r' => board.set_piece(rank * 8 + file, PieceType::Rook, Side::Black, true),
-                    'q' => board.set_piece(rank * 8 + file, PieceType::Queen, Side::Black, true),
-                    'k' => board.set_piece(rank * 8 + file, PieceType::King, Side::Black, true),
-                    'P' => board.set_piece(rank * 8 + file, PieceType::Pawn, Side::White, true),
-                    'N' => board.set_piece(rank * 8 + file, PieceType::Knight, Side::White, true),
-                    'B' => board.set_piece(rank * 8 + file, PieceType::Bishop, Side::White, true),
-                    'R' => board.set_piece(rank * 8 + file, PieceType::Rook, Side::White, true),
-                    'Q' => board.set_piece(rank * 8 + file, PieceType::Queen, Side::White, true),
-                    'K' => board.set_piece(rank * 8 + file, PieceType::King, Side::White, true),
-                    '0'..='8' => file += c.to_digit(10).unwrap() as usize - 1,
+                    '1'..='9' => {},
+                    _ if PieceType::from_char(c) != PieceType::Empty => {
+                        let side = if c.is_ascii_uppercase() { Side::White } else { Side::Black };
+                        board.set_piece(board.get_index(file, rank), PieceType::from_char(c), side, true);
+                    },
                     _ => return Err(format!("Invalid FEN notation: {}", c)),
                 }
-                file += 1
+                file += squares;
             }
         }
 
@@ -150,35 +200,298 @@ impl Board {
             _ => return Err("Invalid FEN notation 2".to_string()),
         }
         
-        for c in parts[2].chars() {
-            match c {
-                'K' => board.castling[0].add(CastlingAbility::King),
-                'Q' => board.castling[0].add(CastlingAbility::Queen),
-                'k' => board.castling[1].add(CastlingAbility::King),
-                'q' => board.castling[1].add(CastlingAbility::Queen),
-                '-' => break, // this should be handled in the new board initialization
-                _ => return Err("Invalid FEN notation 3".to_string()),
-            }
-        }
+        board.parse_castling(parts[2])?;
 
-        if parts[3].len() != 1 {
-            let chars : Vec<_> = parts[3].chars().collect();
-            let file = File::from(chars[0]) as i8;
-            let rank = (chars[1].to_digit(10).unwrap() as i8) - 1;
-            board.ep_target = Some(rank * 8 + file);
+        if parts[3] != "-" {
+            let square = board.dimensions.square_from_str(parts[3]).ok_or(format!("Invalid en passant square: {}", parts[3]))?;
+            let (file, rank) = board.get_coordinates(square);
+            // the target is stored as the square of the pawn that moved two steps
+            let pawn_rank = board.variant.get_pawn_rank();
+            board.ep_target = match rank {
+                _ if rank == pawn_rank + 1 => Some(board.get_index(file, rank + 1) as i8),
+                _ if rank + pawn_rank + 2 == height => Some(board.get_index(file, rank - 1) as i8),
+                _ => return Err(format!("Invalid en passant square: {}", parts[3])),
+            };
         }
         
-        board.move_counter = parts[4].parse().unwrap();
+        // the move clocks are optional
+        if let Some(halfmoves) = parts.get(4) {
+            let halfmoves: u32 = halfmoves.parse().map_err(|_| format!("Invalid halfmove clock: {}", halfmoves))?;
+            board.moves_to_50 = halfmoves.min(i8::MAX as u32) as i8;
+        }
+        board.move_counter = match parts.get(5) {
+            Some(fullmoves) => fullmoves.parse().map_err(|_| format!("Invalid fullmove number: {}", fullmoves))?,
+            None => 1,
+        };
 
-        board.calculate_attacking_and_attacked(board.side);
-        board.calculate_pinned_pieces(board.side);
+        board.update_all_calculations();
 
         Ok(board)
     }
 
+    /// Get the FEN of the board, castling rights of Chess960 positions are written as X-FEN
+    pub(crate) fn to_fen(&self) -> String {
+        self.to_fen_with(false)
+    }
+
+    /// Get the FEN of the board with the castling rights written as rook files
+    pub(crate) fn to_shredder_fen(&self) -> String {
+        self.to_fen_with(true)
+    }
+
+    fn to_fen_with(&self, shredder: bool) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..self.dimensions.get_height()).rev() {
+            let mut empty = 0;
+            for file in 0..self.dimensions.get_width() {
+                let index = self.get_index(file, rank);
+                let piece = self.get_piece_type_at_pos(index);
+                if piece == PieceType::Empty {
+                    empty += 1;
+                    continue;
+                }
+
+                if empty != 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                fen.push(match self.white.get(index) {
+                    true => piece.to_char().to_ascii_uppercase(),
+                    false => piece.to_char(),
+                });
+            }
+
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push_str(match self.side {
+            Side::White => " w ",
+            Side::Black => " b ",
+        });
+
+        fen.push_str(&self.castling_to_string(shredder));
+
+        // the en passant target is the square behind the pawn that moved two steps
+        match self.ep_target {
+            Some(ep_index) => {
+                let target = match self.side {
+                    Side::White => ep_index as usize + self.dimensions.get_width(),
+                    Side::Black => ep_index as usize - self.dimensions.get_width(),
+                };
+                fen.push_str(&format!(" {}", self.dimensions.square_to_string(target)));
+            },
+            None => fen.push_str(" -"),
+        }
+
+        fen.push_str(&format!(" {} {}", self.moves_to_50, self.move_counter));
+        self.variant.write_fen(self, fen)
+    }
+
+    /// Parse the castling field of a FEN, which is either KQkq, X-FEN or Shredder-FEN
+    ///
+    /// KQkq stand for the outermost rook on that side of the king, file letters for the rook on
+    /// that file. Rights without a king and rook on the back rank are ignored.
+    fn parse_castling(&mut self, field: &str) -> Result<(), String> {
+        if field == "-" {
+            return Ok(());
+        }
+
+        let dimensions = self.dimensions;
+        let (width, height) = (dimensions.get_width(), dimensions.get_height());
+        for c in field.chars() {
+            let (side, color, back_rank) = match c.is_ascii_uppercase() {
+                true => (Side::White, 0, 0),
+                false => (Side::Black, 1, height - 1),
+            };
+            let king_file = match (0..width).find(|file| self.get_piece_board(PieceType::King, side).get(self.get_index(*file, back_rank))) {
+                Some(king_file) => king_file,
+                None => continue,
+            };
+            let rooks = self.get_piece_board(PieceType::Rook, side);
+            let is_rook = |file: &usize| rooks.get(dimensions.get_index(*file, back_rank));
+
+            let rook_file = match c.to_ascii_lowercase() {
+                'k' => (king_file + 1..width).rev().find(is_rook),
+                'q' => (0..king_file).find(is_rook),
+                file @ 'a'..='p' if (file as usize - 'a' as usize) < width => {
+                    self.chess960 = true;
+                    Some(file as usize - 'a' as usize).filter(is_rook)
+                },
+                _ => return Err(format!("Invalid castling rights: {}", field)),
+            };
+
+            if let Some(rook_file) = rook_file {
+                let castling_side = match rook_file > king_file {
+                    true => CastlingSide::King,
+                    false => CastlingSide::Queen,
+                };
+                self.castling[color].add(castling_side, rook_file);
+
+                // only the standard start squares are able to castle in standard chess
+                let standard_file = match castling_side {
+                    CastlingSide::King => width - 1,
+                    CastlingSide::Queen => 0,
+                };
+                if king_file != width / 2 || rook_file != standard_file {
+                    self.chess960 = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn castling_to_string(&self, shredder: bool) -> String {
+        let mut castling = String::new();
+
+        let width = self.dimensions.get_width();
+        for (side, color, back_rank) in [(Side::White, 0, 0), (Side::Black, 1, self.dimensions.get_height() - 1)] {
+            let rooks = self.get_piece_board(PieceType::Rook, side);
+            for (castling_side, letter) in [(CastlingSide::King, 'k'), (CastlingSide::Queen, 'q')] {
+                let rook_file = match self.castling[color].get_rook_file(castling_side) {
+                    Some(rook_file) => rook_file,
+                    None => continue,
+                };
+
+                // X-FEN only uses the file when another rook is further out on the same side
+                let outer_files = match castling_side {
+                    CastlingSide::King => rook_file + 1..width,
+                    CastlingSide::Queen => 0..rook_file,
+                };
+                let is_outermost = !outer_files.into_iter().any(|file| rooks.get(self.get_index(file, back_rank)));
+                let c = match shredder || (self.chess960 && !is_outermost) {
+                    true => (b'a' + rook_file as u8) as char,
+                    false => letter,
+                };
+
+                castling.push(match side {
+                    Side::White => c.to_ascii_uppercase(),
+                    Side::Black => c,
+                });
+            }
+        }
+
+        match castling.is_empty() {
+            true => "-".to_string(),
+            false => castling,
+        }
+    }
+
+    /// Check if a move is castling, and towards which side
+    ///
+    /// In Chess960 castling is written as the king taking its own rook, in standard chess as the
+    /// king moving more than one square along the back rank
+    pub(crate) fn get_castling_side(&self, start: usize, end: usize) -> Option<CastlingSide> {
+        if self.get_piece_type_at_pos(start) != PieceType::King {
+            return None;
+        }
+
+        let side = match self.white.get(start) {
+            true => Side::White,
+            false => Side::Black,
+        };
+        let is_castling = match self.chess960 {
+            true => self.get_piece_board(PieceType::Rook, side).get(end),
+            false => {
+                let ((start_x, start_y), (end_x, end_y)) = (self.get_coordinates(start), self.get_coordinates(end));
+                start_x.abs_diff(end_x) >= 2 && start_y == end_y
+            },
+        };
+
+        match (is_castling, end > start) {
+            (false, _) => None,
+            (true, true) => Some(CastlingSide::King),
+            (true, false) => Some(CastlingSide::Queen),
+        }
+    }
+
+    /// Get the files the king and the rook end on when castling towards castling_side, next to
+    /// the corner like the g- and f-file or the c- and d-file of a standard board
+    pub(crate) fn get_castling_files(&self, castling_side: CastlingSide) -> (usize, usize) {
+        let width = self.dimensions.get_width();
+        match castling_side {
+            CastlingSide::King => (width - 2, width - 3),
+            CastlingSide::Queen => (2, 3),
+        }
+    }
+
+    /// Check if every castling right belongs to a king on the middle file and a rook in the corner
+    pub(crate) fn has_standard_castling(&self) -> bool {
+        let (width, height) = (self.dimensions.get_width(), self.dimensions.get_height());
+        [(Side::White, 0), (Side::Black, 1)].iter().all(|(side, color)| {
+            let castling = self.castling[*color];
+            let back_rank = if *color == 0 { 0 } else { height - 1 };
+            let king_in_middle = self.get_piece_board(PieceType::King, *side).get(self.get_index(width / 2, back_rank));
+            (castling == CastlingAbility::default() || king_in_middle)
+                && castling.get_rook_file(CastlingSide::King).is_none_or(|file| file == width - 1)
+                && castling.get_rook_file(CastlingSide::Queen).is_none_or(|file| file == 0)
+        })
+    }
+
+    #[inline]
+    pub(crate) fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
     #[inline]
-    pub(crate) fn is_inbounds(x: usize, y: usize) -> bool {
-        (0..8).contains(&x) && (0..8).contains(&y)
+    pub(crate) fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
+    #[inline]
+    /// Get the variant the board is played with
+    pub fn get_variant(&self) -> &Arc<dyn Variant> {
+        &self.variant
+    }
+
+    /// Get the state the variant adds to the position, None if it has none of type T
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new_variant(Arc::new(ThreeCheck)).unwrap();
+    /// let checks = chess.board.get_variant_state::<ThreeCheckState>().unwrap().get_checks(Side::White);
+    /// ```
+    pub fn get_variant_state<T: VariantState>(&self) -> Option<&T> {
+        let state: &dyn Any = self.variant_state.as_deref()?;
+        state.downcast_ref()
+    }
+
+    /// Get the state the variant adds to the position to change it, None if it has none of type T
+    pub(crate) fn get_variant_state_mut<T: VariantState>(&mut self) -> Option<&mut T> {
+        let state: &mut dyn Any = self.variant_state.as_deref_mut()?;
+        state.downcast_mut()
+    }
+
+    /// Get the pieces in hand of side, empty in variants without drops
+    pub(crate) fn get_pocket(&self, side: Side) -> [u8; 6] {
+        self.variant_state.as_ref().map_or([0; 6], |state| state.get_pocket(side))
+    }
+
+    #[inline]
+    /// Get the number of files and ranks of the board
+    pub fn get_dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    #[inline]
+    pub(crate) fn is_inbounds(&self, x: usize, y: usize) -> bool {
+        self.dimensions.is_inbounds(x, y)
+    }
+
+    #[inline]
+    pub(crate) fn get_index(&self, x: usize, y: usize) -> usize {
+        self.dimensions.get_index(x, y)
+    }
+
+    #[inline]
+    pub(crate) fn get_coordinates(&self, index: usize) -> (usize, usize) {
+        self.dimensions.get_coordinates(index)
     }
 
     #[inline]
@@ -192,7 +505,7 @@ impl Board {
     }
 
     #[inline]
-    pub(crate) fn get_playing_side(&self) -> Side {
+    pub fn get_playing_side(&self) -> Side {
         self.side
     }
 
@@ -203,16 +516,15 @@ impl Board {
         }
     }
 
-    #[inline]
-    pub(crate) fn get_combined_piece_board(&self) -> BitBoard {
-        self.white | self.black
-    }
-
     #[inline]
     pub(crate) fn get_moves_to_50(&self) -> i8 {
         self.moves_to_50
     }
 
+    pub(crate) fn get_move_counter(&self) -> u32 {
+        self.move_counter
+    }
+
     pub(crate) fn get_side_computed_boards(&self, side: Side) -> (BitBoard, &Vec<Piece>, BitBoard) {
         match side {
             Side::White => (self.white_pinned, &self.white_attacking_king, self.white_attacked),
@@ -221,8 +533,8 @@ impl Board {
     }
 
     fn take_en_passant(&mut self, piece: &Piece, ep_index: i8) {
-        let index = (piece.get_pos_as_usize().1 as i8) * 8 + (ep_index % 8);
-        self.set_piece(index as usize, piece.get_piece_type(), piece.get_color().get_opposite(), false);
+        let index = self.get_index(self.get_coordinates(ep_index as usize).0, piece.get_pos_as_usize().1);
+        self.set_piece(index, piece.get_piece_type(), piece.get_color().get_opposite(), false);
     }
     
 }
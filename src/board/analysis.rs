@@ -1,20 +1,25 @@
 use crate::{bitboard, CoordinateIterator};
+use crate::pieces::{get_fairy_attacks, FAIRY_PIECES};
 
-use super::{BitBoard, Board, Piece, PieceType, Side};
+use super::{BitBoard, Board, Piece, PieceType, Side, NUM_INDECES};
+
+const KNIGHT_DELTAS: [(isize, isize); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+// alternating straight and diagonal directions, starting with NORTH
+const KING_DELTAS: [(isize, isize); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
 
 impl Board {
     
     fn add_pinned(&self, pinned: &mut BitBoard, it: CoordinateIterator, opponent_pinner: BitBoard, sides_board: BitBoard) {
         let board = self.all_pieces_bitboard();
-        let mut first_piece_index = 65;
+        let mut first_piece_index = NUM_INDECES;
 
         for (x, y) in it {
-            let index = y * 8 + x;
+            let index = self.get_index(x, y);
 
             if !board.get(index) {}
-            else if first_piece_index == 65 && sides_board.get(index) {
-                first_piece_index = index as usize;
-            } else if first_piece_index != 65 && opponent_pinner.get(index) {
+            else if first_piece_index == NUM_INDECES && sides_board.get(index) {
+                first_piece_index = index;
+            } else if first_piece_index != NUM_INDECES && opponent_pinner.get(index) {
                 pinned.set(first_piece_index, true);
                 break;
             }
@@ -22,24 +27,34 @@ impl Board {
     }
 
     pub(crate) fn calculate_pinned_pieces(&mut self, side: Side) {
+        // nothing is pinned without a king, like after it exploded in Atomic
+        if self.get_piece_board(PieceType::King, side).count() == 0 {
+            match side {
+                Side::White => self.white_pinned = bitboard::EMPTY,
+                Side::Black => self.black_pinned = bitboard::EMPTY,
+            }
+            return;
+        }
+
         let sides_board = self.get_sides_board(side);
         let opponent = self.get_sides_board(side.get_opposite());
         
         let king_index = self.get_king(side);
-        let king_pos = (king_index % 8, king_index / 8);
+        let king_pos = self.get_coordinates(king_index);
+        let (right, top) = (self.dimensions.get_width() - 1, self.dimensions.get_height() - 1);
 
         let mut pinned = bitboard::EMPTY;
         let straight_pieces = (self.pieces[PieceType::Rook.to_value()] | self.pieces[PieceType::Queen.to_value()]) & opponent;
         let diagonal_pieces = (self.pieces[PieceType::Bishop.to_value()] | self.pieces[PieceType::Queen.to_value()] ) & opponent;
  
         // NORTH
-        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (king_pos.0, 7)), straight_pieces, sides_board);
+        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (king_pos.0, top)), straight_pieces, sides_board);
         // NORTH-EAST
-        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (7, 7)), diagonal_pieces, sides_board);
+        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (right, top)), diagonal_pieces, sides_board);
         // EAST
-        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (7, king_pos.1)), straight_pieces, sides_board);
+        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (right, king_pos.1)), straight_pieces, sides_board);
         // SOUTH-EAST
-        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (7, 0)), diagonal_pieces, sides_board);
+        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (right, 0)), diagonal_pieces, sides_board);
         // SOUTH
         self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (king_pos.0, 0)), straight_pieces, sides_board);
         // SOUTH-WEST
@@ -47,7 +62,7 @@ impl Board {
         // WEST
         self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (0, king_pos.1)), straight_pieces, sides_board);
         // NORTH-WEST
-        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (0, 7)), diagonal_pieces, sides_board);
+        self.add_pinned(&mut pinned, CoordinateIterator::from_to(king_pos, (0, top)), diagonal_pieces, sides_board);
         
         match side {
             Side::White => self.white_pinned = pinned,
@@ -58,7 +73,7 @@ impl Board {
     pub(crate) fn calculate_attacking_and_attacked(&mut self, side: Side) {
         let mut attacking = vec![];
         let mut attacked = bitboard::EMPTY;
-        let king_index = self.get_king(side);
+        let king = self.get_piece_board(PieceType::King, side);
         let pieces = self.get_all_pieces();
 
         for piece in pieces {
@@ -68,7 +83,7 @@ impl Board {
 
             let attack = piece.get_attacked_square(self);
 
-            if attack.get(king_index) {
+            if (attack & king).count() != 0 {
                 attacking.push(piece);
             }
 
@@ -88,78 +103,97 @@ impl Board {
     }
 
     pub(crate) fn is_king_safety(&self, piece: &Piece, x: usize, y: usize) -> bool {
-        let (pinned, attacking, attacked) = self.get_side_computed_boards(piece.get_color());
- 
-        // if piece is pinned
-        if pinned.get(piece.get_occupied_slot()) && !self.still_pinned_piece_at_pos(piece, x, y) {
-            return false;
-        } else if piece.get_piece_type() == PieceType::King {
-            // king moves to attacked square
-            if attacked.get(y * 8 + x) {
-                return false;
-            }
-            // if king move still causes it to be attacked
-            if self.king_moving_away_from_attacker(piece, attacking, x, y) {
-                return false;
-            }
-        } else {
-            // king is attacked by more than two and trying to move another piece
-            if attacking.len() >= 2 {
-                return false;
-            }
-            if attacking.len() == 1 && !self.will_block_or_capture_king_attack(piece, &attacking[0], x, y) {
-                return false;
-            }
-        }
+        let side = piece.get_color();
+        let (from, to) = (piece.get_occupied_slot(), self.get_index(x, y));
 
-        true
-    }
+        // king moves are not checked against the attacked squares, they were found with the king
+        // still on its square, where it blocks sliders and is a hurdle for a grasshopper
 
-    fn will_block_or_capture_king_attack(&self, piece: &Piece, attacking_piece: &Piece, x: usize, y: usize) -> bool {
-        match attacking_piece.get_piece_type() {
-            PieceType::Pawn | PieceType::Knight => return y * 8 + x == attacking_piece.get_occupied_slot(),
-            _ => {}
-        }
-        
-        if y * 8 + x == attacking_piece.get_occupied_slot() {
+        // positions set up without a king for this side can not leave it in check
+        if self.get_piece_board(PieceType::King, side).count() == 0 {
             return true;
         }
 
-        let king_index = self.get_king(piece.get_color());
-        CoordinateIterator::from_to(attacking_piece.get_pos_as_usize(), (king_index % 8, king_index / 8)).contains((x, y))
-    }
-
-    pub(crate) fn is_no_possible_moves(&self, side: Side) -> bool {
-
-        for piece in self.get_all_pieces() {
-            if piece.get_color() == side && piece.get_possible_moves(self).to_number() != 0 {
-                return false;
-            }
+        // play the move on the occupancy boards and look if the king is attacked afterwards
+        let mut occupied = self.all_pieces_bitboard();
+        let mut opponent = self.get_opponent_board(side);
+        let (from_x, from_y) = piece.get_pos_as_usize();
+        if piece.get_piece_type() == PieceType::Pawn && from_x != x && !occupied.get(to) {
+            // en passant captures the pawn next to the moving pawn
+            let captured = self.get_index(x, from_y);
+            occupied.set(captured, false);
+            opponent.set(captured, false);
         }
+        occupied.set(from, false);
+        occupied.set(to, true);
+        opponent.set(to, false);
 
-        true
-    }
+        let king_index = match piece.get_piece_type() {
+            PieceType::King => to,
+            _ => self.get_king(side),
+        };
 
-    pub(crate) fn still_pinned_piece_at_pos(&self, piece: &Piece, x: usize, y: usize) -> bool {
-        let king_index = self.get_king(piece.get_color());
-        let piece_movement = CoordinateIterator::from_to(piece.get_pos_as_usize(), (x, y)).get_change();
-        let king_to_piece = CoordinateIterator::from_to((king_index % 8, king_index / 8), piece.get_pos_as_usize()).get_change();
+        !self.is_attacked_by(king_index, side.get_opposite(), occupied, opponent)
+    }
 
-        piece_movement == king_to_piece || (piece_movement.0 == -king_to_piece.0 && piece_movement.1 == -king_to_piece.1)
+    /// Check if index is attacked by the pieces of side that are left in attackers, using occupied
+    /// as the blockers for sliding pieces
+    pub(crate) fn is_attacked_by(&self, index: usize, side: Side, occupied: BitBoard, attackers: BitBoard) -> bool {
+        (self.attackers_to(index, occupied) & attackers & self.get_sides_board(side)).count() != 0
     }
 
-    pub(crate) fn king_moving_away_from_attacker(&self, piece: &Piece, attackers: &Vec<Piece>, x: usize, y: usize) -> bool {
-        let king_pos = piece.get_pos_as_usize();
-        let move_dir = CoordinateIterator::from_to(king_pos, (x, y)).get_change();
-        
-        for attacker in attackers {
-            let piece_to_king = CoordinateIterator::from_to(attacker.get_pos_as_usize(), king_pos).get_change();
-            if move_dir == piece_to_king {
-                return true;
+    /// Get all pieces of both sides that attack index, using occupied as the blockers for sliding
+    /// pieces. Removing pieces from occupied reveals the x-ray attackers behind them.
+    pub(crate) fn attackers_to(&self, index: usize, occupied: BitBoard) -> BitBoard {
+        let dimensions = self.dimensions;
+        let mut attackers = bitboard::EMPTY;
+        let mut add_if_on = |board: BitBoard, (dx, dy): (isize, isize)| {
+            if let Some(slot) = dimensions.get_offset(index, dx, dy).filter(|slot| board.get(*slot)) {
+                attackers.set(slot, true);
+            }
+        };
+
+        let knights = self.pieces[PieceType::Knight.to_value()];
+        KNIGHT_DELTAS.iter().for_each(|delta| add_if_on(knights, *delta));
+
+        let kings = self.pieces[PieceType::King.to_value()];
+        KING_DELTAS.iter().for_each(|delta| add_if_on(kings, *delta));
+
+        // pawns attack forward so look backwards from their point of view
+        let pawns = self.pieces[PieceType::Pawn.to_value()];
+        add_if_on(pawns & self.white, (-1, -1));
+        add_if_on(pawns & self.white, (1, -1));
+        add_if_on(pawns & self.black, (-1, 1));
+        add_if_on(pawns & self.black, (1, 1));
+
+        let queens = self.pieces[PieceType::Queen.to_value()];
+        let straight = self.pieces[PieceType::Rook.to_value()] | queens;
+        let diagonal = self.pieces[PieceType::Bishop.to_value()] | queens;
+        for (direction, (dx, dy)) in KING_DELTAS.iter().enumerate() {
+            let sliders = if direction % 2 == 0 { straight } else { diagonal };
+            let mut next = dimensions.get_offset(index, *dx, *dy);
+
+            while let Some(slot) = next {
+                if occupied.get(slot) {
+                    if sliders.get(slot) {
+                        attackers.set(slot, true);
+                    }
+                    break;
+                }
+                next = dimensions.get_offset(slot, *dx, *dy);
             }
         }
-        
-        false
-    }
 
+        // fairy pieces are rare, so their attacks are generated from each of them
+        for piece in FAIRY_PIECES {
+            for from in self.pieces[piece.to_value()] {
+                let side = if self.white.get(from) { Side::White } else { Side::Black };
+                if get_fairy_attacks(piece, dimensions, from, side, occupied).get(index) {
+                    attackers.set(from, true);
+                }
+            }
+        }
+
+        attackers & occupied
+    }
 }
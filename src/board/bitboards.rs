@@ -1,4 +1,4 @@
-use super::{Board, Piece, Side, BitBoard, PieceType};
+use super::{Board, Side, BitBoard, PieceType};
 
 impl Board {
     #[inline]
@@ -14,7 +14,7 @@ impl Board {
         (match side {
             Side::White => self.black,
             Side::Black => self.white,
-        }) | !(self.black | self.white)
+        }) | (self.dimensions.get_all() & !(self.black | self.white))
     }
 
     pub(crate) fn get_sides_board(&self, side: Side) -> BitBoard {
@@ -31,22 +31,19 @@ impl Board {
         }
     }
 
+    pub(crate) fn get_piece_board(&self, piece: PieceType, side: Side) -> BitBoard {
+        self.pieces[piece.to_value()] & self.get_sides_board(side)
+    }
+
     pub(crate) fn all_pieces_bitboard(&self) -> BitBoard {
         self.white | self.black
     }
 
     pub(crate) fn set_piece(&mut self, index: usize, piece: PieceType, side: Side, value: bool) {
-        self.pieces[piece.to_value() as usize].set(index, value);
+        self.pieces[piece.to_value()].set(index, value);
          match side {
             Side::White => self.white.set(index, value),
             Side::Black => self.black.set(index, value),
         };
     }
-
-    pub(crate) fn print_side(&self, side: Side) {
-        match side {
-            Side::White => println!("{}", self.white),
-            Side::Black => println!("{}", self.black),
-        }
-    }
 }
@@ -0,0 +1,198 @@
+use crate::Move;
+
+use super::{BitBoard, Board, Piece, PieceType};
+
+const DROPS: [PieceType; 5] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen];
+
+impl Board {
+    /// Get all legal moves for the side to move under the rules of the variant of the board
+    pub fn get_legal_moves(&self) -> Vec<Move> {
+        self.variant.clone().get_legal_moves(self)
+    }
+
+    /// Get all legal moves for the side to move under the rules of standard chess, with one move
+    /// per promotion piece type
+    pub fn generate_legal_moves(&self) -> Vec<Move> {
+        self.generate_moves(|piece| piece.get_possible_moves(self))
+    }
+
+    /// Get all moves for the side to move without looking at the safety of its own king, with one
+    /// move per promotion piece type
+    pub fn generate_pseudo_legal_moves(&self) -> Vec<Move> {
+        self.generate_moves(|piece| piece.get_pseudo_legal_moves(self))
+    }
+
+    fn generate_moves(&self, piece_moves: impl Fn(&Piece) -> BitBoard) -> Vec<Move> {
+        let mut moves = vec![];
+        let promotions = self.variant.get_promotion_pieces();
+
+        for piece in self.get_all_pieces() {
+            if piece.get_color() != self.side {
+                continue;
+            }
+
+            let is_pawn = piece.get_piece_type() == PieceType::Pawn;
+            for index in piece_moves(&piece) {
+                if is_pawn && self.is_last_rank(index) {
+                    moves.extend(promotions.iter().map(|promotion| Move::new(piece.get_occupied_slot(), index, Some(*promotion))));
+                } else {
+                    moves.push(Move::new(piece.get_occupied_slot(), index, None));
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Get all legal drops of the pieces in the pocket of the side to move
+    ///
+    /// Pawns are not dropped on the first and last rank, and in check only drops that block the
+    /// check are legal
+    pub fn generate_drops(&self) -> Vec<Move> {
+        let side = self.side;
+        let pocket = self.get_pocket(side);
+        let pieces: Vec<_> = DROPS.iter().filter(|piece| pocket[piece.to_value()] != 0).collect();
+        if pieces.is_empty() {
+            return vec![];
+        }
+
+        let occupied = self.all_pieces_bitboard();
+        let opponent = self.get_sides_board(side.get_opposite());
+        let king = match self.is_in_check() {
+            true => Some(self.get_king(side)),
+            false => None,
+        };
+
+        let mut moves = vec![];
+        for index in 0..self.dimensions.get_num_squares() {
+            if occupied.get(index) {
+                continue;
+            }
+
+            if let Some(king) = king {
+                let mut blocked = occupied;
+                blocked.set(index, true);
+                if self.is_attacked_by(king, side.get_opposite(), blocked, opponent) {
+                    continue;
+                }
+            }
+
+            for piece in pieces.iter() {
+                if **piece != PieceType::Pawn || !self.is_last_rank(index) {
+                    moves.push(Move::new_drop(**piece, index));
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Play a move for the side to move if it is legal under the rules of the variant of the board
+    ///
+    /// # Return: If the move was legal
+    pub(crate) fn make_move(&mut self, chess_move: Move) -> bool {
+        let variant = self.variant.clone();
+        if !variant.is_legal(self, chess_move) {
+            return false;
+        }
+
+        variant.play_move(self, chess_move);
+        true
+    }
+
+    /// Check if a move is legal under the rules of standard chess
+    pub fn is_standard_legal(&self, chess_move: Move) -> bool {
+        if chess_move.get_drop().is_some() {
+            return false;
+        }
+
+        let piece = match self.get_piece_at_pos(chess_move.get_start()) {
+            Some(piece) if piece.get_color() == self.side => piece,
+            _ => return false,
+        };
+
+        let is_promoting = piece.get_piece_type() == PieceType::Pawn && self.is_last_rank(chess_move.get_end());
+        match chess_move.get_promotion() {
+            Some(promotion) if is_promoting && self.variant.get_promotion_pieces().contains(&promotion) => {},
+            None if !is_promoting => {},
+            _ => return false,
+        }
+
+        piece.is_allowed_move(self, chess_move.get_end())
+    }
+
+    /// Play a move the way it is played in standard chess, without checking if it is legal
+    ///
+    /// A drop only puts the piece on the board, taking it out of the pocket is up to the variant
+    pub(crate) fn apply_move(&mut self, chess_move: Move) {
+        if let Some(piece) = chess_move.get_drop() {
+            self.drop_piece(piece, chess_move.get_end());
+            return;
+        }
+
+        let piece = match self.get_piece_at_pos(chess_move.get_start()) {
+            Some(piece) => piece,
+            None => return,
+        };
+
+        self.move_piece(&piece, chess_move.get_end(), chess_move.get_promotion());
+
+        // the promoted piece might pin pieces of the side to move
+        if chess_move.get_promotion().is_some() {
+            self.calculate_pinned_pieces(self.side);
+        }
+    }
+
+    /// Check if the side to move is in check under the rules of the variant of the board
+    pub fn is_in_check(&self) -> bool {
+        self.variant.clone().is_in_check(self)
+    }
+
+    /// Check if the king of the side to move is attacked by any piece of the opponent
+    pub fn is_standard_check(&self) -> bool {
+        !self.get_side_computed_boards(self.side).1.is_empty()
+    }
+
+    /// Check if a move takes a piece, en passant included
+    pub fn is_capture(&self, chess_move: Move) -> bool {
+        if chess_move.get_drop().is_some() {
+            return false;
+        }
+        if self.get_opponent_board(self.side).get(chess_move.get_end()) {
+            return true;
+        }
+
+        self.get_piece_type_at_pos(chess_move.get_start()) == PieceType::Pawn
+            && self.get_coordinates(chess_move.get_start()).0 != self.get_coordinates(chess_move.get_end()).0
+    }
+
+    /// Check if index is on the first or the last rank, where pawns promote
+    pub(crate) fn is_last_rank(&self, index: usize) -> bool {
+        let y = self.get_coordinates(index).1;
+        y == 0 || y == self.dimensions.get_height() - 1
+    }
+
+    /// Count all leaf nodes of the legal move tree at depth
+    pub(crate) fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        // a game that a rule of the variant has ended has no moves left
+        if self.variant.get_outcome(self).is_some() {
+            return 0;
+        }
+
+        let moves = self.get_legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        // the moves are known to be legal, so they are played without checking them again
+        moves.iter().map(|chess_move| {
+            let mut board = self.clone();
+            self.variant.play_move(&mut board, *chess_move);
+            board.perft(depth - 1)
+        }).sum()
+    }
+}
@@ -1,16 +1,44 @@
 use crate::bitboard;
 
-use super::{BitBoard, Board, Piece, PieceType, Side, NUM_INDECES};
+use super::{BitBoard, Board, CastlingAbility, CastlingSide, Piece, PieceType, Side, NUM_INDECES};
 
 impl Board {
-    pub(crate) fn move_piece(&mut self, piece: &Piece, index: usize) -> bool {
-        if !piece.is_allowed_move(self, index) {
-            return false;
+    /// Move the king and the rook of castling_side to their squares next to each other
+    fn castle(&mut self, king: &Piece, castling_side: CastlingSide) {
+        let side = king.get_color();
+        let back_rank = king.get_pos_as_usize().1;
+        let rook_file = self.get_castling(side).get_rook_file(castling_side).expect("castling without castling rights");
+        let (king_to, rook_to) = self.get_castling_files(castling_side);
+
+        // both are taken off first since in Chess960 they are able to land on each other's square
+        self.set_piece(king.get_occupied_slot(), PieceType::King, side, false);
+        self.set_piece(self.get_index(rook_file, back_rank), PieceType::Rook, side, false);
+        self.set_piece(self.get_index(king_to, back_rank), PieceType::King, side, true);
+        self.set_piece(self.get_index(rook_to, back_rank), PieceType::Rook, side, true);
+
+        self.ep_target = None;
+        self.moves_to_50 = self.moves_to_50.saturating_add(1);
+        self.update_castling_ability(king);
+        if self.side == Side::Black {
+            self.move_counter += 1;
+        }
+
+        self.update_calculations();
+        self.side = self.side.get_opposite();
+    }
+
+    /// Move a piece and replace it with the promotion piece type on its new square, the move is
+    /// not checked for legality
+    pub(crate) fn move_piece(&mut self, piece: &Piece, index: usize, promotion: Option<PieceType>) {
+        if let Some(castling_side) = self.get_castling_side(piece.get_occupied_slot(), index) {
+            self.castle(piece, castling_side);
+            return;
         }
 
         // if there is an active en passant target
         if let Some(ep_index) = self.ep_target {
-            if piece.get_piece_type() == PieceType::Pawn && index.abs_diff(ep_index as usize) == 8 {
+            // a pawn moving diagonally to an empty square takes en passant
+            if piece.get_piece_type() == PieceType::Pawn && self.get_coordinates(index).0 != piece.get_pos_as_usize().0 && self.is_empty(index) {
                 self.take_en_passant(piece, ep_index);
             }
             self.ep_target = None;
@@ -23,30 +51,20 @@ impl Board {
         let capture_type = self.get_piece_type_at_pos(index);
         if capture_type != PieceType::Empty {
             self.set_piece(index, capture_type, piece.get_color().get_opposite(), false);
+            if capture_type == PieceType::Rook {
+                self.remove_castling_for_rook(piece.get_color().get_opposite(), index);
+            }
             self.moves_to_50 = 0; // reset to 0 on capture
         } else if piece.get_piece_type() == PieceType::Pawn {
             self.encode_en_passant(piece, index as i8);
             self.moves_to_50 = 0; // reset if a pawn is moved
         } else {
-            self.moves_to_50 += 1;
+            self.moves_to_50 = self.moves_to_50.saturating_add(1);
         }
 
         // move current piece to new index
         self.set_piece(piece.get_occupied_slot(), piece.get_piece_type(), piece.get_color(), false);
-        self.set_piece(index, piece.get_piece_type(), piece.get_color(), true);
-
-        // castling 
-        if piece.get_piece_type() == PieceType::King && index.abs_diff(piece.get_occupied_slot()) == 2 {
-            let (rook_offset, rook_x): (i8, usize) = match (index as i8 - piece.get_occupied_slot() as i8).signum() {
-                1 => (-1, 7),
-                -1 => (1, 0),
-                _ => (0, 0),
-            };
-            if rook_offset != 0 {
-                self.set_piece((index / 8) * 8 + rook_x, PieceType::Rook, piece.get_color(), false);
-                self.set_piece((index as i8 + rook_offset) as usize, PieceType::Rook, piece.get_color(), true);
-            }
-        }
+        self.set_piece(index, promotion.unwrap_or(piece.get_piece_type()), piece.get_color(), true);
 
         self.update_castling_ability(piece);
         
@@ -57,11 +75,44 @@ impl Board {
         self.update_calculations();
         // switch side so that it's the next players turn
         self.side = self.side.get_opposite();
+    }
+
+    /// Put a piece of the side to move on the empty square index, the pocket is left to the variant
+    pub(crate) fn drop_piece(&mut self, piece: PieceType, index: usize) {
+        self.set_piece(index, piece, self.side, true);
+
+        self.ep_target = None;
+        self.moves_to_50 = match piece {
+            PieceType::Pawn => 0,
+            _ => self.moves_to_50.saturating_add(1),
+        };
+        if self.side == Side::Black {
+            self.move_counter += 1;
+        }
 
-        true
+        self.update_calculations();
+        self.side = self.side.get_opposite();
+    }
+
+    /// Take the piece on index off the board, together with the castling rights that depend on it
+    ///
+    /// The attack and pin calculations are not updated
+    pub(crate) fn remove_piece(&mut self, index: usize) {
+        let piece = match self.get_piece_at_pos(index) {
+            Some(piece) => piece,
+            None => return,
+        };
+
+        let side = piece.get_color();
+        self.set_piece(index, piece.get_piece_type(), side, false);
+        match piece.get_piece_type() {
+            PieceType::Rook => self.remove_castling_for_rook(side, index),
+            PieceType::King => self.castling[side.to_index()] = CastlingAbility::default(),
+            _ => {},
+        }
     }
 
-    pub(crate) fn get_piece_type_at_pos(&self, index: usize) -> PieceType {
+    pub fn get_piece_type_at_pos(&self, index: usize) -> PieceType {
         if !self.all_pieces_bitboard().get(index) {
             return PieceType::Empty;
         }
@@ -76,13 +127,14 @@ impl Board {
     }
 
     fn index_to_piece(&self, index: usize, piecetype: PieceType) -> Piece {
+        let (x, y) = self.get_coordinates(index);
         Piece::new(piecetype, match self.white.get(index) {
             true => Side::White,
             _ => Side::Black
-        }, (index % 8) as i8, (index / 8) as i8)
+        }, x as i8, y as i8, index)
     }
 
-    pub(crate) fn get_piece_at_pos(&self, index: usize) -> Option<Piece> {
+    pub fn get_piece_at_pos(&self, index: usize) -> Option<Piece> {
         let piecetype = self.get_piece_type_at_pos(index);
         
         if piecetype == PieceType::Empty {
@@ -93,7 +145,7 @@ impl Board {
     }
 
     pub(crate) fn get_all_pieces(&self) -> Vec<Piece> {
-        (0..NUM_INDECES).filter_map(|n| self.get_piece_at_pos(n)).collect::<Vec<_>>()
+        (0..self.dimensions.get_num_squares()).filter_map(|n| self.get_piece_at_pos(n)).collect::<Vec<_>>()
     }
     
     pub(crate) fn get_king(&self, side: Side) -> usize {
@@ -109,7 +161,7 @@ impl Board {
     {
         let mut board = bitboard::EMPTY;
         for (x, y) in moves {
-            if Board::is_inbounds(x, y) && stop_pred_and_action(&mut board, x, y) {
+            if self.is_inbounds(x, y) && stop_pred_and_action(&mut board, x, y) {
                 break
             }
         }
@@ -118,10 +170,11 @@ impl Board {
     }
 
     pub(crate) fn filter_king_safety(&self, old_board: BitBoard, piece: &Piece) -> BitBoard {
-        let mut board = old_board.clone();
+        let mut board = old_board;
 
         for index in old_board {
-            if !self.is_king_safety(piece, index % 8, index / 8) {
+            let (x, y) = self.get_coordinates(index);
+            if !self.is_king_safety(piece, x, y) {
                 board.set(index, false);
             }
         }
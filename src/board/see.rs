@@ -0,0 +1,106 @@
+use crate::{eval, Move};
+
+use super::{BitBoard, Board, PieceType, Side};
+
+// the king is worth more than anything it could capture
+const KING_VALUE: i32 = 20000;
+
+impl Board {
+    /// Static exchange evaluation of a move
+    ///
+    /// Plays out the capture sequence on the end index, where both sides always recapture with
+    /// their least valuable piece and are allowed to stop when it would lose material. Attackers
+    /// behind sliding pieces join in once the pieces in front of them have captured. Pins are
+    /// not taken into account.
+    ///
+    /// # Return: The material gained by the side to move in centipawns, negative if the move loses material
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::from_fen("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1".to_string());
+    /// let rxe5 = Move::from_uci("e1e5").unwrap();
+    /// assert!(chess.board.see(rxe5) == 100 - 500);
+    /// ```
+    pub fn see(&self, chess_move: Move) -> i32 {
+        let (start, end) = (chess_move.get_start(), chess_move.get_end());
+        let moving = self.get_piece_type_at_pos(start);
+        let mut occupied = self.all_pieces_bitboard();
+        let mut captured = self.get_piece_type_at_pos(end);
+
+        // en passant takes the pawn next to the moving pawn
+        let ((start_x, start_y), (end_x, _)) = (self.get_coordinates(start), self.get_coordinates(end));
+        if moving == PieceType::Pawn && captured == PieceType::Empty && start_x != end_x {
+            captured = PieceType::Pawn;
+            occupied.set(self.get_index(end_x, start_y), false);
+        }
+
+        let mut gain = vec![see_value(captured)];
+        let mut on_square = see_value(moving);
+        if let Some(promotion) = chess_move.get_promotion() {
+            gain[0] += see_value(promotion) - see_value(PieceType::Pawn);
+            on_square = see_value(promotion);
+        }
+
+        occupied.set(start, false);
+        let mut side = self.side.get_opposite();
+
+        loop {
+            let attackers = self.attackers_to(end, occupied);
+            let (index, piece) = match self.least_valuable_attacker(attackers, side) {
+                Some(attacker) => attacker,
+                None => break,
+            };
+
+            // the king can only recapture if the square is not defended anymore
+            if piece == PieceType::King && (attackers & self.get_sides_board(side.get_opposite())).count() != 0 {
+                break;
+            }
+
+            gain.push(on_square - gain[gain.len() - 1]);
+            on_square = see_value(piece);
+            occupied.set(index, false);
+            side = side.get_opposite();
+        }
+
+        // every side is able to stop capturing, so go backwards and let each side choose
+        while gain.len() > 1 {
+            let last = gain.pop().unwrap();
+            let previous = gain.len() - 1;
+            gain[previous] = -(-gain[previous]).max(last);
+        }
+
+        gain[0]
+    }
+
+    /// Check if the static exchange evaluation of a move is at least threshold
+    ///
+    /// # Example:
+    /// ```
+    /// if !chess.board.see_ge(chess_move, 0) {
+    ///     println!("{} hangs material", chess_move);
+    /// }
+    /// ```
+    pub fn see_ge(&self, chess_move: Move, threshold: i32) -> bool {
+        self.see(chess_move) >= threshold
+    }
+
+    fn least_valuable_attacker(&self, attackers: BitBoard, side: Side) -> Option<(usize, PieceType)> {
+        let attackers = attackers & self.get_sides_board(side);
+
+        [
+            PieceType::Pawn, PieceType::Grasshopper, PieceType::Camel, PieceType::Knight, PieceType::Bishop, PieceType::Rook,
+            PieceType::Archbishop, PieceType::Chancellor, PieceType::Queen, PieceType::Amazon, PieceType::King,
+        ].iter()
+            .find_map(|piece| {
+                let mut board = attackers & self.pieces[piece.to_value()];
+                board.next().map(|index| (index, *piece))
+            })
+    }
+}
+
+fn see_value(piece: PieceType) -> i32 {
+    match piece {
+        PieceType::King => KING_VALUE,
+        _ => eval::piece_value(piece),
+    }
+}
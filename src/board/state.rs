@@ -1,4 +1,5 @@
 use crate::{Board, Piece, Side, CastlingAbility, PieceType};
+use crate::pieces::FAIRY_PIECES;
 
 impl Board {
     pub(crate) fn update_calculations(&mut self) {
@@ -9,44 +10,67 @@ impl Board {
         self.calculate_pinned_pieces(self.side.get_opposite());
     }
 
+    /// Calculate the attacks and pins of both sides again, after pieces were added or removed
+    /// outside of a normal move
+    pub(crate) fn update_all_calculations(&mut self) {
+        for side in [Side::White, Side::Black] {
+            self.calculate_attacking_and_attacked(side);
+            self.calculate_pinned_pieces(side);
+        }
+    }
+
     pub(crate) fn update_castling_ability(&mut self, piece: &Piece) {
-        let color = match piece.get_color() {
-            Side::White => 0,
-            Side::Black => 1,
-        };
+        let color = piece.get_color().to_index();
 
         match piece.get_piece_type() {
             PieceType::King => {
-                self.castling[color] = CastlingAbility::None;
-            },
-            PieceType::Rook => {
-                if self.castling[color] != CastlingAbility::None {
-                    return;
-                }
-
-                match piece.get_pos_as_usize().0 {
-                    0 => self.castling[color].remove(CastlingAbility::King),
-                    7 => self.castling[color].remove(CastlingAbility::Queen),
-                    _ => {}
-                }
+                self.castling[color] = CastlingAbility::default();
             },
+            PieceType::Rook => self.remove_castling_for_rook(piece.get_color(), piece.get_occupied_slot()),
             _ => {}
         }
     }
 
+    /// Remove the castling ability belonging to a rook that moved from or was captured on index
+    pub(crate) fn remove_castling_for_rook(&mut self, side: Side, index: usize) {
+        let (color, back_rank) = match side {
+            Side::White => (0, 0),
+            Side::Black => (1, self.dimensions.get_height() - 1),
+        };
+
+        let (x, y) = self.get_coordinates(index);
+        if y != back_rank {
+            return;
+        }
+
+        self.castling[color].remove_file(x);
+    }
+
     pub(crate) fn encode_en_passant(&mut self, piece: &Piece, new_index: i8) {
-        if piece.get_occupied_slot().abs_diff(new_index as usize) == 16 {
+        // pawns moving two squares from the first rank in Horde are not taken en passant
+        let (y, new_y) = (piece.get_pos_as_usize().1, self.get_coordinates(new_index as usize).1);
+        let pawn_rank = self.variant.get_pawn_rank();
+        if y.abs_diff(new_y) == 2 && (y == pawn_rank || y + pawn_rank + 1 == self.dimensions.get_height()) {
             self.ep_target = Some(new_index);
         }
     }
 
-    pub(crate) fn is_only_kings_left(&self) -> bool {
-        let mut board = self.get_combined_piece_board();
-        // remove kings from board
-        board.set(self.get_king(self.get_playing_side()), false);
-        board.set(self.get_king(self.get_playing_side().get_opposite()), false);
+    /// Check if side has too little material to ever checkmate, a lone king or a king and one minor piece
+    pub fn has_insufficient_material(&self, side: Side) -> bool {
+        let heavy = self.get_piece_board(PieceType::Pawn, side).count()
+            + self.get_piece_board(PieceType::Rook, side).count()
+            + self.get_piece_board(PieceType::Queen, side).count()
+            + FAIRY_PIECES.iter().map(|piece| self.get_piece_board(*piece, side).count()).sum::<u32>();
+        let minor = self.get_piece_board(PieceType::Knight, side).count()
+            + self.get_piece_board(PieceType::Bishop, side).count();
 
-        board.to_number() == 0
+        heavy == 0 && minor <= 1
     }
 
+    /// Check if neither side is able to checkmate, at most one minor piece is left besides the kings
+    pub fn is_insufficient_material(&self) -> bool {
+        let minor = self.pieces[PieceType::Knight.to_value()].count() + self.pieces[PieceType::Bishop.to_value()].count();
+
+        self.has_insufficient_material(Side::White) && self.has_insufficient_material(Side::Black) && minor <= 1
+    }
 }
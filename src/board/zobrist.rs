@@ -0,0 +1,94 @@
+use crate::dimensions::MAX_FILES;
+
+use super::{Board, CastlingSide, PieceType, Side, NUM_INDECES, NUM_PIECES};
+
+const PIECE_KEYS: usize = 2 * NUM_PIECES * NUM_INDECES;
+const SIDE_KEY: usize = PIECE_KEYS;
+const CASTLING_KEYS: usize = SIDE_KEY + 1; // [white king, white queen, black king, black queen]
+const EP_KEYS: usize = CASTLING_KEYS + 4; // one per file
+const POCKET_KEYS: usize = EP_KEYS + MAX_FILES; // per side, piece type and number of pieces in hand
+const MAX_POCKET: usize = 16;
+const CHECK_KEYS: usize = POCKET_KEYS + 2 * NUM_PIECES * MAX_POCKET; // per side and number of checks
+const MAX_CHECKS: usize = 4;
+const PROMOTED_KEYS: usize = CHECK_KEYS + 2 * MAX_CHECKS; // per square of a promoted piece
+const NUM_KEYS: usize = PROMOTED_KEYS + NUM_INDECES;
+
+static KEYS: [u64; NUM_KEYS] = generate_keys();
+
+// splitmix64 so that the keys are the same for every build
+const fn generate_keys() -> [u64; NUM_KEYS] {
+    let mut keys = [0; NUM_KEYS];
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut i = 0;
+
+    while i < NUM_KEYS {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    keys
+}
+
+impl Board {
+    /// Get the Zobrist key of the position, equal positions always get the same key
+    pub(crate) fn get_zobrist_key(&self) -> u64 {
+        let mut key = 0;
+
+        for (side_index, side) in [Side::White, Side::Black].iter().enumerate() {
+            let sides_board = self.get_sides_board(*side);
+            for (piece_index, piece_board) in self.pieces.iter().enumerate() {
+                for index in *piece_board & sides_board {
+                    key ^= KEYS[(side_index * NUM_PIECES + piece_index) * NUM_INDECES + index];
+                }
+            }
+
+            let castling = self.get_castling(*side);
+            if castling.has(CastlingSide::King) {
+                key ^= KEYS[CASTLING_KEYS + side_index * 2];
+            }
+            if castling.has(CastlingSide::Queen) {
+                key ^= KEYS[CASTLING_KEYS + side_index * 2 + 1];
+            }
+
+        }
+
+        if let Some(state) = &self.variant_state {
+            key ^= state.get_zobrist_key();
+        }
+
+        if self.side == Side::Black {
+            key ^= KEYS[SIDE_KEY];
+        }
+
+        if let Some(ep_index) = self.ep_target {
+            key ^= KEYS[EP_KEYS + self.get_coordinates(ep_index as usize).0];
+        }
+
+        key
+    }
+}
+
+/// Get the key of side having count pieces of a piece type in hand, 0 for an empty pocket
+pub(crate) fn get_pocket_key(side: Side, piece: PieceType, count: u8) -> u64 {
+    match count {
+        0 => 0,
+        count => KEYS[POCKET_KEYS + (side.to_index() * NUM_PIECES + piece.to_value()) * MAX_POCKET + (count as usize).min(MAX_POCKET - 1)],
+    }
+}
+
+/// Get the key of side having given a number of checks, 0 for no checks
+pub(crate) fn get_checks_key(side: Side, checks: u8) -> u64 {
+    match checks {
+        0 => 0,
+        checks => KEYS[CHECK_KEYS + side.to_index() * MAX_CHECKS + (checks as usize).min(MAX_CHECKS - 1)],
+    }
+}
+
+/// Get the key of the piece on index having been a pawn before it promoted
+pub(crate) fn get_promoted_key(index: usize) -> u64 {
+    KEYS[PROMOTED_KEYS + index]
+}
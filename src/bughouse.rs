@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use crate::clock::Clock;
+use crate::variant::{self, Bughouse};
+use crate::{Chess, Move, Side, Termination};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// The two teams of a Bughouse game
+///
+/// # First: White on the first board and black on the second board
+/// # Second: Black on the first board and white on the second board
+///
+pub enum Team {
+    First,
+    Second,
+}
+
+impl Team {
+    /// Get the team that plays side on board
+    pub fn of(board: usize, side: Side) -> Team {
+        match (board == 0, side) {
+            (true, Side::White) | (false, Side::Black) => Team::First,
+            _ => Team::Second,
+        }
+    }
+
+    /// Get the other team
+    pub fn get_opposite(&self) -> Team {
+        match self {
+            Team::First => Team::Second,
+            Team::Second => Team::First,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// How a Bughouse game ended
+///
+/// # winner: The team that won, None for a draw
+/// # board: The board the game ended on
+/// # termination: Why the game on that board ended
+///
+pub struct BughouseOutcome {
+    pub winner: Option<Team>,
+    pub board: usize,
+    pub termination: Termination,
+}
+
+/// A Bughouse game of two linked boards, a piece captured on one board goes into the pocket of the
+/// partner on the other board
+///
+/// The game ends for both teams as soon as one board ends.
+///
+/// # Example:
+/// ```
+/// let mut game = BughouseGame::new();
+/// for (board, uci) in [(0, "e2e4"), (0, "d7d5"), (0, "e4d5")] {
+///     game.play_move(board, Move::from_uci(uci).unwrap());
+/// }
+/// // white on the first board took a pawn, which goes to black on the second board
+/// assert!(game.get_board(1).get_pocket(Side::Black)[PieceType::Pawn.to_value()] == 1);
+/// ```
+pub struct BughouseGame {
+    boards: [Chess; 2],
+    ended: Option<usize>, // the board that ended first, its result stays the result of the game
+}
+
+impl Default for BughouseGame {
+    fn default() -> Self {
+        BughouseGame::new()
+    }
+}
+
+impl BughouseGame {
+    /// Create a game with both boards in the start position
+    pub fn new() -> BughouseGame {
+        let board = || Chess::new_variant(Arc::new(Bughouse)).unwrap();
+        BughouseGame { boards: [board(), board()], ended: None }
+    }
+
+    /// Create a game from the FEN of each board, the pockets are written like in Crazyhouse
+    pub fn from_fens(first: &str, second: &str) -> Result<BughouseGame, String> {
+        let board = |fen: &str| Chess::try_from_fen_variant(fen.to_string(), Arc::new(Bughouse));
+        Ok(BughouseGame { boards: [board(first)?, board(second)?], ended: None })
+    }
+
+    /// Get the game on board, 0 for the first board and 1 for the second
+    pub fn get_board(&self, board: usize) -> &Chess {
+        &self.boards[board]
+    }
+
+    /// Start a clock on each board, both start running right away
+    pub fn set_clocks(&mut self, first: Clock, second: Clock) {
+        self.boards[0].set_clock(first);
+        self.boards[1].set_clock(second);
+    }
+
+    /// Play a move on board, a captured piece goes to the partner in the same step
+    ///
+    /// # Return: If the move was legal and played, moves are not played once the game is over
+    pub fn play_move(&mut self, board: usize, chess_move: Move) -> bool {
+        // a flag may have fallen since the last move
+        self.check_ended(0);
+        if board > 1 || self.ended.is_some() {
+            return false;
+        }
+
+        let side = self.boards[board].get_playing_side();
+        let captured = variant::get_pocket_piece(&self.boards[board].board, chess_move);
+        if !self.boards[board].play_move(chess_move) {
+            return false;
+        }
+
+        // the partner plays the other color on the other board
+        if let Some(piece) = captured {
+            self.boards[1 - board].add_to_pocket(side.get_opposite(), piece);
+        }
+        self.check_ended(board);
+        true
+    }
+
+    /// Get the outcome of the game, decided by the first board that ended
+    ///
+    /// A flag fall always loses, since pieces from the partner are able to checkmate at any time.
+    pub fn get_outcome(&self) -> Option<BughouseOutcome> {
+        let board = self.ended.or_else(|| (0..2).find(|board| self.boards[*board].get_outcome().is_some()))?;
+        let outcome = self.boards[board].get_outcome()?;
+        let winner = outcome.result.get_winner().map(|side| Team::of(board, side));
+        Some(BughouseOutcome { winner, board, termination: outcome.termination })
+    }
+
+    /// Record the board that ended, looking at first before the other board, and stop both clocks
+    fn check_ended(&mut self, first: usize) {
+        if self.ended.is_some() {
+            return;
+        }
+
+        self.ended = [first, 1 - first].into_iter().find(|board| self.boards[*board].get_outcome().is_some());
+        if self.ended.is_some() {
+            for board in self.boards.iter_mut() {
+                board.stop_clock();
+            }
+        }
+    }
+}
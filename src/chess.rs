@@ -1,12 +1,21 @@
-use crate::{Board, Piece, PieceType, Side};
+use std::sync::Arc;
+
+use crate::clock::Clock;
+use crate::variant::{CrazyhouseState, Standard, ThreeCheckState, Variant};
+use crate::{Board, Move, Piece, PieceType, Side};
+
+mod san;
 
 /// Initialize this object
 ///
 /// This is the main chess game object
+#[derive(Clone)]
 pub struct Chess {
     pub board: Board,
     state: State,
-    promoting_index: Option<usize>
+    promoting_index: Option<usize>,
+    history: Vec<(Board, Move, Option<Clock>)>, // every played move with the board and clock from before it
+    clock: Option<Clock>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -20,6 +29,7 @@ pub struct Chess {
 /// # Stalemate: The game is in stalemate
 /// # Draw: The game is a draw
 /// # Promotion: There is a pawn promotion that must be handled
+/// # VariantEnd: The game ended by a rule of the variant
 ///
 pub enum State {
     Playing,
@@ -27,7 +37,83 @@ pub enum State {
     Checkmate,
     Stalemate,
     Draw,
-    Promotion
+    Promotion,
+    VariantEnd,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+/// The result of a finished game
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    /// Get the side that won, None for a draw
+    pub fn get_winner(&self) -> Option<Side> {
+        match self {
+            GameResult::WhiteWins => Some(Side::White),
+            GameResult::BlackWins => Some(Side::Black),
+            GameResult::Draw => None,
+        }
+    }
+
+    /// Get the result as it is written in PGN
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+/// Why a game ended
+///
+/// # Checkmate: The side to move is checkmated
+/// # Stalemate: The side to move has no legal moves but is not in check
+/// # FiftyMoves: Fifty moves by each side without a capture or pawn move
+/// # Repetition: The same position occurred too often, when a player or a match runner calls it a draw
+/// # InsufficientMaterial: Neither side has enough material left to checkmate
+/// # TimeForfeit: A player ran out of time
+/// # Forfeit: A player lost by breaking the rules, like playing an illegal move
+/// # Variant: A win condition or draw rule of the variant
+///
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    FiftyMoves,
+    Repetition,
+    InsufficientMaterial,
+    TimeForfeit,
+    Forfeit,
+    Variant,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+/// How a game ended
+pub struct Outcome {
+    pub result: GameResult,
+    pub termination: Termination,
+}
+
+impl Outcome {
+    /// Create the outcome of side losing because of termination
+    pub fn loss(side: Side, termination: Termination) -> Outcome {
+        let result = match side {
+            Side::White => GameResult::BlackWins,
+            Side::Black => GameResult::WhiteWins,
+        };
+
+        Outcome { result, termination }
+    }
+
+    /// Create a drawn outcome
+    pub fn draw(termination: Termination) -> Outcome {
+        Outcome { result: GameResult::Draw, termination }
+    }
 }
 
 impl Default for Chess {
@@ -43,19 +129,171 @@ impl Chess {
             board: Board::default(),
             state: State::Playing,
             promoting_index: None,
+            history: vec![],
+            clock: None,
         }
     }
 
     /// Create a new chess game but start from a specific board position
     pub fn from_fen(fen: String) -> Chess {
+        Chess::try_from_fen(fen).unwrap()
+    }
+
+    /// Create a new chess game from a specific board position, without panicking on invalid FEN
+    ///
+    /// # Example:
+    /// ```
+    /// match Chess::try_from_fen(user_input) {
+    ///     Ok(chess) => println!("{:?}", chess.get_state()),
+    ///     Err(error) => println!("{}", error),
+    /// }
+    /// ```
+    pub fn try_from_fen(fen: String) -> Result<Chess, String> {
+        Chess::try_from_fen_variant(fen, Arc::new(Standard))
+    }
+
+    /// Create a new game of a variant starting from its start position
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new_variant(Arc::new(Standard)).unwrap();
+    /// assert!(chess.get_fen() == Chess::new().get_fen());
+    /// ```
+    pub fn new_variant(variant: Arc<dyn Variant>) -> Result<Chess, String> {
+        Chess::try_from_fen_variant(variant.get_start_fen(), variant)
+    }
+
+    /// Create a new game of a variant from a specific board position, the FEN may contain the
+    /// state the variant adds to it
+    pub fn try_from_fen_variant(fen: String, variant: Arc<dyn Variant>) -> Result<Chess, String> {
         let mut chess = Chess {
-            board: Board::from_fen(fen).unwrap(),
+            board: Board::from_fen_variant(fen, variant)?,
             state: State::Playing,
-            promoting_index: None
+            promoting_index: None,
+            history: vec![],
+            clock: None,
         };
         chess.update_state();
 
-        chess
+        Ok(chess)
+    }
+
+    /// Get the FEN notation of the current board position
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// assert!(chess.get_fen() == "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// ```
+    pub fn get_fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    /// Get the FEN notation of the current board position with the castling rights written as the
+    /// files of the castling rooks
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// assert!(chess.get_shredder_fen() == "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1");
+    /// ```
+    pub fn get_shredder_fen(&self) -> String {
+        self.board.to_shredder_fen()
+    }
+
+    /// Create a new Chess960 game from the number of its start position
+    ///
+    /// The numbering is the standard one from 0 to 959, where 518 is the standard start position
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new_chess960(0).unwrap();
+    /// assert!(chess.get_fen() == "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    /// ```
+    pub fn new_chess960(index: u16) -> Result<Chess, String> {
+        if index >= 960 {
+            return Err(format!("Invalid Chess960 position: {}", index));
+        }
+
+        let mut back_rank = [None; 8];
+        let mut index = index as usize;
+        // the bishops go on one light and one dark square
+        back_rank[(index % 4) * 2 + 1] = Some('b');
+        index /= 4;
+        back_rank[(index % 4) * 2] = Some('b');
+        index /= 4;
+
+        let mut place = |piece: char, nth_empty: usize| {
+            let file = (0..8).filter(|file| back_rank[*file].is_none()).nth(nth_empty).unwrap();
+            back_rank[file] = Some(piece);
+        };
+        place('q', index % 6);
+        index /= 6;
+
+        // the knights take two of the five empty squares, the rooks and king the other three
+        let knights = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][index];
+        place('n', knights.0);
+        place('n', knights.1 - 1);
+        place('r', 0);
+        place('k', 0);
+        place('r', 0);
+
+        let black: String = back_rank.iter().flatten().collect();
+        let fen = format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", black, black.to_ascii_uppercase());
+        let mut chess = Chess::try_from_fen(fen)?;
+        chess.set_chess960(true);
+
+        Ok(chess)
+    }
+
+    /// Get the variant the game is played with
+    pub fn get_variant(&self) -> &Arc<dyn Variant> {
+        self.board.get_variant()
+    }
+
+    /// Get the number of pieces in hand of side in variants with drops, indexed by piece type value
+    ///
+    /// # Example:
+    /// ```
+    /// let knights = chess.get_pocket(Side::White)[PieceType::Knight.to_value()];
+    /// ```
+    pub fn get_pocket(&self, side: Side) -> [u8; 6] {
+        self.board.get_pocket(side)
+    }
+
+    /// Put a piece into the pocket of side from outside of the game, like from the other board in
+    /// Bughouse
+    pub(crate) fn add_to_pocket(&mut self, side: Side, piece: PieceType) {
+        let Some(state) = self.board.get_variant_state_mut::<CrazyhouseState>() else {
+            return;
+        };
+        state.add_to_pocket(side, piece);
+
+        // a piece in hand might block a check that was mate before
+        if self.state != State::Promotion {
+            self.update_state();
+        }
+    }
+
+    /// Get the number of checks side has given in variants that count them
+    pub fn get_checks(&self, side: Side) -> u8 {
+        self.board.get_variant_state::<ThreeCheckState>().map_or(0, |state| state.get_checks(side))
+    }
+
+    /// Check if castling follows the Chess960 rules, where castling is written as the king taking
+    /// its own rook
+    pub fn is_chess960(&self) -> bool {
+        self.board.is_chess960()
+    }
+
+    /// Write castling moves as the king taking its own rook, which is how Chess960 engines and
+    /// interfaces expect them
+    ///
+    /// Positions that do not have the standard castling squares are always Chess960 positions
+    pub fn set_chess960(&mut self, chess960: bool) {
+        if chess960 || self.board.has_standard_castling() {
+            self.board.set_chess960(chess960);
+        }
     }
 
     /// Check if a piece at index is selectable
@@ -84,25 +322,32 @@ impl Chess {
     /// chess.make_move(d2, d4);
     /// ```
     pub fn make_move(&mut self, start_index: usize, end_index: usize) -> bool {
-        if self.state == State::Promotion {
+        if self.state == State::Promotion || self.is_flagged() {
             return false;
         }
 
-        if let Some(piece) = self.board.get_piece_at_pos(start_index) {
-            if !self.board.move_piece(&piece, end_index) {
-                return false;
-            }
+        // a promoting pawn becomes a queen until promote replays the move with the chosen piece
+        let is_promoting = self.board.get_piece_type_at_pos(start_index) == PieceType::Pawn && self.board.is_last_rank(end_index);
+        let promotion = if is_promoting { Some(PieceType::Queen) } else { None };
 
-            let y = end_index / 8;
-            if piece.get_piece_type() == PieceType::Pawn && (y == 0 || y == 7) {
-                self.promoting_index = Some(end_index);
-            }
+        let mut board = self.board.clone();
+        if !board.make_move(Move::new(start_index, end_index, promotion)) {
+            return false;
+        }
+        // a promotion only completes the move once the piece is chosen
+        let clock = self.clock.clone();
+        if !is_promoting && !self.press_clock() {
+            return false;
+        }
+        self.history.push((std::mem::replace(&mut self.board, board), Move::new(start_index, end_index, None), clock));
 
-            self.update_state();
-            return true;
+        if is_promoting {
+            self.promoting_index = Some(end_index);
         }
 
-        false
+        self.update_state();
+        self.stop_finished_clock();
+        true
     }
 
     /// Get a vector of all coordinates that a certain piece at index is able to move to
@@ -117,30 +362,248 @@ impl Chess {
     /// }
     /// ```
     pub fn get_moves(&self, index: usize) -> Vec<(usize, usize)> {
-        let mut moves = vec![];
+        let mut moves: Vec<_> = self.get_legal_moves().iter()
+            .filter(|chess_move| chess_move.get_start() == index && chess_move.get_drop().is_none())
+            .map(|chess_move| self.board.get_coordinates(chess_move.get_end()))
+            .collect();
+        // promotions have one move per piece type
+        moves.dedup();
+
+        moves
+    }
 
-        if let Some(piece) = self.board.get_piece_at_pos(index) {
-            moves.extend(piece.get_possible_moves(&self.board).map(|index| (index % 8, index / 8)));
+    /// Play a move, with the promotion piece type included if a pawn reaches the last rank
+    ///
+    /// # Return: If the move was legal
+    ///
+    /// # Example:
+    /// ```
+    /// let mut chess = Chess::new();
+    /// chess.play_move(Move::from_uci("e2e4").unwrap());
+    /// ```
+    pub fn play_move(&mut self, chess_move: Move) -> bool {
+        if self.state == State::Promotion || self.is_flagged() {
+            return false;
         }
 
-        moves
+        let mut board = self.board.clone();
+        if !board.make_move(chess_move) {
+            return false;
+        }
+        let clock = self.clock.clone();
+        if !self.press_clock() {
+            return false;
+        }
+        self.history.push((std::mem::replace(&mut self.board, board), chess_move, clock));
+
+        self.update_state();
+        self.stop_finished_clock();
+        true
+    }
+
+    /// Play the game with a clock, the clock of the side to move starts right away
+    ///
+    /// Every move presses the clock, and a side that runs out of time is not able to move anymore
+    ///
+    /// # Example:
+    /// ```
+    /// let mut chess = Chess::new();
+    /// chess.set_clock(Clock::new(TimeControl::fischer(Duration::from_secs(300), Duration::from_secs(3))));
+    /// ```
+    pub fn set_clock(&mut self, mut clock: Clock) {
+        clock.start(self.get_playing_side());
+        self.clock = Some(clock);
+    }
+
+    /// Get the clock of the game
+    pub fn get_clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Remove the clock from the game
+    pub fn take_clock(&mut self) -> Option<Clock> {
+        self.clock.take()
+    }
+
+    /// Stop the clock of the game without completing a move
+    pub(crate) fn stop_clock(&mut self) {
+        if let Some(clock) = self.clock.as_mut() {
+            clock.stop();
+        }
+    }
+
+    fn is_flagged(&self) -> bool {
+        self.clock.as_ref().is_some_and(|clock| clock.get_flagged().is_some())
+    }
+
+    /// Press the clock for a move that is about to be played
+    ///
+    /// # Return: If the move was made in time, the move must not be played otherwise
+    fn press_clock(&mut self) -> bool {
+        match self.clock.as_mut() {
+            Some(clock) => clock.press(),
+            None => true,
+        }
+    }
+
+    fn stop_finished_clock(&mut self) {
+        let game_over = self.get_outcome().is_some();
+        if let Some(clock) = self.clock.as_mut().filter(|_| game_over) {
+            clock.stop();
+        }
+    }
+
+    /// Take back the last played move
+    ///
+    /// # Return: The move that was taken back, None if no moves have been played
+    ///
+    /// # Example:
+    /// ```
+    /// let mut chess = Chess::new();
+    /// chess.play_move(Move::from_uci("e2e4").unwrap());
+    /// chess.undo();
+    /// assert!(chess.get_fen() == Chess::new().get_fen());
+    /// ```
+    pub fn undo(&mut self) -> Option<Move> {
+        let (board, chess_move, clock) = self.history.pop()?;
+        self.board = board;
+        self.promoting_index = None;
+        self.update_state();
+
+        // the side to move gets back the time it had when it started thinking about the move
+        if let Some(mut clock) = clock {
+            clock.restart(self.get_playing_side());
+            self.clock = Some(clock);
+        }
+
+        Some(chess_move)
+    }
+
+    /// Get the FEN of the position before the first move of the history
+    pub fn get_start_fen(&self) -> String {
+        match self.history.first() {
+            Some((board, _, _)) => board.to_fen(),
+            None => self.get_fen(),
+        }
+    }
+
+    /// Get all moves played in this game, the first move first
+    pub fn get_history(&self) -> Vec<Move> {
+        self.history.iter().map(|(_, chess_move, _)| *chess_move).collect()
+    }
+
+    /// Get all legal moves for the current playing side
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// for chess_move in chess.get_legal_moves() {
+    ///     println!("{}", chess_move);
+    /// }
+    /// ```
+    pub fn get_legal_moves(&self) -> Vec<Move> {
+        if self.state == State::Promotion {
+            return vec![];
+        }
+
+        self.board.get_legal_moves()
+    }
+
+    /// Get the Zobrist key of the current position
+    pub fn get_zobrist_key(&self) -> u64 {
+        self.board.get_zobrist_key()
+    }
+
+    /// Count the number of leaf nodes of the legal move tree at depth
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// assert!(chess.perft(3) == 8902);
+    /// ```
+    pub fn perft(&self, depth: u32) -> u64 {
+        self.board.perft(depth)
+    }
+
+    /// Get how the game ended, None while it is still going on
+    ///
+    /// # Example:
+    /// ```
+    /// if let Some(outcome) = chess.get_outcome() {
+    ///     println!("{} by {:?}", outcome.result.to_pgn(), outcome.termination);
+    /// }
+    /// ```
+    pub fn get_outcome(&self) -> Option<Outcome> {
+        // a flag fall is only a loss if the opponent is able to checkmate at all
+        if let Some(side) = self.clock.as_ref().and_then(|clock| clock.get_flagged()) {
+            return match self.has_insufficient_material(side.get_opposite()) {
+                true => Some(Outcome::draw(Termination::TimeForfeit)),
+                false => Some(Outcome::loss(side, Termination::TimeForfeit)),
+            };
+        }
+
+        match self.state {
+            State::Checkmate => Some(Outcome::loss(self.get_playing_side(), Termination::Checkmate)),
+            State::Stalemate => Some(Outcome::draw(Termination::Stalemate)),
+            State::Draw => Some(Outcome::draw(self.get_draw_termination()?)),
+            State::VariantEnd => self.get_variant().get_outcome(&self.board),
+            _ => None,
+        }
+    }
+
+    /// Check if side does not have enough material left to ever checkmate
+    pub fn has_insufficient_material(&self, side: Side) -> bool {
+        self.get_variant().has_insufficient_material(&self.board, side)
+    }
+
+    /// Get the number of times the current position has occurred in this game, the current one included
+    ///
+    /// Only positions since the last capture or pawn move are able to repeat. A repetition does not
+    /// end the game by itself, a player or a match runner decides when it counts as a draw.
+    ///
+    /// # Example:
+    /// ```
+    /// if chess.get_repetition_count() >= 3 {
+    ///     println!("{}", Outcome::draw(Termination::Repetition).result.to_pgn());
+    /// }
+    /// ```
+    pub fn get_repetition_count(&self) -> usize {
+        let key = self.board.get_zobrist_key();
+        let reversible = self.board.get_moves_to_50().max(0) as usize;
+
+        1 + self.history.iter().rev()
+            .take(reversible)
+            .filter(|(board, _, _)| board.get_zobrist_key() == key)
+            .count()
+    }
+
+    fn get_draw_termination(&self) -> Option<Termination> {
+        if self.board.get_moves_to_50() >= 100 {
+            Some(Termination::FiftyMoves)
+        } else if self.get_variant().is_insufficient_material(&self.board) {
+            Some(Termination::InsufficientMaterial)
+        } else {
+            None
+        }
     }
 
     fn update_state(&mut self) {
-        if self.board.get_moves_to_50() >= 50 {
-            self.state = State::Draw;
-        } else if self.promoting_index != None {
+        if self.promoting_index.is_some() {
             self.state = State::Promotion;
-        } else if self.board.get_side_computed_boards(self.board.get_playing_side()).1.len() != 0 { 
-            if self.board.is_no_possible_moves(self.board.get_playing_side()) {
+        } else if self.get_variant().get_outcome(&self.board).is_some() {
+            self.state = State::VariantEnd;
+        } else if self.board.is_in_check() {
+            if self.board.get_legal_moves().is_empty() {
                 self.state = State::Checkmate;
+            } else if self.get_draw_termination().is_some() {
+                self.state = State::Draw;
             } else {
                 self.state = State::Check;
             }
-        } else if self.board.is_only_kings_left() {
-            self.state = State::Draw;
-        } else if self.board.is_no_possible_moves(self.board.get_playing_side()) {
+        } else if self.board.get_legal_moves().is_empty() {
             self.state = State::Stalemate;
+        } else if self.get_draw_termination().is_some() {
+            self.state = State::Draw;
         } else {
             self.state = State::Playing;
         }
@@ -159,14 +622,25 @@ impl Chess {
     /// }
     /// ```
     pub fn promote(&mut self, new_piece: PieceType) {
-        if let Some(index) = self.promoting_index {
-            let color = self.board.get_playing_side().get_opposite();
-            self.board.set_piece(index, PieceType::Pawn, color, false);
-            self.board.set_piece(index, new_piece, color, true);
+        if self.promoting_index.is_none() {
+            return;
+        }
+
+        // the move is played again from the previous board so that the variant sees the real promotion
+        if let Some((previous, chess_move, _)) = self.history.last() {
+            let promotion = Move::new(chess_move.get_start(), chess_move.get_end(), Some(new_piece));
+            let mut board = previous.clone();
+            if !board.make_move(promotion) || !self.press_clock() {
+                return;
+            }
 
+            self.board = board;
+            if let Some((_, chess_move, _)) = self.history.last_mut() {
+                *chess_move = promotion;
+            }
             self.promoting_index = None;
-            self.board.update_calculations();
             self.update_state();
+            self.stop_finished_clock();
         }
     }
 
@@ -205,5 +679,10 @@ impl Chess {
         self.board.get_playing_side()
     }
 
+    /// Get the fullmove number, which starts at 1 and goes up after every move of black
+    pub fn get_fullmove_number(&self) -> u32 {
+        self.board.get_move_counter()
+    }
+
     
 }
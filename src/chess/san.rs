@@ -0,0 +1,129 @@
+use crate::{Board, CastlingSide, Move, PieceType};
+
+use super::Chess;
+
+impl Chess {
+    /// Get the standard algebraic notation of a legal move, with check and mate marks
+    ///
+    /// # Return: None if the move is not legal
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// assert!(chess.to_san(Move::from_uci("g1f3").unwrap()) == Some("Nf3".to_string()));
+    /// ```
+    pub fn to_san(&self, chess_move: Move) -> Option<String> {
+        let moves = self.get_legal_moves();
+        if !moves.contains(&chess_move) {
+            return None;
+        }
+
+        let mut san = san_without_suffix(&self.board, chess_move, &moves);
+
+        let mut board = self.board.clone();
+        board.make_move(chess_move);
+        if board.is_in_check() {
+            san.push(if board.get_legal_moves().is_empty() { '#' } else { '+' });
+        }
+
+        Some(san)
+    }
+
+    /// Parse a move in standard algebraic notation for the current position
+    ///
+    /// Check marks, annotations like ! and ?, and a missing = before the promotion are allowed.
+    /// Drops are written like N@f3, the P of pawn drops may be left out.
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// assert!(chess.parse_san("Nf3").unwrap() == Move::from_uci("g1f3").unwrap());
+    /// ```
+    pub fn parse_san(&self, san: &str) -> Result<Move, String> {
+        let wanted = normalize(san);
+        if wanted.is_empty() {
+            return Err("Empty move".to_string());
+        }
+
+        let moves = self.get_legal_moves();
+        let mut found = moves.iter().filter(|chess_move| normalize(&san_without_suffix(&self.board, **chess_move, &moves)) == wanted);
+
+        match (found.next(), found.next()) {
+            (Some(chess_move), None) => Ok(*chess_move),
+            (Some(_), Some(_)) => Err(format!("Ambiguous move: {}", san)),
+            (None, _) => Err(format!("Illegal move: {}", san)),
+        }
+    }
+}
+
+fn normalize(san: &str) -> String {
+    let san = san.trim();
+    let san = match san.starts_with('@') {
+        true => format!("P{}", san),
+        false => san.to_string(),
+    };
+
+    san.replace('0', "O")
+        .chars()
+        .filter(|c| !matches!(c, '+' | '#' | '!' | '?' | '='))
+        .collect()
+}
+
+fn san_without_suffix(board: &Board, chess_move: Move, moves: &[Move]) -> String {
+    let (start, end) = (chess_move.get_start(), chess_move.get_end());
+    let dimensions = board.get_dimensions();
+    let square_to_string = |index| dimensions.square_to_string(index);
+    if let Some(piece) = chess_move.get_drop() {
+        return format!("{}@{}", piece.to_char().to_ascii_uppercase(), square_to_string(end));
+    }
+
+    let piece = board.get_piece_type_at_pos(start);
+
+    match board.get_castling_side(start, end) {
+        Some(CastlingSide::King) => return "O-O".to_string(),
+        Some(CastlingSide::Queen) => return "O-O-O".to_string(),
+        None => {},
+    }
+
+    let mut san = String::new();
+    let is_capture = board.is_capture(chess_move);
+
+    if piece == PieceType::Pawn {
+        if is_capture {
+            san.push(square_to_string(start).remove(0));
+        }
+    } else {
+        san.push(piece.to_char().to_ascii_uppercase());
+
+        // other pieces of the same type that are able to go to the same square
+        let others: Vec<_> = moves.iter()
+            .filter(|other| other.get_end() == end && other.get_start() != start)
+            .filter(|other| board.get_piece_type_at_pos(other.get_start()) == piece)
+            .map(|other| other.get_start())
+            .collect();
+
+        if !others.is_empty() {
+            let square = square_to_string(start);
+            let (x, y) = dimensions.get_coordinates(start);
+            if others.iter().all(|other| dimensions.get_coordinates(*other).0 != x) {
+                san.push_str(&square[..1]);
+            } else if others.iter().all(|other| dimensions.get_coordinates(*other).1 != y) {
+                san.push_str(&square[1..]);
+            } else {
+                san.push_str(&square);
+            }
+        }
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+    san.push_str(&square_to_string(end));
+
+    if let Some(promotion) = chess_move.get_promotion() {
+        san.push('=');
+        san.push(promotion.to_char().to_ascii_uppercase());
+    }
+
+    san
+}
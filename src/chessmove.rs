@@ -0,0 +1,157 @@
+use core::fmt::Display;
+
+use crate::{Dimensions, PieceType};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+/// A move of a piece from one index to another
+///
+/// The promotion piece type is only set when a pawn moves to the last rank. A drop puts a piece
+/// from the pocket on the end index, its start index is the end index as well.
+pub struct Move {
+    start: usize,
+    end: usize,
+    promotion: Option<PieceType>,
+    drop: Option<PieceType>,
+}
+
+impl Move {
+    /// Create a new move from index to another index
+    pub fn new(start: usize, end: usize, promotion: Option<PieceType>) -> Move {
+        Move {
+            start,
+            end,
+            promotion,
+            drop: None,
+        }
+    }
+
+    /// Create a move that drops a piece from the pocket on index
+    ///
+    /// # Example:
+    /// ```
+    /// let drop = Move::new_drop(PieceType::Knight, square_from_str("f3").unwrap());
+    /// assert!(drop.to_string() == "N@f3");
+    /// ```
+    pub fn new_drop(piece: PieceType, index: usize) -> Move {
+        Move {
+            start: index,
+            end: index,
+            promotion: None,
+            drop: Some(piece),
+        }
+    }
+
+    /// Parse a move written in UCI long algebraic notation, for example e2e4, e7e8q or the drop N@f3
+    ///
+    /// # Example:
+    /// ```
+    /// let e2e4 = Move::from_uci("e2e4").unwrap();
+    /// assert!(e2e4.get_start() == 12 && e2e4.get_end() == 28);
+    /// ```
+    pub fn from_uci(notation: &str) -> Result<Move, String> {
+        Move::from_uci_with(notation, Dimensions::STANDARD)
+    }
+
+    /// Parse a move written in UCI long algebraic notation on a board of dimensions, ranks above
+    /// the ninth are written with two digits like in a1a10
+    ///
+    /// # Example:
+    /// ```
+    /// let grand = Dimensions::new(10, 10).unwrap();
+    /// let promotion = Move::from_uci_with("j9j10c", grand).unwrap();
+    /// assert!(promotion.get_end() == 99 && promotion.get_promotion() == Some(PieceType::Chancellor));
+    /// ```
+    pub fn from_uci_with(notation: &str, dimensions: Dimensions) -> Result<Move, String> {
+        let invalid = || format!("Invalid move notation: {}", notation);
+        if let Some((piece, square)) = notation.split_once('@') {
+            let end = dimensions.square_from_str(square).ok_or_else(invalid)?;
+            let mut chars = piece.chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(invalid()),
+            };
+            return match PieceType::from_char(c) {
+                PieceType::Empty | PieceType::King => Err(format!("Invalid drop piece: {}", c)),
+                piece => Ok(Move::new_drop(piece, end)),
+            };
+        }
+
+        // every square starts with the letter of its file, so a letter after a digit starts the
+        // next part of the move
+        let mut parts = vec![];
+        for (i, c) in notation.char_indices() {
+            if c.is_ascii_alphabetic() && notation[..i].ends_with(|c: char| c.is_ascii_digit()) {
+                parts.push(i);
+            }
+        }
+
+        let (start, end, promotion) = match parts[..] {
+            [end] => (&notation[..end], &notation[end..], None),
+            [end, promotion] => (&notation[..end], &notation[end..promotion], Some(&notation[promotion..])),
+            _ => return Err(invalid()),
+        };
+
+        let start = dimensions.square_from_str(start).ok_or_else(invalid)?;
+        let end = dimensions.square_from_str(end).ok_or_else(invalid)?;
+        let promotion = match promotion {
+            Some(piece) => match (piece.len(), PieceType::from_char(piece.chars().next().unwrap())) {
+                // promoting to a king is only legal in variants like Antichess
+                (1, PieceType::Empty | PieceType::Pawn) | (2.., _) => return Err(format!("Invalid promotion piece: {}", piece)),
+                (_, piece) => Some(piece),
+            },
+            None => None,
+        };
+
+        Ok(Move::new(start, end, promotion))
+    }
+
+    /// Get the index that the piece moves from
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    /// Get the index that the piece moves to
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    /// Get the piece type that a pawn gets promoted to
+    pub fn get_promotion(&self) -> Option<PieceType> {
+        self.promotion
+    }
+
+    /// Get the piece type that is dropped from the pocket, None for moves of pieces on the board
+    pub fn get_drop(&self) -> Option<PieceType> {
+        self.drop
+    }
+
+    /// Get the move in UCI long algebraic notation on a board of dimensions
+    pub fn to_uci_with(&self, dimensions: Dimensions) -> String {
+        if let Some(piece) = self.drop {
+            return format!("{}@{}", piece.to_char().to_ascii_uppercase(), dimensions.square_to_string(self.end));
+        }
+
+        let mut uci = format!("{}{}", dimensions.square_to_string(self.start), dimensions.square_to_string(self.end));
+        if let Some(promotion) = self.promotion {
+            uci.push(promotion.to_char());
+        }
+        uci
+    }
+}
+
+impl Display for Move {
+    /// Written in UCI notation of a standard board, use to_uci_with for boards of other dimensions
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_uci_with(Dimensions::STANDARD))
+    }
+}
+
+/// Get the index of a square of a standard board written like e4
+pub fn square_from_str(square: &str) -> Option<usize> {
+    Dimensions::STANDARD.square_from_str(square)
+}
+
+/// Get the name of the square at index of a standard board, for example e4
+pub fn square_to_string(index: usize) -> String {
+    Dimensions::STANDARD.square_to_string(index)
+}
@@ -1,100 +1,438 @@
-use std::{char, io::{self, Write}, time::{SystemTime, UNIX_EPOCH}};
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
 
-use crate::{Board, Chess, Side};
+use crate::search::{self, Search, SearchLimits};
+use crate::{pgn, variant, Chess, Move, Side, State};
 
-pub fn start() {
-    let mut chess = Chess::default();
-    
-    loop {
-        let mut input = String::new();
+const DEFAULT_HINT_DEPTH: u32 = 5;
 
-        input.clear();
-        print!("> ");
-        let _ = io::stdout().flush();
-        io::stdin().read_line(&mut input).unwrap();
+const HELP: &str = "\
+show                 show the board
+<move>, move <move>  play a move in SAN like Nf3 or UCI like g1f3
+undo                 take back the last move
+moves                list the legal moves
+fen [fen]            print the FEN or start from a FEN
+new [variant]        start a new game
+load <file>          load the first game of a PGN file
+save <file>          save the game as PGN
+pgn                  print the game as PGN
+perft <depth>        count the leaf nodes of the move tree
+hint [depth]         ask the engine for a move
+state                print the state of the game
+turn                 print the side to move
+attacks <square>     list the moves of a piece
+pinned <w|b>         show the pinned pieces of a side
+aa <w|b>             show the squares attacked by a side
+oae <w|b>            show the empty squares and the pieces of the opponent of a side
+white, black         show the pieces of a side
+help                 show this help
+quit, exit           leave
 
-        let split : Vec<_> = input.trim().split_whitespace().collect();
+Run chess --batch [file] to read commands from a file or stdin and get one JSON object per command";
 
-        if split.len() == 0 {
-            continue;
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// A command of the command line interface
+///
+/// # Show: Show the board
+/// # Move: Play a move written in SAN or UCI
+/// # Undo: Take back the last move
+/// # Moves: List the legal moves
+/// # Fen: Print the FEN, or start a new game of the same variant from a FEN
+/// # New: Start a new game, of a variant if one is named
+/// # Load: Load the first game of a PGN file
+/// # Save: Save the game to a PGN file
+/// # Pgn: Print the game as PGN
+/// # Perft: Count the leaf nodes of the move tree at a depth
+/// # Hint: Search for the best move up to a depth
+/// # State: Print the state of the game
+/// # Turn: Print the side to move
+/// # Attacks: List the moves of the piece on a square
+/// # Pinned: Show the pinned pieces of a side
+/// # Attacked: Show the squares attacked by a side
+/// # OpponentAndEmpty: Show the empty squares and the pieces of the opponent of a side
+/// # Pieces: Show the pieces of a side
+/// # Help: List the commands
+/// # Quit: Leave the interface
+///
+pub enum Command {
+    Show,
+    Move(String),
+    Undo,
+    Moves,
+    Fen(Option<String>),
+    New(Option<String>),
+    Load(String),
+    Save(String),
+    Pgn,
+    Perft(u32),
+    Hint(u32),
+    State,
+    Turn,
+    Attacks(String),
+    Pinned(Side),
+    Attacked(Side),
+    OpponentAndEmpty(Side),
+    Pieces(Side),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parse one line of input, a single word that is not a command is taken as a move
+    ///
+    /// # Example:
+    /// ```
+    /// assert!(Command::parse("e4") == Ok(Command::Move("e4".to_string())));
+    /// assert!(Command::parse("perft 3") == Ok(Command::Perft(3)));
+    /// ```
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        let Some(&name) = tokens.first() else {
+            return Err("Empty command".to_string());
+        };
+        let args = &tokens[1..];
+        let arg = |usage: &str| args.first().map(|arg| arg.to_string()).ok_or(format!("Usage: {}", usage));
+        let rest = || match args.is_empty() {
+            true => None,
+            false => Some(args.join(" ")),
+        };
+
+        Ok(match name {
+            "show" | "board" | "d" => Command::Show,
+            // the old form move e2 e4 is still understood
+            "move" | "m" => match args.is_empty() {
+                true => return Err("Usage: move <move>".to_string()),
+                false => Command::Move(args.concat()),
+            },
+            "undo" | "u" => Command::Undo,
+            "moves" => Command::Moves,
+            "fen" => Command::Fen(rest()),
+            "new" => Command::New(rest()),
+            "load" => Command::Load(arg("load <file>")?),
+            "save" => Command::Save(arg("save <file>")?),
+            "pgn" => Command::Pgn,
+            "perft" => Command::Perft(parse_depth(&arg("perft <depth>")?)?),
+            "hint" => Command::Hint(match args.first() {
+                Some(depth) => parse_depth(depth)?,
+                None => DEFAULT_HINT_DEPTH,
+            }),
+            "state" => Command::State,
+            "turn" => Command::Turn,
+            "attacks" | "a" => Command::Attacks(arg("attacks <square>")?),
+            "pinned" => Command::Pinned(parse_side(&arg("pinned <w|b>")?)?),
+            "aa" => Command::Attacked(parse_side(&arg("aa <w|b>")?)?),
+            "oae" => Command::OpponentAndEmpty(parse_side(&arg("oae <w|b>")?)?),
+            "white" => Command::Pieces(Side::White),
+            "black" => Command::Pieces(Side::Black),
+            "help" | "?" => Command::Help,
+            "quit" | "exit" | "q" => Command::Quit,
+            _ if args.is_empty() => Command::Move(name.to_string()),
+            _ => return Err(format!("Unknown command: {}, type help for a list of commands", name)),
+        })
+    }
+}
+
+fn parse_depth(depth: &str) -> Result<u32, String> {
+    depth.parse().map_err(|_| format!("Invalid depth: {}", depth))
+}
+
+fn parse_side(side: &str) -> Result<Side, String> {
+    match side {
+        "white" | "w" => Ok(Side::White),
+        "black" | "b" => Ok(Side::Black),
+        _ => Err(format!("Invalid side: {}, use w or b", side)),
+    }
+}
+
+/// A game played through commands
+///
+/// # Example:
+/// ```
+/// let mut session = Session::new();
+/// session.execute(&Command::parse("e4").unwrap()).unwrap();
+/// println!("{}", session.execute(&Command::Show).unwrap());
+/// ```
+pub struct Session {
+    chess: Chess,
+    search: Search, // kept for every hint, so its table is only allocated once
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::new()
+    }
+}
+
+impl Session {
+    /// Create a session with a standard game
+    pub fn new() -> Session {
+        Session {
+            chess: Chess::new(),
+            search: Search::new(SearchLimits::default(), Arc::new(AtomicBool::new(false))),
         }
+    }
 
-        let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    /// Get the game of the session
+    pub fn get_chess(&self) -> &Chess {
+        &self.chess
+    }
 
-        match split[0] {
-            "fen" => {
-                if split.len() <= 1 {
-                    continue;
+    /// Run a command on the game
+    ///
+    /// # Return: The text to show, or why the command failed
+    pub fn execute(&mut self, command: &Command) -> Result<String, String> {
+        let chess = &mut self.chess;
+        let dimensions = chess.board.get_dimensions();
+
+        Ok(match command {
+            Command::Show => render(chess),
+            Command::Move(notation) => {
+                let chess_move = parse_move(chess, notation)?;
+                let san = chess.to_san(chess_move).unwrap_or_else(|| chess_move.to_uci_with(dimensions));
+                if !chess.play_move(chess_move) {
+                    return Err(format!("Illegal move: {}", notation));
                 }
-                chess.board = Board::from_fen(split[1..].join(" ")).unwrap()
+                format!("{}{}", san, describe_end(chess))
             },
-            "move" | "m" => { 
-                if split.len() <= 2 {
-                    continue;
-                }
-                chess.make_move(notation_to_index(split[1]), notation_to_index(split[2]));
+            Command::Undo => match chess.undo() {
+                Some(chess_move) => format!("Took back {}", chess_move.to_uci_with(dimensions)),
+                None => return Err("No moves to take back".to_string()),
             },
-            "attacks" | "a" => {
-                if split.len() <= 1 {
-                    continue;
-                }
-                let index = notation_to_index(split[1]);
-
-                if let Some(piece) = chess.board.get_piece_at_pos(index) {
-                    println!("{}", piece);
-                }
-                for pmove in chess.get_moves(index) {
-                    println!("{}{}", (pmove.0 as u8 + b'A') as char, pmove.1 + 1);
+            Command::Moves => {
+                let moves: Vec<_> = chess.get_legal_moves().into_iter()
+                    .map(|chess_move| chess.to_san(chess_move).unwrap_or_else(|| chess_move.to_uci_with(dimensions)))
+                    .collect();
+                match moves.is_empty() {
+                    true => "No legal moves".to_string(),
+                    false => moves.join(" "),
                 }
             },
-            "pinned" => {
-                if split.len() <= 1 {
-                    continue;
-                }
-                match split[1] {
-                    "white" | "w" => println!("{}", chess.board.get_side_computed_boards(Side::White).0),
-                    "black" | "b" => println!("{}", chess.board.get_side_computed_boards(Side::White).0),
-                    _ => {}
-                }
+            Command::Fen(None) => chess.get_fen(),
+            Command::Fen(Some(fen)) => {
+                // a whole new game, so the history and state belong to the new position
+                let mut new_chess = Chess::try_from_fen_variant(fen.clone(), chess.get_variant().clone())?;
+                new_chess.set_chess960(chess.is_chess960());
+                *chess = new_chess;
+                render(chess)
             },
-            "aa" => {
-                if split.len() <= 1 {
-                    continue;
-                }
-                match split[1] {
-                    "white" | "w" => println!("{}", chess.board.get_side_computed_boards(Side::White).2),
-                    "black" | "b" => println!("{}", chess.board.get_side_computed_boards(Side::Black).2),
-                    _ => {}
-                }
-            }
-            "oae" => {
-                if split.len() <= 1 {
-                    continue;
-                }
-                match split[1] {
-                    "white" | "w" => println!("{}", chess.board.get_opponent_and_empty_squares_board(Side::White)),
-                    "black" | "b" => println!("{}", chess.board.get_opponent_and_empty_squares_board(Side::Black)),
-                    _ => {}
-                }
+            Command::New(name) => {
+                *chess = match name {
+                    Some(name) => Chess::new_variant(variant::from_name(name).ok_or(format!("Unknown variant: {}", name))?)?,
+                    None => Chess::new(),
+                };
+                render(chess)
+            },
+            Command::Load(path) => {
+                let text = std::fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+                let game = pgn::parse_pgn(&text)?.into_iter().next().ok_or(format!("No game in {}", path))?;
+                *chess = game.chess;
+                render(chess)
             },
-            "white" => chess.board.print_side(Side::White),
-            "black" => chess.board.print_side(Side::Black),
-            "state" => println!("{:?}", chess.get_state()),
-            "turn" => println!("{:?}", chess.board.get_playing_side()),
-            "exit" => break,
-            _ => {}
+            Command::Save(path) => {
+                std::fs::write(path, pgn::write_pgn(&[], chess, get_result(chess)))
+                    .map_err(|error| format!("Could not write {}: {}", path, error))?;
+                format!("Saved to {}", path)
+            },
+            Command::Pgn => pgn::write_pgn(&[], chess, get_result(chess)).trim_end().to_string(),
+            Command::Perft(depth) => {
+                let start = Instant::now();
+                let nodes = chess.perft(*depth);
+                format!("{} nodes in {}ms", nodes, start.elapsed().as_millis())
+            },
+            Command::Hint(depth) => {
+                self.search.set_limits(SearchLimits { depth: Some(*depth), ..Default::default() });
+                let result = self.search.run(&chess.board);
+                let chess_move = result.best_move.ok_or("No legal moves")?;
+                let san = chess.to_san(chess_move).unwrap_or_else(|| chess_move.to_uci_with(dimensions));
+                let score = match search::mate_in(result.score) {
+                    Some(moves) => format!("mate {}", moves),
+                    None => format!("cp {}", result.score),
+                };
+                format!("{} (score {}, depth {})", san, score, result.depth)
+            },
+            Command::State => format!("{:?}{}", chess.get_state(), describe_end(chess)),
+            Command::Turn => format!("{:?}", chess.get_playing_side()),
+            Command::Attacks(square) => {
+                let index = dimensions.square_from_str(square).ok_or(format!("Invalid square: {}", square))?;
+                let piece = chess.board.get_piece_at_pos(index).ok_or(format!("No piece on {}", square))?;
+                let moves: Vec<_> = chess.get_moves(index).into_iter()
+                    .map(|(x, y)| dimensions.square_to_string(dimensions.get_index(x, y)))
+                    .collect();
+                format!("{}: {}", piece, moves.join(" "))
+            },
+            Command::Pinned(side) => chess.board.get_side_computed_boards(*side).0.to_string_with(dimensions),
+            Command::Attacked(side) => chess.board.get_side_computed_boards(*side).2.to_string_with(dimensions),
+            Command::OpponentAndEmpty(side) => chess.board.get_opponent_and_empty_squares_board(*side).to_string_with(dimensions),
+            Command::Pieces(side) => chess.board.get_sides_board(*side).to_string_with(dimensions),
+            Command::Help => HELP.to_string(),
+            Command::Quit => String::new(),
+        })
+    }
+
+    /// Run one line of input and describe the game after it as a JSON object on one line
+    ///
+    /// The object has the command, if it worked, its output or error, and the FEN, side to move,
+    /// state, result and legal moves in UCI of the game after it.
+    ///
+    /// # Return: The object, as an error if the command failed
+    ///
+    /// # Example:
+    /// ```
+    /// let mut session = Session::new();
+    /// assert!(session.execute_json("e4").is_ok());
+    /// assert!(session.execute_json("e4").unwrap_err().contains("\"ok\":false"));
+    /// ```
+    pub fn execute_json(&mut self, line: &str) -> Result<String, String> {
+        let line = line.trim();
+        let outcome = Command::parse(line).and_then(|command| self.execute(&command));
+        let json = self.to_json(line, &outcome);
+        match outcome {
+            Ok(_) => Ok(json),
+            Err(_) => Err(json),
         }
+    }
+
+    fn to_json(&self, line: &str, outcome: &Result<String, String>) -> String {
+        let (output, error) = match outcome {
+            Ok(output) => (to_json_string(output), "null".to_string()),
+            Err(error) => ("null".to_string(), to_json_string(error)),
+        };
 
-        let end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let chess = &self.chess;
+        let dimensions = chess.board.get_dimensions();
+        let moves: Vec<_> = chess.get_legal_moves().into_iter()
+            .map(|chess_move| to_json_string(&chess_move.to_uci_with(dimensions)))
+            .collect();
+        let result = match chess.get_outcome() {
+            Some(outcome) => to_json_string(outcome.result.to_pgn()),
+            None => "null".to_string(),
+        };
 
-        println!("Time taken: {}us", (end - start).as_micros());
+        format!(
+            "{{\"command\":{},\"ok\":{},\"output\":{},\"error\":{},\"fen\":{},\"turn\":\"{:?}\",\"state\":\"{:?}\",\"result\":{},\"moves\":[{}]}}",
+            to_json_string(line), outcome.is_ok(), output, error, to_json_string(&chess.get_fen()),
+            chess.get_playing_side(), chess.get_state(), result, moves.join(","),
+        )
     }
 }
 
-fn notation_to_index(move_notation: &str) -> usize {
-    let lowercase = move_notation.to_lowercase();
-    let mut chars = lowercase.chars();
-    let file = (chars.next().unwrap() as u8) - b'a';
-    let rank = (chars.next().unwrap() as u8) - b'1';
-    (rank * 8 + file) as usize
+/// Write text as a JSON string with quotes
+fn to_json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+/// Parse a move in SAN, or in UCI if it is not valid SAN, and check that it is legal
+fn parse_move(chess: &Chess, notation: &str) -> Result<Move, String> {
+    let san_error = match chess.parse_san(notation) {
+        Ok(chess_move) => return Ok(chess_move),
+        Err(error) => error,
+    };
+
+    // not UCI either, so the reason SAN failed is the better error
+    let chess_move = Move::from_uci_with(notation, chess.board.get_dimensions()).map_err(|_| san_error)?;
+    match chess.get_legal_moves().contains(&chess_move) {
+        true => Ok(chess_move),
+        false => Err(format!("Illegal move: {}", notation)),
+    }
+}
+
+fn get_result(chess: &Chess) -> &'static str {
+    chess.get_outcome().map_or("*", |outcome| outcome.result.to_pgn())
+}
+
+/// Describe how the game ended, empty while it is going on
+fn describe_end(chess: &Chess) -> String {
+    match chess.get_outcome() {
+        Some(outcome) => format!("\nGame over: {} by {:?}", outcome.result.to_pgn(), outcome.termination),
+        None if chess.get_state() == State::Check => " (check)".to_string(),
+        None => String::new(),
+    }
+}
+
+/// Draw the board with white at the bottom and the side to move below it
+fn render(chess: &Chess) -> String {
+    format!("{}{:?} to move", chess.board, chess.get_playing_side())
+}
+
+/// Run commands read from input without a prompt, writing one JSON object per command to output
+///
+/// Empty lines and lines starting with # are skipped, and quit ends the input early.
+///
+/// # Return: The number of commands that failed
+///
+/// # Example:
+/// ```
+/// let script = "e4\ne5\nNf3\n";
+/// let failed = cli::run_batch(script.as_bytes(), std::io::stdout()).unwrap();
+/// assert!(failed == 0);
+/// ```
+pub fn run_batch(input: impl BufRead, mut output: impl Write) -> io::Result<usize> {
+    let mut session = Session::new();
+    let mut failed = 0;
+
+    for line in input.lines() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let json = session.execute_json(trimmed).unwrap_or_else(|json| {
+            failed += 1;
+            json
+        });
+        writeln!(output, "{}", json)?;
+
+        if Command::parse(trimmed) == Ok(Command::Quit) {
+            break;
+        }
+    }
+    output.flush()?;
+
+    Ok(failed)
+}
+
+/// Run the interactive command line interface on stdin and stdout until quit or the end of input
+pub fn start() {
+    let mut session = Session::new();
+    println!("{}", render(session.get_chess()));
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match Command::parse(&line) {
+            Ok(Command::Quit) => break,
+            Ok(command) => match session.execute(&command) {
+                Ok(output) => println!("{}", output),
+                Err(error) => println!("Error: {}", error),
+            },
+            Err(error) => println!("Error: {}", error),
+        }
+    }
 }
@@ -0,0 +1,335 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::Side;
+
+/// Source of the current time for a clock
+///
+/// Only the difference between two readings is used, so any fixed starting point works
+pub trait TimeSource: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// Real time from the system's monotonic clock
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[derive(Clone, Default)]
+/// Time that only moves when it is told to, clones share the same time
+///
+/// # Example:
+/// ```
+/// let time = ManualTime::default();
+/// let mut clock = Clock::with_time_source(TimeControl::sudden_death(Duration::from_secs(60)), Arc::new(time.clone()));
+/// clock.start(Side::White);
+/// time.advance(Duration::from_secs(5));
+/// assert!(clock.get_remaining(Side::White) == Duration::from_secs(55));
+/// ```
+pub struct ManualTime {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualTime {
+    /// Move the time forward
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Time that is given back for every move
+///
+/// # None: No time is given back
+/// # Fischer: The increment is added after every move
+/// # Bronstein: The time used is given back after every move, up to the delay
+/// # Simple: The clock only starts running after the delay has passed
+///
+pub enum Delay {
+    None,
+    Fischer(Duration),
+    Bronstein(Duration),
+    Simple(Duration),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// One period of a time control
+///
+/// # moves: The number of moves to be played in time, None for the rest of the game
+/// # time: Time added to the clock at the start of the period
+/// # delay: Time given back during the period
+///
+pub struct Period {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub delay: Delay,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// The periods of a time control, the last one has to last for the rest of the game
+///
+/// # Example:
+/// ```
+/// // 40 moves in 90 minutes, then 30 minutes for the rest of the game, 30 seconds increment from the start
+/// let classical = TimeControl::parse("40/5400+30:1800+30").unwrap();
+/// ```
+pub struct TimeControl {
+    periods: Vec<Period>,
+}
+
+impl TimeControl {
+    /// Create a time control from its periods
+    pub fn new(mut periods: Vec<Period>) -> Result<TimeControl, String> {
+        match periods.last_mut() {
+            Some(last) => last.moves = None,
+            None => return Err("A time control needs at least one period".to_string()),
+        }
+        if periods.iter().any(|period| period.moves == Some(0)) {
+            return Err("A period needs at least one move".to_string());
+        }
+
+        Ok(TimeControl { periods })
+    }
+
+    /// All moves have to be played in time
+    pub fn sudden_death(time: Duration) -> TimeControl {
+        TimeControl::single(time, Delay::None)
+    }
+
+    /// Increment added after every move
+    pub fn fischer(time: Duration, increment: Duration) -> TimeControl {
+        TimeControl::single(time, Delay::Fischer(increment))
+    }
+
+    /// The time used for a move is given back up to the delay
+    pub fn bronstein(time: Duration, delay: Duration) -> TimeControl {
+        TimeControl::single(time, Delay::Bronstein(delay))
+    }
+
+    /// The clock waits for the delay before it starts running
+    pub fn simple_delay(time: Duration, delay: Duration) -> TimeControl {
+        TimeControl::single(time, Delay::Simple(delay))
+    }
+
+    /// Parse a time control as written in the PGN TimeControl tag, with times in seconds
+    ///
+    /// Periods are separated by ':', and are either moves/seconds or seconds, with an optional
+    /// +increment
+    pub fn parse(notation: &str) -> Result<TimeControl, String> {
+        let invalid = || format!("Invalid time control: {}", notation);
+        let seconds = |value: &str| value.parse::<f64>().ok().filter(|value| *value >= 0.0).map(Duration::from_secs_f64);
+
+        let periods = notation.split(':').map(|period| {
+            let (period, increment) = match period.split_once('+') {
+                Some((period, increment)) => (period, Delay::Fischer(seconds(increment).ok_or_else(invalid)?)),
+                None => (period, Delay::None),
+            };
+            let (moves, time) = match period.split_once('/') {
+                Some((moves, time)) => (Some(moves.parse().map_err(|_| invalid())?), time),
+                None => (None, period),
+            };
+
+            Ok(Period { moves, time: seconds(time).ok_or_else(invalid)?, delay: increment })
+        }).collect::<Result<Vec<_>, String>>()?;
+
+        TimeControl::new(periods)
+    }
+
+    /// Get the periods of the time control
+    pub fn get_periods(&self) -> &[Period] {
+        &self.periods
+    }
+
+    fn single(time: Duration, delay: Delay) -> TimeControl {
+        TimeControl {
+            periods: vec![Period { moves: None, time, delay }],
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Clock of both players, for a time control or for one time control per side when giving time odds
+///
+/// The clock of one side runs at a time, pressing it stops that side and starts the other side.
+///
+/// # Example:
+/// ```
+/// let mut clock = Clock::new(TimeControl::fischer(Duration::from_secs(180), Duration::from_secs(2)));
+/// clock.start(Side::White);
+/// // white thinks and moves
+/// clock.press();
+/// if let Some(side) = clock.get_flagged() {
+///     println!("{:?} lost on time", side);
+/// }
+/// ```
+pub struct Clock {
+    controls: [TimeControl; 2],  // [0: white, 1: black]
+    remaining: [Duration; 2],    // time left when the clock of the side was stopped last
+    moves: [u32; 2],             // moves played in the current period
+    period: [usize; 2],          // index of the current period
+    running: Option<(Side, Duration)>, // side whose clock runs and the time it was started
+    flagged: Option<Side>,
+    time_source: Arc<dyn TimeSource>,
+}
+
+impl Clock {
+    /// Create a clock with the same time control for both sides using the system time
+    pub fn new(control: TimeControl) -> Clock {
+        Clock::with_odds(control.clone(), control, Arc::new(SystemClock::default()))
+    }
+
+    /// Create a clock with the same time control for both sides
+    pub fn with_time_source(control: TimeControl, time_source: Arc<dyn TimeSource>) -> Clock {
+        Clock::with_odds(control.clone(), control, time_source)
+    }
+
+    /// Create a clock with a different time control per side
+    pub fn with_odds(white: TimeControl, black: TimeControl, time_source: Arc<dyn TimeSource>) -> Clock {
+        let remaining = [white.periods[0].time, black.periods[0].time];
+
+        Clock {
+            controls: [white, black],
+            remaining,
+            moves: [0, 0],
+            period: [0, 0],
+            running: None,
+            flagged: None,
+            time_source,
+        }
+    }
+
+    /// Start the clock of side, stopping the other one
+    pub fn start(&mut self, side: Side) {
+        self.stop();
+        if self.flagged.is_none() {
+            self.running = Some((side, self.time_source.now()));
+        }
+    }
+
+    /// Start the clock of side from now, without charging anyone for the time since it was last started
+    ///
+    /// Used to go on with a copy of the clock from earlier in the game
+    pub fn restart(&mut self, side: Side) {
+        self.running = None;
+        self.start(side);
+    }
+
+    /// Stop the running clock without completing a move
+    pub fn stop(&mut self) {
+        if let Some((side, started)) = self.running.take() {
+            let used = self.charged_time(side, self.time_source.now().saturating_sub(started));
+            self.charge(side, used);
+        }
+    }
+
+    /// Complete the move of the running side and start the clock of the other side
+    ///
+    /// # Return: If the move was made in time
+    pub fn press(&mut self) -> bool {
+        let (side, started) = match self.running.take() {
+            Some(running) => running,
+            None => return false,
+        };
+
+        let elapsed = self.time_source.now().saturating_sub(started);
+        let used = self.charged_time(side, elapsed);
+        if !self.charge(side, used) {
+            return false;
+        }
+
+        let color = side.to_index();
+        match self.get_period(side).delay {
+            Delay::Fischer(increment) => self.remaining[color] += increment,
+            Delay::Bronstein(delay) => self.remaining[color] += elapsed.min(delay),
+            Delay::None | Delay::Simple(_) => {},
+        }
+
+        // the time of the next period is added once the moves of this period are played
+        self.moves[color] += 1;
+        if self.get_period(side).moves == Some(self.moves[color]) {
+            self.period[color] += 1;
+            self.moves[color] = 0;
+            self.remaining[color] += self.get_period(side).time;
+        }
+
+        self.running = Some((side.get_opposite(), self.time_source.now()));
+        true
+    }
+
+    /// Get the time left of side, counting the time of the running clock
+    pub fn get_remaining(&self, side: Side) -> Duration {
+        let remaining = self.remaining[side.to_index()];
+        match self.running {
+            Some((running, started)) if running == side => {
+                remaining.saturating_sub(self.charged_time(side, self.time_source.now().saturating_sub(started)))
+            },
+            _ => remaining,
+        }
+    }
+
+    /// Get the side whose time ran out, if any
+    pub fn get_flagged(&self) -> Option<Side> {
+        if self.flagged.is_some() {
+            return self.flagged;
+        }
+
+        match self.running {
+            Some((side, _)) if self.get_remaining(side).is_zero() => Some(side),
+            _ => None,
+        }
+    }
+
+    /// Get the side whose clock is running
+    pub fn get_running(&self) -> Option<Side> {
+        self.running.map(|(side, _)| side)
+    }
+
+    /// Get the number of moves side has left to play in the current period, None in the last period
+    pub fn get_moves_to_go(&self, side: Side) -> Option<u32> {
+        self.get_period(side).moves.map(|moves| moves - self.moves[side.to_index()])
+    }
+
+    fn get_period(&self, side: Side) -> Period {
+        let color = side.to_index();
+        self.controls[color].periods[self.period[color]]
+    }
+
+    /// Time that counts against the clock for a move that took elapsed
+    fn charged_time(&self, side: Side, elapsed: Duration) -> Duration {
+        match self.get_period(side).delay {
+            Delay::Simple(delay) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        }
+    }
+
+    /// Take used from the time of side, flagging side when it is more than what is left
+    fn charge(&mut self, side: Side, used: Duration) -> bool {
+        let remaining = &mut self.remaining[side.to_index()];
+        if used >= *remaining {
+            *remaining = Duration::ZERO;
+            self.flagged = Some(side);
+            return false;
+        }
+
+        *remaining -= used;
+        true
+    }
+}
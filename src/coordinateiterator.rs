@@ -1,5 +1,7 @@
 use std::iter::Iterator;
 
+use crate::Dimensions;
+
 pub struct CoordinateIterator {
     current: (usize, usize),
     end: (usize, usize),
@@ -19,33 +21,15 @@ impl CoordinateIterator {
         }
     }
 
-    pub fn from_inclusive_to(start: (usize, usize), end: (usize, usize)) -> Self {
+    pub fn from_delta(start: (usize, usize), delta: (isize, isize), dimensions: Dimensions) -> Self {
         CoordinateIterator {
             current: start,
-            end,
-            dx: (end.0 as isize - start.0 as isize).signum(),
-            dy: (end.1 as isize - start.1 as isize).signum(),
-            inclusive: true,
-        }
-    }
-
-    pub fn from_delta(start: (usize, usize), delta: (isize, isize)) -> Self {
-        CoordinateIterator {
-            current: start,
-            end: (if delta.0 < 0 {0} else {8}, if delta.1 < 0 {0} else {8}),
+            end: (if delta.0 < 0 {0} else {dimensions.get_width()}, if delta.1 < 0 {0} else {dimensions.get_height()}),
             dx: delta.0,
             dy: delta.1,
             inclusive: false,
         }
     }
-
-    pub fn contains(&mut self, coord: (usize, usize)) -> bool {
-        self.find(|(x, y)| *x == coord.0 && *y == coord.1) != None
-    }
-
-    pub fn get_change(&self) -> (isize, isize) {
-        (self.dx, self.dy)
-    }
 }
 
 impl Iterator for CoordinateIterator {
@@ -0,0 +1,135 @@
+use crate::{bitboard, BitBoard, NUM_INDECES};
+
+/// The most files or ranks a board is able to have, the files are named from a to p
+pub(crate) const MAX_FILES: usize = 16;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+/// The number of files and ranks of a board
+///
+/// Squares are numbered rank by rank starting at a1, so the index of a square is y * width + x.
+/// Boards have at most 16 files, 16 ranks and 128 squares.
+///
+/// # Example:
+/// ```
+/// let capablanca = Dimensions::new(10, 8).unwrap();
+/// assert!(capablanca.square_from_str("j8") == Some(79));
+/// assert!(capablanca.square_to_string(79) == "j8");
+/// ```
+pub struct Dimensions {
+    width: usize,
+    height: usize,
+}
+
+impl Default for Dimensions {
+    fn default() -> Self {
+        Dimensions::STANDARD
+    }
+}
+
+impl Dimensions {
+    /// The 8x8 board of standard chess
+    pub const STANDARD: Dimensions = Dimensions { width: 8, height: 8 };
+
+    /// Create the dimensions of a board with width files and height ranks
+    pub fn new(width: usize, height: usize) -> Result<Dimensions, String> {
+        if !(1..=MAX_FILES).contains(&width) || !(1..=MAX_FILES).contains(&height) || width * height > NUM_INDECES {
+            return Err(format!("Invalid board size {}x{}, at most {} files, {} ranks and {} squares are allowed", width, height, MAX_FILES, MAX_FILES, NUM_INDECES));
+        }
+
+        Ok(Dimensions { width, height })
+    }
+
+    /// Get the number of files
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the number of ranks
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the number of squares
+    pub fn get_num_squares(&self) -> usize {
+        self.width * self.height
+    }
+
+    #[inline]
+    /// Get the index of the square on file x and rank y, both starting at 0
+    pub fn get_index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    #[inline]
+    /// Get the file and rank of the square at index, both starting at 0
+    pub fn get_coordinates(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    #[inline]
+    /// Check if file x and rank y are on the board
+    pub fn is_inbounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    #[inline]
+    /// Get the index of the square that is dx files and dy ranks away from index, None if it is
+    /// off the board
+    pub fn get_offset(&self, index: usize, dx: isize, dy: isize) -> Option<usize> {
+        let (x, y) = self.get_coordinates(index);
+        let (nx, ny) = (x as isize + dx, y as isize + dy);
+        match nx >= 0 && ny >= 0 && self.is_inbounds(nx as usize, ny as usize) {
+            true => Some(self.get_index(nx as usize, ny as usize)),
+            false => None,
+        }
+    }
+
+    /// Get the index of a square written like e4 or a10
+    pub fn square_from_str(&self, square: &str) -> Option<usize> {
+        let mut chars = square.chars();
+        let file = chars.next()?.to_ascii_lowercase();
+        let rank = chars.as_str();
+        if !file.is_ascii_lowercase() || rank.is_empty() || !rank.chars().all(|c| c.is_ascii_digit()) || rank.starts_with('0') {
+            return None;
+        }
+
+        let (x, y) = ((file as u8 - b'a') as usize, rank.parse::<usize>().ok()? - 1);
+        match self.is_inbounds(x, y) {
+            true => Some(self.get_index(x, y)),
+            false => None,
+        }
+    }
+
+    /// Get the name of the square at index, for example e4
+    pub fn square_to_string(&self, index: usize) -> String {
+        let (x, y) = self.get_coordinates(index);
+        format!("{}{}", (x as u8 + b'a') as char, y + 1)
+    }
+
+    /// Get every square of the board
+    pub(crate) fn get_all(&self) -> BitBoard {
+        let num_squares = self.get_num_squares();
+        match num_squares {
+            NUM_INDECES => !bitboard::EMPTY,
+            _ => BitBoard::from_number((1 << num_squares) - 1),
+        }
+    }
+
+    /// Get the squares of file x
+    pub(crate) fn get_file(&self, x: usize) -> BitBoard {
+        let mut board = bitboard::EMPTY;
+        for y in 0..self.height {
+            board.set(self.get_index(x, y), true);
+        }
+        board
+    }
+
+    /// Get the squares of rank y
+    pub(crate) fn get_rank(&self, y: usize) -> BitBoard {
+        let mut board = bitboard::EMPTY;
+        for x in 0..self.width {
+            board.set(self.get_index(x, y), true);
+        }
+        board
+    }
+}
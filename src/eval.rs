@@ -0,0 +1,453 @@
+use core::fmt::Display;
+use std::ops::{Add, AddAssign, Sub};
+
+use crate::{bitboard, BitBoard, Board, Dimensions, PieceType, Side};
+
+/// Game phase of a board with all pieces still on it
+pub const MAX_PHASE: i32 = 24;
+
+// material values in centipawns as (middlegame, endgame)
+const MATERIAL: [(i32, i32); 11] = [
+    (100, 120),   // pawn
+    (320, 300),   // knight
+    (330, 320),   // bishop
+    (500, 530),   // rook
+    (950, 980),   // queen
+    (0, 0),       // king
+    (850, 850),   // archbishop
+    (900, 930),   // chancellor
+    (1250, 1300), // amazon
+    (250, 250),   // camel
+    (200, 250),   // grasshopper
+];
+
+// how much each piece contributes to the game phase
+const PHASE_WEIGHT: [i32; 11] = [0, 1, 1, 2, 4, 0, 3, 4, 5, 1, 1];
+
+// score per square a piece is able to move to
+const MOBILITY: [(i32, i32); 11] = [(0, 0), (4, 4), (5, 5), (2, 4), (1, 2), (0, 0), (2, 3), (2, 3), (1, 2), (4, 4), (2, 2)];
+
+// every piece that counts as material
+const MATERIAL_PIECES: [PieceType; 10] = [
+    PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen,
+    PieceType::Archbishop, PieceType::Chancellor, PieceType::Amazon, PieceType::Camel, PieceType::Grasshopper,
+];
+
+const DOUBLED_PAWN: Score = Score::new(-10, -20);
+const ISOLATED_PAWN: Score = Score::new(-10, -15);
+// indexed by the rank relative to the pawns side
+const PASSED_PAWN: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(10, 20),
+    Score::new(20, 35),
+    Score::new(35, 60),
+    Score::new(60, 100),
+    Score::new(100, 150),
+    Score::new(0, 0),
+];
+
+const PAWN_SHIELD: Score = Score::new(10, 0);
+const KING_OPEN_FILE: Score = Score::new(-15, 0);
+const KING_ZONE_ATTACKED: Score = Score::new(-8, -2);
+
+// Piece-square tables, written from whites point of view with A8 as the first element so that they
+// read like a board. Index with `index ^ 56` for white pieces and `index` for black pieces, the
+// squares of other board sizes are first scaled to a standard board with `standard_index`.
+const PAWN_MG: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const PAWN_EG: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    15,  15,  15,  15,  15,  15,  15,  15,
+     5,   5,   5,   5,   5,   5,   5,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const KNIGHT: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+const BISHOP: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+const ROOK: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+const QUEEN: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+const KING_MG: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+const KING_EG: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+/// A middlegame and endgame score pair that gets tapered by the game phase
+struct Score {
+    mg: i32,
+    eg: i32,
+}
+
+impl Score {
+    const fn new(mg: i32, eg: i32) -> Score {
+        Score { mg, eg }
+    }
+
+    fn times(self, n: i32) -> Score {
+        Score::new(self.mg * n, self.eg * n)
+    }
+
+    fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        self.mg += other.mg;
+        self.eg += other.eg;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+/// A static evaluation split up into the terms it was calculated from
+///
+/// Every term is in centipawns, already tapered by the game phase and seen from the side to move,
+/// meaning that a positive value is good for the player whose turn it is.
+///
+/// # material: Value of the pieces on the board
+/// # piece_squares: Bonus or penalty depending on which squares the pieces stand on
+/// # mobility: Bonus for the number of squares each piece is able to move to
+/// # pawn_structure: Doubled, isolated and passed pawns
+/// # king_safety: Pawn shield, open files and attacked squares around the king
+/// # phase: Game phase from MAX_PHASE (opening) down to 0 (pawn endgame)
+/// # total: Sum of all terms
+///
+pub struct Evaluation {
+    pub material: i32,
+    pub piece_squares: i32,
+    pub mobility: i32,
+    pub pawn_structure: i32,
+    pub king_safety: i32,
+    pub phase: i32,
+    pub total: i32,
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Material:       {:>6}", self.material)?;
+        writeln!(f, "Piece squares:  {:>6}", self.piece_squares)?;
+        writeln!(f, "Mobility:       {:>6}", self.mobility)?;
+        writeln!(f, "Pawn structure: {:>6}", self.pawn_structure)?;
+        writeln!(f, "King safety:    {:>6}", self.king_safety)?;
+        writeln!(f, "Phase:          {:>6}", self.phase)?;
+        write!(f, "Total:          {:>6}", self.total)
+    }
+}
+
+/// Score a board in centipawns from the side to move's point of view
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new();
+/// println!("Evaluation: {}", evaluate(&chess.board));
+/// ```
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_breakdown(board).total
+}
+
+/// Score a board and get every term of the evaluation separately
+///
+/// # Example:
+/// ```
+/// let chess = Chess::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1".to_string());
+/// println!("{}", evaluate_breakdown(&chess.board));
+/// ```
+pub fn evaluate_breakdown(board: &Board) -> Evaluation {
+    let phase = get_phase(board);
+    let perspective = match board.get_playing_side() {
+        Side::White => 1,
+        Side::Black => -1,
+    };
+    let term = |f: fn(&Board, Side) -> Score| {
+        (f(board, Side::White) - f(board, Side::Black)).taper(phase) * perspective
+    };
+
+    let material = term(material);
+    let piece_squares = term(piece_squares);
+    let mobility = term(mobility);
+    let pawn_structure = term(pawn_structure);
+    let king_safety = term(king_safety);
+
+    Evaluation {
+        material,
+        piece_squares,
+        mobility,
+        pawn_structure,
+        king_safety,
+        phase,
+        total: material + piece_squares + mobility + pawn_structure + king_safety,
+    }
+}
+
+/// Get the game phase of a board, going from MAX_PHASE with all pieces on the board down to 0
+pub fn get_phase(board: &Board) -> i32 {
+    let phase = MATERIAL_PIECES.iter()
+        .map(|piece| {
+            let count = (board.get_piece_board(*piece, Side::White) | board.get_piece_board(*piece, Side::Black)).count();
+            count as i32 * PHASE_WEIGHT[piece.to_value()]
+        })
+        .sum::<i32>();
+
+    phase.min(MAX_PHASE)
+}
+
+/// Get the middlegame value of a piece type in centipawns
+pub fn piece_value(piece: PieceType) -> i32 {
+    match piece {
+        PieceType::Empty => 0,
+        _ => MATERIAL[piece.to_value()].0,
+    }
+}
+
+fn material(board: &Board, side: Side) -> Score {
+    let mut score = Score::default();
+    // pieces in hand of variants with drops count like pieces on the board
+    let pocket = board.get_pocket(side);
+
+    for piece in MATERIAL_PIECES {
+        let (mg, eg) = MATERIAL[piece.to_value()];
+        let in_hand = pocket.get(piece.to_value()).copied().unwrap_or(0);
+        let count = board.get_piece_board(piece, side).count() as i32 + in_hand as i32;
+        score += Score::new(mg, eg).times(count);
+    }
+
+    score
+}
+
+fn piece_squares(board: &Board, side: Side) -> Score {
+    let mut score = Score::default();
+    let flip = match side {
+        Side::White => 56,
+        Side::Black => 0,
+    };
+
+    for piece in [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King] {
+        let (mg_table, eg_table) = match piece {
+            PieceType::Pawn => (&PAWN_MG, &PAWN_EG),
+            PieceType::Knight => (&KNIGHT, &KNIGHT),
+            PieceType::Bishop => (&BISHOP, &BISHOP),
+            PieceType::Rook => (&ROOK, &ROOK),
+            PieceType::Queen => (&QUEEN, &QUEEN),
+            _ => (&KING_MG, &KING_EG),
+        };
+
+        for index in board.get_piece_board(piece, side) {
+            let index = standard_index(board.get_dimensions(), index);
+            score += Score::new(mg_table[index ^ flip], eg_table[index ^ flip]);
+        }
+    }
+
+    score
+}
+
+fn mobility(board: &Board, side: Side) -> Score {
+    let mut score = Score::default();
+
+    for piece in board.get_all_pieces() {
+        if piece.get_color() != side {
+            continue;
+        }
+
+        let (mg, eg) = MOBILITY[piece.get_piece_type().to_value()];
+        if mg == 0 && eg == 0 {
+            continue;
+        }
+
+        score += Score::new(mg, eg).times(piece.get_possible_moves(board).count() as i32);
+    }
+
+    score
+}
+
+fn pawn_structure(board: &Board, side: Side) -> Score {
+    let mut score = Score::default();
+    let pawns = board.get_piece_board(PieceType::Pawn, side);
+    let opponent_pawns = board.get_piece_board(PieceType::Pawn, side.get_opposite());
+    let dimensions = board.get_dimensions();
+
+    for file in 0..dimensions.get_width() {
+        let on_file = (pawns & dimensions.get_file(file)).count() as i32;
+        if on_file > 1 {
+            score += DOUBLED_PAWN.times(on_file - 1);
+        }
+        if on_file > 0 && (pawns & adjacent_files_mask(dimensions, file)).count() == 0 {
+            score += ISOLATED_PAWN.times(on_file);
+        }
+    }
+
+    for index in pawns {
+        let (x, y) = dimensions.get_coordinates(index);
+        let blockers = opponent_pawns & (dimensions.get_file(x) | adjacent_files_mask(dimensions, x)) & ahead_mask(dimensions, side, y);
+
+        if blockers.count() == 0 {
+            // ranks of taller boards are scaled to the eight ranks of the table
+            score += PASSED_PAWN[relative_rank(dimensions, side, y) * 8 / dimensions.get_height()];
+        }
+    }
+
+    score
+}
+
+fn king_safety(board: &Board, side: Side) -> Score {
+    let mut score = Score::default();
+    if board.get_piece_board(PieceType::King, side).count() == 0 {
+        return score;
+    }
+
+    let dimensions = board.get_dimensions();
+    let (x, y) = dimensions.get_coordinates(board.get_king(side));
+    let pawns = board.get_piece_board(PieceType::Pawn, side);
+
+    // only count the pawn shield if the king is still on its own back rank
+    if relative_rank(dimensions, side, y) == 0 {
+        let top = dimensions.get_height() - 1;
+        let shield_ranks = match side {
+            Side::White => dimensions.get_rank(1) | dimensions.get_rank(2),
+            Side::Black => dimensions.get_rank(top - 1) | dimensions.get_rank(top - 2),
+        };
+        let shield = pawns & (dimensions.get_file(x) | adjacent_files_mask(dimensions, x)) & shield_ranks;
+        score += PAWN_SHIELD.times(shield.count() as i32);
+    }
+
+    if (pawns & dimensions.get_file(x)).count() == 0 {
+        score += KING_OPEN_FILE;
+    }
+
+    let mut opponent_attacks = bitboard::EMPTY;
+    let mut king_zone = bitboard::EMPTY;
+    for piece in board.get_all_pieces() {
+        if piece.get_color() != side {
+            opponent_attacks |= piece.get_attacked_square(board);
+        } else if piece.get_piece_type() == PieceType::King {
+            king_zone = piece.get_attacked_square(board);
+        }
+    }
+    score += KING_ZONE_ATTACKED.times((king_zone & opponent_attacks).count() as i32);
+
+    score
+}
+
+fn relative_rank(dimensions: Dimensions, side: Side, y: usize) -> usize {
+    match side {
+        Side::White => y,
+        Side::Black => dimensions.get_height() - 1 - y,
+    }
+}
+
+// the square of a standard board at the same relative place as index
+fn standard_index(dimensions: Dimensions, index: usize) -> usize {
+    let (x, y) = dimensions.get_coordinates(index);
+    (y * 8 / dimensions.get_height()) * 8 + x * 8 / dimensions.get_width()
+}
+
+fn adjacent_files_mask(dimensions: Dimensions, x: usize) -> BitBoard {
+    let mut board = bitboard::EMPTY;
+    if x > 0 {
+        board |= dimensions.get_file(x - 1);
+    }
+    if x + 1 < dimensions.get_width() {
+        board |= dimensions.get_file(x + 1);
+    }
+    board
+}
+
+// all ranks in front of rank y seen from side
+fn ahead_mask(dimensions: Dimensions, side: Side, y: usize) -> BitBoard {
+    let mut board = bitboard::EMPTY;
+    let ranks: Vec<usize> = match side {
+        Side::White => (y + 1..dimensions.get_height()).collect(),
+        Side::Black => (0..y).collect(),
+    };
+    for rank in ranks {
+        board |= dimensions.get_rank(rank);
+    }
+    board
+}
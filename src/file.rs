@@ -1,4 +1,4 @@
-use std::convert::{Into, From};
+use std::convert::From;
 
 pub enum File {
     A,
@@ -11,9 +11,9 @@ pub enum File {
     H
 }
 
-impl Into<char> for File {
-    fn into(self) -> char {
-        match self {
+impl From<File> for char {
+    fn from(value: File) -> Self {
+        match value {
             File::A => 'a',
             File::B => 'b',
             File::C => 'c',
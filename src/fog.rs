@@ -0,0 +1,170 @@
+use crate::bitboard::{self, BitBoard};
+use crate::{Board, Chess, Move, Outcome, PieceType, Side};
+
+/// Get the squares side sees in Dark Chess
+///
+/// A side sees the squares of its own pieces, every square they attack and every square they are
+/// able to move to.
+pub fn get_visible_squares(chess: &Chess, side: Side) -> BitBoard {
+    let board = &chess.board;
+    let mut visible = board.get_sides_board(side) | board.get_side_computed_boards(side.get_opposite()).2;
+    for piece in board.get_all_pieces().iter().filter(|piece| piece.get_color() == side) {
+        visible |= piece.get_pseudo_legal_moves(board);
+    }
+
+    visible
+}
+
+/// Get the position as side sees it in Dark Chess, pieces of the opponent are only kept on the
+/// squares side sees
+///
+/// # Example:
+/// ```
+/// let view = fog::get_dark_view(&Chess::new(), Side::White);
+/// assert!(view.get_piece_type_at_pos(51) == PieceType::Empty);
+/// ```
+pub fn get_dark_view(chess: &Chess, side: Side) -> Board {
+    project(&chess.board, side, get_visible_squares(chess, side))
+}
+
+/// Remove every piece of the opponent of side that is not on a square of visible
+fn project(board: &Board, side: Side, visible: BitBoard) -> Board {
+    let mut view = board.clone();
+    for index in board.get_opponent_board(side) {
+        if !visible.get(index) {
+            view.remove_piece(index);
+        }
+    }
+
+    view.update_all_calculations();
+    view
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Line a check comes from, as the Kriegspiel referee announces it
+///
+/// # Rank: Along the rank of the king
+/// # File: Along the file of the king
+/// # LongDiagonal: Along the longer of the two diagonals through the king
+/// # ShortDiagonal: Along the shorter of the two diagonals through the king
+/// # Knight: From a piece that leaps from off the lines through the king, like a knight
+///
+pub enum CheckDirection {
+    Rank,
+    File,
+    LongDiagonal,
+    ShortDiagonal,
+    Knight,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// What the Kriegspiel referee announces to both players after a move was tried
+///
+/// # Illegal: The move is not legal and the same player tries again
+/// # Legal: The move was played, with the square a piece was captured on, the checks it gives and
+/// the outcome if it ended the game
+///
+pub enum Announcement {
+    Illegal,
+    Legal {
+        capture: Option<usize>,
+        checks: Vec<CheckDirection>,
+        outcome: Option<Outcome>,
+    },
+}
+
+/// Referee of a Kriegspiel game, the players only see their own pieces and try moves without
+/// knowing if they are legal
+///
+/// # Example:
+/// ```
+/// let mut referee = Referee::new(Chess::new());
+/// assert!(referee.try_move(Move::from_uci("e2e5").unwrap()) == Announcement::Illegal);
+/// assert!(referee.try_move(Move::from_uci("e2e4").unwrap()) != Announcement::Illegal);
+/// let view = referee.get_view(Side::Black);
+/// ```
+pub struct Referee {
+    chess: Chess,
+}
+
+impl Referee {
+    /// Create a referee for a game
+    pub fn new(chess: Chess) -> Referee {
+        Referee { chess }
+    }
+
+    /// Try a move for the side to move, it is only played if it is legal
+    pub fn try_move(&mut self, chess_move: Move) -> Announcement {
+        let board = &self.chess.board;
+        let capture = match board.is_capture(chess_move) {
+            // en passant takes the pawn next to the moving pawn, not a piece on the empty end square
+            true if board.get_piece_type_at_pos(chess_move.get_end()) == PieceType::Empty => {
+                let ((_, start_y), (end_x, _)) = (board.get_coordinates(chess_move.get_start()), board.get_coordinates(chess_move.get_end()));
+                Some(board.get_index(end_x, start_y))
+            },
+            true => Some(chess_move.get_end()),
+            false => None,
+        };
+
+        if !self.chess.play_move(chess_move) {
+            return Announcement::Illegal;
+        }
+
+        Announcement::Legal {
+            capture,
+            checks: get_check_directions(&self.chess.board),
+            outcome: self.chess.get_outcome(),
+        }
+    }
+
+    /// Get the position as side sees it, only its own pieces
+    pub fn get_view(&self, side: Side) -> Board {
+        project(&self.chess.board, side, bitboard::EMPTY)
+    }
+
+    /// Get the side that tries the next move
+    pub fn get_playing_side(&self) -> Side {
+        self.chess.get_playing_side()
+    }
+
+    /// Get the game the referee watches over, which shows the whole position
+    pub fn get_chess(&self) -> &Chess {
+        &self.chess
+    }
+}
+
+/// Get the lines of every check on the king of the side to move
+fn get_check_directions(board: &Board) -> Vec<CheckDirection> {
+    let side = board.get_playing_side();
+    if board.get_piece_board(PieceType::King, side).count() == 0 {
+        return vec![];
+    }
+
+    let dimensions = board.get_dimensions();
+    let (right, top) = (dimensions.get_width() - 1, dimensions.get_height() - 1);
+    let (kx, ky) = dimensions.get_coordinates(board.get_king(side));
+    board.get_side_computed_boards(side).1.iter().map(|piece| {
+        let (x, y) = piece.get_pos_as_usize();
+        if y == ky {
+            CheckDirection::Rank
+        } else if x == kx {
+            CheckDirection::File
+        } else if x.abs_diff(kx) != y.abs_diff(ky) {
+            // not on a line through the king, so it leaps like a knight
+            CheckDirection::Knight
+        } else {
+            // count the squares of both diagonals through the king, equal ones count as long
+            let rising = (x > kx) == (y > ky);
+            let rising_length = kx.min(ky) + (right - kx).min(top - ky) + 1;
+            let falling_length = kx.min(top - ky) + (right - kx).min(ky) + 1;
+            let (length, other) = match rising {
+                true => (rising_length, falling_length),
+                false => (falling_length, rising_length),
+            };
+            match length >= other {
+                true => CheckDirection::LongDiagonal,
+                false => CheckDirection::ShortDiagonal,
+            }
+        }
+    }).collect()
+}
@@ -5,7 +5,27 @@ mod board;
 pub use crate::board::*;
 
 mod pieces;
-pub use crate::pieces::{PieceType, Piece};
+pub use crate::pieces::{PieceType, Piece, Betza};
+
+mod dimensions;
+pub use crate::dimensions::Dimensions;
+
+mod chessmove;
+pub use crate::chessmove::{Move, square_from_str, square_to_string};
+
+pub mod eval;
+pub mod search;
+pub mod transposition;
+pub mod moveorder;
+pub mod uci;
+pub mod pgn;
+pub mod stats;
+pub mod clock;
+pub mod variant;
+pub mod fog;
+pub mod bughouse;
+pub mod render;
+pub mod svg;
 
 mod bitboard;
 use crate::bitboard::BitBoard;
@@ -15,12 +35,22 @@ use crate::coordinateiterator::*;
 
 mod file;
 
-mod cli;
+pub mod cli;
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{atomic::AtomicBool, Arc};
+    use std::time::Duration;
+
     use super::*;
 
+    fn notation_to_index(move_notation: &'static str) -> usize {
+        let mut chars = move_notation.chars();
+        let file = (chars.next().unwrap() as u8) - b'A';
+        let rank = (chars.next().unwrap() as u8) - b'1';
+        (rank * 8 + file) as usize
+    }
+
     #[test]
     fn stalemate() {
         let chess = Chess::from_fen("8/8/2p1k3/8/8/1q6/8/K7 w - - 0 1".to_string());
@@ -63,7 +93,7 @@ mod tests {
         if let Some(queen) = chess.board.get_piece_at_pos(g8) {
             assert!(queen.get_piece_type() == PieceType::Queen);
         } else {
-            assert!(false);
+            panic!("No queen on g8 after promotion");
         }
     }
 
@@ -309,15 +339,1578 @@ mod tests {
         assert!(chess.make_move(notation_to_index("E8"), notation_to_index("G8")));
     }
 
+    #[test]
+    fn pinned_piece_moves_along_pin() {
+        let mut chess = Chess::from_fen("4k3/4r3/8/8/8/8/4R3/4K3 w - - 0 1".to_string());
+
+        assert!(!chess.make_move(notation_to_index("E2"), notation_to_index("D2")));
+        assert!(chess.make_move(notation_to_index("E2"), notation_to_index("E7")));
+    }
+
+    #[test]
+    fn king_not_able_to_step_back_along_check() {
+        let mut chess = Chess::from_fen("4k3/8/8/8/4r3/8/8/4K3 w - - 0 1".to_string());
+
+        assert!(chess.get_state() == State::Check);
+        assert!(!chess.make_move(notation_to_index("E1"), notation_to_index("E2")));
+        assert!(chess.make_move(notation_to_index("E1"), notation_to_index("D2")));
+    }
+
+    #[test]
+    fn only_king_moves_in_double_check() {
+        let chess = Chess::from_fen("4k3/8/8/8/4r3/3n4/8/2N1K2R w K - 0 1".to_string());
+
+        // taking the checking knight on d3 leaves the king in check from the rook
+        assert!(chess.get_state() == State::Check);
+        let moves = chess.get_legal_moves();
+        assert!(!moves.is_empty() && moves.iter().all(|chess_move| chess_move.get_start() == notation_to_index("E1")));
+        assert!(!moves.contains(&Move::from_uci("e1g1").unwrap()));
+    }
+
+    #[test]
+    fn en_passant_exposing_king() {
+        // taking en passant removes both pawns from the rank of the king
+        let mut chess = Chess::from_fen("8/8/8/K2pP2r/8/8/8/4k3 w - d6 0 1".to_string());
+
+        assert!(!chess.make_move(notation_to_index("E5"), notation_to_index("D6")));
+        assert!(chess.make_move(notation_to_index("E5"), notation_to_index("E6")));
+    }
+
+    #[test]
+    fn castling_rights_of_moved_and_captured_rooks() {
+        let mut chess = Chess::from_fen("r3k2r/8/8/8/8/8/6b1/R3K2R b KQkq - 0 1".to_string());
+
+        // the captured rook takes only its own side of castling along
+        assert!(chess.make_move(notation_to_index("G2"), notation_to_index("H1")));
+        assert!(chess.get_fen() == "r3k2r/8/8/8/8/8/8/R3K2b w Qkq - 0 2");
+        assert!(!chess.make_move(notation_to_index("E1"), notation_to_index("G1")));
+        assert!(chess.make_move(notation_to_index("A1"), notation_to_index("A2")));
+        assert!(chess.get_fen() == "r3k2r/8/8/8/8/8/R7/4K2b b kq - 1 2");
+    }
+
+    #[test]
+    fn castling_needs_rook_on_corner() {
+        // castling rights in a FEN without the rook do not allow castling
+        let chess = Chess::from_fen("4k3/8/8/8/8/8/8/4K3 w KQ - 0 1".to_string());
+
+        assert!(chess.get_legal_moves().iter().all(|chess_move| chess_move.get_end().abs_diff(chess_move.get_start()) != 2));
+    }
+
+    #[test]
+    fn en_passant_square_in_fen() {
+        let mut chess = Chess::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1".to_string());
+
+        assert!(chess.make_move(notation_to_index("E5"), notation_to_index("D6")));
+        assert!(chess.get_fen() == "4k3/8/3P4/8/8/8/8/4K3 b - - 0 1");
+        assert!(Chess::try_from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d5 0 1".to_string()).is_err());
+    }
+
+    #[test]
+    fn evaluate_start_position_is_equal() {
+        let chess = Chess::new();
+        let evaluation = eval::evaluate_breakdown(&chess.board);
+
+        assert!(evaluation.total == 0);
+        assert!(evaluation.phase == eval::MAX_PHASE);
+    }
+
+    #[test]
+    fn evaluate_from_side_to_move() {
+        let white = Chess::from_fen("4k3/8/8/8/8/8/3QP3/4K3 w - - 0 1".to_string());
+        let black = Chess::from_fen("4k3/8/8/8/8/8/3QP3/4K3 b - - 0 1".to_string());
+
+        assert!(eval::evaluate(&white.board) > 900);
+        assert!(eval::evaluate(&black.board) < -900);
+    }
+
+    #[test]
+    fn evaluate_breakdown_sums_to_total() {
+        let chess = Chess::from_fen("r3k2r/pp3ppp/2n5/3P4/8/8/PP3PPP/R3K2R b KQkq - 0 1".to_string());
+        let evaluation = eval::evaluate_breakdown(&chess.board);
+
+        assert!(evaluation.total == evaluation.material + evaluation.piece_squares + evaluation.mobility
+            + evaluation.pawn_structure + evaluation.king_safety);
+        // the passed and isolated d-pawn is good for white
+        assert!(evaluation.pawn_structure < 0);
+    }
+
+    #[test]
+    fn perft_start_position() {
+        let chess = Chess::new();
+
+        assert!(chess.perft(1) == 20);
+        assert!(chess.perft(2) == 400);
+        assert!(chess.perft(3) == 8902);
+    }
+
+    #[test]
+    fn perft_kiwipete() {
+        let chess = Chess::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1".to_string());
+
+        assert!(chess.perft(1) == 48);
+        assert!(chess.perft(2) == 2039);
+    }
+
+    #[test]
+    fn perft_en_passant_and_pins() {
+        let chess = Chess::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1".to_string());
+
+        assert!(chess.perft(3) == 2812);
+    }
+
+    #[test]
+    fn perft_promotions() {
+        let chess = Chess::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8".to_string());
+
+        assert!(chess.perft(2) == 1486);
+    }
+
+    #[test]
+    fn play_move_with_promotion() {
+        let mut chess = Chess::from_fen("8/6P1/2p1k3/8/8/1q6/8/4K3 w - - 0 1".to_string());
+
+        assert!(!chess.play_move(Move::from_uci("g7g8").unwrap()));
+        assert!(chess.play_move(Move::from_uci("g7g8n").unwrap()));
+        assert!(chess.get_playing_side() == Side::Black);
+        assert!(chess.board.get_piece_type_at_pos(notation_to_index("G8")) == PieceType::Knight);
+    }
+
+    #[test]
+    fn search_finds_mate_in_one() {
+        let chess = Chess::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string());
+        let limits = search::SearchLimits { depth: Some(3), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+
+        assert!(result.best_move == Some(Move::from_uci("a1a8").unwrap()));
+        assert!(search::mate_in(result.score) == Some(1));
+    }
+
+    #[test]
+    fn quiescence_sees_mate_after_capture() {
+        // every white move allows Qxg2 mate, which the depth one search only finds in quiescence
+        let chess = Chess::from_fen("k7/1b6/6q1/8/8/P7/6PP/7K w - - 0 1".to_string());
+        let limits = search::SearchLimits { depth: Some(1), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+
+        assert!(search::mate_in(result.score) == Some(-1));
+    }
+
+    #[test]
+    fn search_takes_hanging_queen() {
+        let chess = Chess::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1".to_string());
+        let limits = search::SearchLimits { depth: Some(2), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+
+        assert!(result.best_move == Some(Move::from_uci("d2d5").unwrap()));
+        assert!(result.pv[0] == Move::from_uci("d2d5").unwrap());
+        assert!(result.score > 300);
+    }
+
+    #[test]
+    fn search_respects_limits_and_stop() {
+        let chess = Chess::new();
+        let limits = search::SearchLimits { nodes: Some(500), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+        assert!(result.best_move.is_some());
+        assert!(result.nodes <= 500);
+
+        let result = search::Search::new(search::SearchLimits::default(), Arc::new(AtomicBool::new(true))).run(&chess.board);
+        assert!(result.best_move.is_some());
+        assert!(result.depth == 0);
+    }
+
+    #[test]
+    fn zobrist_key_of_transposition() {
+        let mut knights = Chess::new();
+        for chess_move in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            assert!(knights.play_move(Move::from_uci(chess_move).unwrap()));
+        }
+        let mut pawns = Chess::new();
+        for chess_move in ["e2e3", "d7d6", "d2d3", "e7e6"] {
+            assert!(pawns.play_move(Move::from_uci(chess_move).unwrap()));
+        }
+        let transposed = Chess::from_fen("rnbqkbnr/ppp2ppp/3pp3/8/8/3PP3/PPP2PPP/RNBQKBNR w KQkq - 0 3".to_string());
+
+        assert!(knights.get_zobrist_key() == Chess::new().get_zobrist_key());
+        assert!(pawns.get_zobrist_key() == transposed.get_zobrist_key());
+        assert!(Chess::new().get_zobrist_key() != Chess::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1".to_string()).get_zobrist_key());
+    }
+
+    #[test]
+    fn transposition_table_mate_scores_and_replacement() {
+        let table = transposition::TranspositionTable::new(1);
+        let chess_move = Move::from_uci("e2e4").unwrap();
+        table.new_search();
+        table.store(42, 3, transposition::Bound::Exact, search::MATE - 5, Some(chess_move), 2);
+
+        let entry = table.probe(42).unwrap();
+        assert!(entry.get_best_move() == Some(chess_move));
+        assert!(entry.get_score(2) == search::MATE - 5);
+        assert!(entry.get_score(4) == search::MATE - 7);
+        assert!(table.probe(43).is_none());
+
+        // a shallower result of the same search does not replace a bound
+        table.store(42, 1, transposition::Bound::Lower, 10, None, 0);
+        assert!(table.probe(42).unwrap().get_depth() == 3);
+
+        table.clear();
+        assert!(table.probe(42).is_none());
+        assert!(table.hashfull() == 0);
+
+        // threads store into the same table without a lock around all of it
+        let table = Arc::new(table);
+        let threads: Vec<_> = (0..4u64).map(|thread| {
+            let table = table.clone();
+            std::thread::spawn(move || {
+                for key in (0..100).map(|i| (i * 4 + thread) << 32 | i) {
+                    table.store(key, 1, transposition::Bound::Exact, key as i32 % 100, None, 0);
+                }
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!((0..400).map(|i| (i << 32) | (i / 4)).all(|key| table.probe(key).is_some_and(|entry| entry.get_score(0) == key as i32 % 100)));
+    }
+
+    #[test]
+    fn search_fills_shared_transposition_table() {
+        let chess = Chess::new();
+        let table = Arc::new(transposition::TranspositionTable::new(1));
+        let limits = search::SearchLimits { depth: Some(3), ..Default::default() };
+        let result = search::Search::with_table(limits, Arc::new(AtomicBool::new(false)), table.clone()).run(&chess.board);
+
+        assert!(result.depth == 3);
+        assert!(table.hashfull() > 0);
+
+        // a second search finds the results of the first one in the shards they were stored in
+        let entry = table.probe(chess.get_zobrist_key()).unwrap();
+        assert!(entry.get_depth() == 3 && entry.get_best_move() == result.best_move);
+        let again = search::Search::with_table(limits, Arc::new(AtomicBool::new(false)), table.clone()).run(&chess.board);
+        assert!(again.best_move == result.best_move && again.nodes < result.nodes);
+
+        // resizing through the shared table empties it
+        table.resize(2);
+        assert!(table.probe(chess.get_zobrist_key()).is_none() && table.hashfull() == 0);
+    }
+
+    #[test]
+    fn move_picker_stages() {
+        // the pawn takes the rook and the knight, the queen the bishop, but taking the rook with
+        // the queen loses it to the pawn on d6
+        let chess = Chess::from_fen("4k3/7P/3p4/n1r5/1P2b3/8/2Q5/4K3 w - - 0 1".to_string());
+        let hash_move = Move::from_uci("e1d1").unwrap();
+        let killer = Move::from_uci("c2a4").unwrap();
+        let mut history = moveorder::History::new();
+        history.add(Move::from_uci("c2h2").unwrap(), 4);
+
+        let mut picker = moveorder::MovePicker::new(&chess.board, chess.get_legal_moves(), Some(hash_move), [Some(killer), None], &history);
+        let mut picked = vec![];
+        while let Some(chess_move) = picker.next() {
+            picked.push((chess_move, picker.get_stage().unwrap()));
+        }
+
+        use moveorder::Stage::*;
+        let expected = [
+            ("e1d1", HashMove),
+            // most valuable victim first
+            ("b4c5", Captures), ("c2e4", Captures), ("b4a5", Captures),
+            ("h7h8q", Promotions), ("h7h8r", Promotions), ("h7h8b", Promotions), ("h7h8n", Promotions),
+            ("c2a4", Killers),
+            // best history first
+            ("c2h2", Quiets),
+        ];
+        for (i, (uci, stage)) in expected.iter().enumerate() {
+            assert!(picked[i] == (Move::from_uci(uci).unwrap(), *stage), "{}", uci);
+        }
+        assert!(picked[expected.len()..picked.len() - 1].iter().all(|(_, stage)| *stage == Quiets));
+        assert!(picked.last() == Some(&(Move::from_uci("c2c5").unwrap(), BadCaptures)));
+
+        // every legal move comes exactly once
+        let mut moves: Vec<_> = picked.iter().map(|(chess_move, _)| *chess_move).collect();
+        let mut legal = chess.get_legal_moves();
+        moves.sort_by_key(|chess_move| (chess_move.get_start(), chess_move.get_end(), chess_move.get_promotion().map(|piece| piece.to_value())));
+        legal.sort_by_key(|chess_move| (chess_move.get_start(), chess_move.get_end(), chess_move.get_promotion().map(|piece| piece.to_value())));
+        assert!(moves == legal);
+    }
+
+    #[test]
+    fn move_picker_only_captures() {
+        let chess = Chess::from_fen("4k3/7P/8/2r5/1P6/8/2Q5/4K3 w - - 0 1".to_string());
+        let picker = moveorder::MovePicker::new_captures(&chess.board, chess.get_legal_moves());
+
+        assert!(picker.remaining() == 6);
+    }
+
+    #[test]
+    fn see_simple_exchanges() {
+        let chess = Chess::from_fen("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e1e5").unwrap()) == -400);
+        assert!(!chess.board.see_ge(Move::from_uci("e1e5").unwrap(), 0));
+
+        let chess = Chess::from_fen("4k3/8/8/4p3/8/8/8/4RK2 w - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e1e5").unwrap()) == 100);
+
+        // moving the rook to a square attacked by a pawn loses it
+        let chess = Chess::from_fen("4k3/8/8/4p3/8/8/8/3RK3 w - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("d1d4").unwrap()) == -500);
+    }
+
+    #[test]
+    fn see_x_ray_attackers() {
+        // the queen behind the rook gets the pawn back after the rook is lost
+        let chess = Chess::from_fen("4k3/8/5p2/4p3/8/8/4R3/4QK2 w - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e2e5").unwrap()) == -300);
+
+        let chess = Chess::from_fen("4k3/4r3/8/4p3/8/8/4R3/4RK2 w - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e2e5").unwrap()) == 100);
+
+        // a bishop behind a pawn recaptures
+        let chess = Chess::from_fen("4k3/8/8/3n4/4P3/5B2/8/4K3 w - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e4d5").unwrap()) == 320);
+    }
+
+    #[test]
+    fn see_king_and_en_passant() {
+        // the king is not able to recapture on a defended square
+        let chess = Chess::from_fen("4r2k/8/8/8/8/2n5/8/3K4 b - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e8e2").unwrap()) == 0);
+        let chess = Chess::from_fen("4r2k/8/8/8/8/8/8/3K4 b - - 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e8e2").unwrap()) == -500);
+
+        let chess = Chess::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1".to_string());
+        assert!(chess.board.see(Move::from_uci("e5d6").unwrap()) == 100);
+    }
+
+    #[test]
+    fn fen_round_trip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 3 12",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/8/8/8/3pP3/8/8/4K2k b - e3 0 40",
+        ] {
+            assert!(Chess::from_fen(fen.to_string()).get_fen() == fen);
+        }
+
+        let mut chess = Chess::new();
+        assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+        assert!(chess.play_move(Move::from_uci("g8f6").unwrap()));
+        assert!(chess.get_fen() == "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2");
+    }
+
+    #[test]
+    fn invalid_fen() {
+        assert!(Chess::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1".to_string()).is_err());
+        assert!(Chess::try_from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()).is_err());
+        assert!(Chess::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1".to_string()).is_err());
+        assert!(Chess::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq z9 0 1".to_string()).is_err());
+        assert!(Chess::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w".to_string()).is_err());
+        assert!(Chess::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -".to_string()).is_ok());
+    }
+
+    #[test]
+    fn search_multipv_lines() {
+        let chess = Chess::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string());
+        let limits = search::SearchLimits { depth: Some(2), ..Default::default() };
+        let mut search = search::Search::new(limits, Arc::new(AtomicBool::new(false)));
+        search.set_multipv(3);
+
+        let mut lines = vec![];
+        let result = search.run_with_info(&chess.board, |info| lines.push(info.clone()));
+
+        assert!(result.best_move == Some(Move::from_uci("a1a8").unwrap()));
+        let last_depth: Vec<_> = lines.iter().filter(|line| line.depth == 2).collect();
+        assert!(last_depth.len() == 3);
+        assert!(last_depth[1].best_move != last_depth[0].best_move && last_depth[2].best_move != last_depth[1].best_move);
+    }
+
+    #[test]
+    fn allocate_time_keeps_reserve() {
+        let time = search::allocate_time(Duration::from_secs(60), Duration::ZERO, None);
+        assert!(time > Duration::from_secs(1) && time < Duration::from_secs(3));
+
+        let time = search::allocate_time(Duration::from_secs(10), Duration::ZERO, Some(1));
+        assert!(time < Duration::from_secs(10));
+
+        let time = search::allocate_time(Duration::from_millis(100), Duration::from_secs(5), None);
+        assert!(time < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn undo_restores_position() {
+        let mut chess = Chess::new();
+        assert!(chess.undo().is_none());
+
+        for notation in ["e2e4", "d7d5", "e4d5"] {
+            assert!(chess.play_move(Move::from_uci(notation).unwrap()));
+        }
+        let after_capture = chess.get_fen();
+        assert!(chess.play_move(Move::from_uci("d8d5").unwrap()));
+
+        assert!(chess.undo() == Some(Move::from_uci("d8d5").unwrap()));
+        assert!(chess.get_fen() == after_capture);
+        assert!(chess.get_history() == vec![Move::from_uci("e2e4").unwrap(), Move::from_uci("d7d5").unwrap(), Move::from_uci("e4d5").unwrap()]);
+
+        while chess.undo().is_some() {}
+        assert!(chess.get_fen() == Chess::new().get_fen());
+    }
+
+    #[test]
+    fn history_records_promotion() {
+        let mut chess = Chess::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1".to_string());
+        assert!(chess.make_move(52, 60));
+        assert!(chess.get_state() == State::Promotion);
+        chess.promote(PieceType::Knight);
+
+        assert!(chess.get_history() == vec![Move::from_uci("e7e8n").unwrap()]);
+        chess.undo();
+        assert!(chess.get_state() == State::Playing);
+        assert!(chess.get_fen() == "8/4P3/8/8/8/8/k7/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn uci_parse_info() {
+        let info = uci::parse_info("info depth 7 seldepth 9 multipv 2 score mate -3 nodes 51234 nps 100000 time 512 pv e2e4 e7e5 g1f3").unwrap();
+        assert!(info.depth == Some(7) && info.multipv == Some(2) && info.nodes == Some(51234));
+        assert!(info.score == Some(uci::Score::Mate(-3)));
+        assert!(info.time == Some(Duration::from_millis(512)));
+        assert!(info.pv.len() == 3 && info.pv[2] == Move::from_uci("g1f3").unwrap());
+
+        let info = uci::parse_info("info score cp 31 lowerbound depth 4").unwrap();
+        assert!(info.score == Some(uci::Score::Centipawns(31)) && info.depth == Some(4));
+
+        assert!(uci::parse_info("info string depth 5").unwrap().depth.is_none());
+        assert!(uci::parse_info("bestmove e2e4").is_none());
+
+        // moves on the tenth file only exist on a wider board
+        let capablanca = Dimensions::new(10, 8).unwrap();
+        let info = uci::parse_info_with("info depth 1 pv j2j4 a7a5", capablanca).unwrap();
+        assert!(info.pv == vec![Move::from_uci_with("j2j4", capablanca).unwrap(), Move::from_uci_with("a7a5", capablanca).unwrap()]);
+        assert!(info.pv[0].to_uci_with(capablanca) == "j2j4");
+        assert!(uci::parse_info("info depth 1 pv j2j4").unwrap().pv.is_empty());
+    }
+
+    #[test]
+    fn bitboard_display() {
+        let board = BitBoard::from_number(1 << notation_to_index("A1") | 1 << notation_to_index("H8"));
+        assert!(board.to_string() == "8|00000001\n7|00000000\n6|00000000\n5|00000000\n4|00000000\n3|00000000\n2|00000000\n1|10000000\n  ABCDEFGH\n");
+
+        let capablanca = Dimensions::new(10, 8).unwrap();
+        let board = BitBoard::from_number(1 << capablanca.square_from_str("j8").unwrap() | 1 << capablanca.square_from_str("i1").unwrap());
+        let text = board.to_string_with(capablanca);
+        assert!(text.lines().next() == Some("8|0000000001") && text.lines().nth(7) == Some("1|0000000010"));
+        assert!(text.ends_with("  ABCDEFGHIJ\n"));
+
+        let large = Dimensions::new(12, 10).unwrap();
+        let text = BitBoard::from_number(1 << large.square_from_str("l10").unwrap()).to_string_with(large);
+        assert!(text.starts_with("10|000000000001\n") && text.contains("\n 1|000000000000\n"));
+    }
+
+    #[test]
+    fn start_fen_of_history() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+        let mut chess = Chess::from_fen(fen.to_string());
+        assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+        assert!(chess.get_start_fen() == fen);
+    }
+
+    #[test]
+    fn san_round_trip() {
+        let chess = Chess::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1".to_string());
+        for chess_move in chess.get_legal_moves() {
+            let san = chess.to_san(chess_move).unwrap();
+            assert!(chess.parse_san(&san).unwrap() == chess_move);
+        }
+
+        assert!(chess.to_san(Move::from_uci("e1g1").unwrap()) == Some("O-O".to_string()));
+        assert!(chess.to_san(Move::from_uci("e2a6").unwrap()) == Some("Bxa6".to_string()));
+        assert!(chess.to_san(Move::from_uci("g2h3").unwrap()) == Some("gxh3".to_string()));
+        assert!(chess.parse_san("0-0-0").unwrap() == Move::from_uci("e1c1").unwrap());
+        assert!(chess.parse_san("Nb6").is_err());
+
+        // knights on b1 and f3 are both able to go to d2
+        let chess = Chess::from_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1".to_string());
+        assert!(chess.to_san(Move::from_uci("b1d2").unwrap()) == Some("Nbd2".to_string()));
+
+        let chess = Chess::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string());
+        assert!(chess.to_san(Move::from_uci("a1a8").unwrap()) == Some("Ra8#".to_string()));
+    }
+
+    #[test]
+    fn pgn_round_trip() {
+        let text = "[Event \"Test\"]\n[White \"A\"]\n\n1. e4 {best by test} e5 2. Nf3 (2. f4 exf4) Nc6 $1 3. Bb5 a6 1/2-1/2\n\n\
+            [Event \"Second\"]\n\n1.d4 d5 2.c4 *\n";
+        let games = pgn::parse_pgn(text).unwrap();
+        assert!(games.len() == 2);
+        assert!(games[0].get_header("White") == Some("A"));
+        assert!(games[0].result == "1/2-1/2" && games[0].chess.get_history().len() == 6);
+        assert!(games[1].result == "*" && games[1].chess.get_history().len() == 3);
+
+        let written = pgn::write_pgn(&games[0].headers, &games[0].chess, "1/2-1/2");
+        assert!(written.contains("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 1/2-1/2"));
+        let again = pgn::parse_pgn(&written).unwrap();
+        assert!(again[0].chess.get_fen() == games[0].chess.get_fen());
+
+        let openings = pgn::parse_epd("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - id \"e4\";\n\n").unwrap();
+        assert!(openings.len() == 1 && openings[0].get_playing_side() == Side::Black);
+    }
+
+    #[test]
+    fn game_outcomes() {
+        let mut chess = Chess::new();
+        for notation in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            assert!(chess.play_move(Move::from_uci(notation).unwrap()));
+            assert!(chess.get_outcome().is_none());
+        }
+        assert!(chess.play_move(Move::from_uci("f6g8").unwrap()));
+        assert!(chess.get_repetition_count() == 3);
+        // a repetition is only a draw when someone calls it one
+        assert!(chess.get_outcome().is_none() && chess.get_state() == State::Playing);
+
+        let chess = Chess::from_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 1".to_string());
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::InsufficientMaterial)));
+
+        let chess = Chess::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80".to_string());
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::FiftyMoves)));
+
+        let chess = Chess::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1".to_string());
+        let outcome = chess.get_outcome().unwrap();
+        assert!(outcome.result == GameResult::WhiteWins && outcome.termination == Termination::Checkmate);
+    }
+
+    #[test]
+    fn draw_rules() {
+        // the fifty move rule counts halfmoves, so the 100th quiet halfmove ends the game
+        let mut chess = Chess::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80".to_string());
+        assert!(chess.get_outcome().is_none());
+        assert!(chess.play_move(Move::from_uci("a1a2").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::FiftyMoves)));
+
+        let insufficient = |fen: &str| Chess::from_fen(fen.to_string()).get_outcome() == Some(Outcome::draw(Termination::InsufficientMaterial));
+        assert!(insufficient("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/4KN2 w - - 0 1"));
+        assert!(!insufficient("4kb2/8/8/8/8/8/8/4KB2 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+
+        // bare kings can still race to the hill
+        let chess = Chess::try_from_fen_variant("4k3/8/8/8/8/8/8/4K3 w - - 0 1".to_string(), Arc::new(variant::KingOfTheHill)).unwrap();
+        assert!(chess.get_outcome().is_none());
+    }
+
+    #[test]
+    fn match_statistics() {
+        let score = stats::MatchScore { wins: 60, draws: 20, losses: 20 };
+        let (elo, error) = score.get_elo().unwrap();
+        assert!((elo - stats::score_to_elo(0.7)).abs() < 1e-9);
+        assert!(error > 0.0 && error < elo);
+        assert!((stats::elo_to_score(stats::score_to_elo(0.3)) - 0.3).abs() < 1e-9);
+
+        let sprt = stats::Sprt::default();
+        assert!(sprt.get_decision(&stats::MatchScore { wins: 600, draws: 200, losses: 200 }) == stats::SprtDecision::AcceptH1);
+        assert!(sprt.get_decision(&stats::MatchScore { wins: 200, draws: 200, losses: 600 }) == stats::SprtDecision::AcceptH0);
+        assert!(sprt.get_decision(&stats::MatchScore { wins: 5, draws: 5, losses: 5 }) == stats::SprtDecision::Continue);
+    }
+
+    fn manual_clock(control: clock::TimeControl) -> (clock::Clock, clock::ManualTime) {
+        let time = clock::ManualTime::default();
+        (clock::Clock::with_time_source(control, Arc::new(time.clone())), time)
+    }
+
+    #[test]
+    fn clock_delays() {
+        let secs = Duration::from_secs;
+
+        let (mut fischer, time) = manual_clock(clock::TimeControl::fischer(secs(60), secs(2)));
+        fischer.start(Side::White);
+        time.advance(secs(5));
+        assert!(fischer.press());
+        assert!(fischer.get_remaining(Side::White) == secs(57));
+        assert!(fischer.get_running() == Some(Side::Black));
+
+        let (mut bronstein, time) = manual_clock(clock::TimeControl::bronstein(secs(60), secs(3)));
+        bronstein.start(Side::White);
+        time.advance(secs(2));
+        bronstein.press();
+        assert!(bronstein.get_remaining(Side::White) == secs(60));
+        time.advance(secs(1));
+        bronstein.press();
+        time.advance(secs(10));
+        bronstein.press();
+        assert!(bronstein.get_remaining(Side::White) == secs(53));
+
+        let (mut simple, time) = manual_clock(clock::TimeControl::simple_delay(secs(60), secs(5)));
+        simple.start(Side::White);
+        time.advance(secs(4));
+        assert!(simple.get_remaining(Side::White) == secs(60));
+        time.advance(secs(3));
+        assert!(simple.get_remaining(Side::White) == secs(58));
+        simple.press();
+        assert!(simple.get_remaining(Side::White) == secs(58));
+
+        let (mut sudden_death, time) = manual_clock(clock::TimeControl::sudden_death(secs(10)));
+        sudden_death.start(Side::White);
+        time.advance(secs(10));
+        assert!(sudden_death.get_flagged() == Some(Side::White));
+        assert!(!sudden_death.press());
+    }
+
+    #[test]
+    fn clock_periods_and_odds() {
+        let secs = Duration::from_secs;
+        let control = clock::TimeControl::parse("2/5400+30:1800+30").unwrap();
+        assert!(control.get_periods().len() == 2 && control.get_periods()[0].moves == Some(2));
+        assert!(clock::TimeControl::parse("40/abc").is_err());
+
+        let (mut classical, time) = manual_clock(control);
+        classical.start(Side::White);
+        for _ in 0..4 {
+            time.advance(secs(100));
+            classical.press();
+        }
+        // two moves of 100 seconds with 30 seconds increment, then the second period is added
+        assert!(classical.get_remaining(Side::White) == secs(5400 - 140 + 1800));
+        assert!(classical.get_moves_to_go(Side::White).is_none());
+
+        let time = clock::ManualTime::default();
+        let mut odds = clock::Clock::with_odds(clock::TimeControl::sudden_death(secs(300)), clock::TimeControl::sudden_death(secs(60)), Arc::new(time.clone()));
+        odds.start(Side::Black);
+        time.advance(secs(30));
+        assert!(odds.get_remaining(Side::White) == secs(300) && odds.get_remaining(Side::Black) == secs(30));
+    }
+
+    #[test]
+    fn clock_flag_fall_ends_game() {
+        let (clock, time) = manual_clock(clock::TimeControl::sudden_death(Duration::from_secs(10)));
+        let mut chess = Chess::new();
+        chess.set_clock(clock);
+        assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+        time.advance(Duration::from_secs(11));
+
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::TimeForfeit)));
+        assert!(!chess.play_move(Move::from_uci("e7e5").unwrap()));
+
+        // white only has a king left, so black running out of time is a draw
+        let (clock, time) = manual_clock(clock::TimeControl::sudden_death(Duration::from_secs(10)));
+        let mut chess = Chess::from_fen("4k3/8/8/8/8/8/3q4/4K3 b - - 0 1".to_string());
+        chess.set_clock(clock);
+        time.advance(Duration::from_secs(10));
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::TimeForfeit)));
+    }
+
+    #[test]
+    fn clock_pressed_before_move_and_restored_by_undo() {
+        let secs = Duration::from_secs;
+        let (clock, time) = manual_clock(clock::TimeControl::fischer(secs(10), secs(1)));
+        let mut chess = Chess::new();
+        chess.set_clock(clock);
+
+        time.advance(secs(3));
+        assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+        time.advance(secs(2));
+        assert!(chess.play_move(Move::from_uci("e7e5").unwrap()));
+        assert!(chess.get_clock().unwrap().get_remaining(Side::White) == secs(8));
+        assert!(chess.get_clock().unwrap().get_remaining(Side::Black) == secs(9));
+
+        // black gets back the time of the move, and thinks again from there
+        time.advance(secs(4));
+        assert!(chess.undo().is_some());
+        time.advance(secs(1));
+        let clock = chess.get_clock().unwrap();
+        assert!(clock.get_running() == Some(Side::Black));
+        assert!(clock.get_remaining(Side::Black) == secs(9) && clock.get_remaining(Side::White) == secs(8));
+
+        assert!(chess.undo().is_some());
+        assert!(chess.get_clock().unwrap().get_remaining(Side::White) == secs(10));
+
+        // a move made after the flag fell is not played
+        time.advance(secs(10));
+        assert!(!chess.play_move(Move::from_uci("e2e4").unwrap()));
+        assert!(chess.get_fen() == Chess::new().get_fen() && chess.get_history().is_empty());
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::White, Termination::TimeForfeit)));
+
+        // the clock is pressed when the promotion piece is chosen
+        let (clock, time) = manual_clock(clock::TimeControl::sudden_death(secs(10)));
+        let mut chess = Chess::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1".to_string());
+        chess.set_clock(clock);
+        assert!(chess.make_move(52, 60));
+        time.advance(secs(10));
+        chess.promote(PieceType::Queen);
+        assert!(chess.get_state() == State::Promotion);
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::TimeForfeit)));
+    }
+
+    #[test]
+    fn perft_chess960() {
+        let positions: [(&str, [u64; 3]); 5] = [
+            ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12189]),
+            ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18002]),
+            ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", [20, 479, 10471]),
+            ("1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9", [29, 502, 14569]),
+            ("qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9", [22, 593, 13440]),
+        ];
+
+        for (fen, counts) in positions {
+            let chess = Chess::from_fen(fen.to_string());
+            assert!(chess.is_chess960());
+            for (depth, count) in counts.iter().enumerate() {
+                assert!(chess.perft(depth as u32 + 1) == *count, "{} depth {}", fen, depth + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn chess960_positions_and_fen() {
+        assert!(Chess::new_chess960(518).unwrap().get_fen() == Chess::new().get_fen());
+        assert!(Chess::new_chess960(0).unwrap().get_fen() == "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+        assert!(Chess::new_chess960(959).unwrap().get_shredder_fen() == "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w CAca - 0 1");
+        assert!(Chess::new_chess960(960).is_err());
+
+        // X-FEN uses the file only when another rook is further out on that side
+        let chess = Chess::from_fen("rk2r3/8/8/8/8/8/8/RK2R2R w EAe - 0 1".to_string());
+        assert!(chess.get_fen() == "rk2r3/8/8/8/8/8/8/RK2R2R w EQk - 0 1");
+        assert!(chess.get_shredder_fen() == "rk2r3/8/8/8/8/8/8/RK2R2R w EAe - 0 1");
+        assert!(Chess::from_fen(chess.get_fen()).get_shredder_fen() == chess.get_shredder_fen());
+    }
+
+    #[test]
+    fn chess960_castling() {
+        // the king on g1 castles king side by taking the rook on h1 and only the rook moves
+        let mut chess = Chess::from_fen("4k3/8/8/8/8/8/8/R5KR w HA - 0 1".to_string());
+        let castle = Move::from_uci("g1h1").unwrap();
+        assert!(chess.to_san(castle) == Some("O-O".to_string()));
+        assert!(chess.play_move(castle));
+        assert!(chess.get_fen() == "4k3/8/8/8/8/8/8/R4RK1 b - - 1 1");
+
+        // queen side castling with the king on b1 and the rook on a1 swaps them to c1 and d1
+        let mut chess = Chess::from_fen("4k3/8/8/8/8/8/8/RK5R w HA - 0 1".to_string());
+        assert!(chess.play_move(Move::from_uci("b1a1").unwrap()));
+        assert!(chess.get_fen() == "4k3/8/8/8/8/8/8/2KR3R b - - 1 1");
+
+        // the rook on b1 shields the king from the queen on a1, after castling it would not
+        let chess = Chess::from_fen("4k3/8/8/8/8/8/8/qRK4R w B - 0 1".to_string());
+        assert!(!chess.get_legal_moves().contains(&Move::from_uci("c1b1").unwrap()));
+
+        let mut chess = Chess::new();
+        chess.set_chess960(true);
+        for notation in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"] {
+            assert!(chess.play_move(Move::from_uci(notation).unwrap()));
+        }
+        assert!(!chess.get_legal_moves().contains(&Move::from_uci("e1g1").unwrap()));
+        assert!(chess.play_move(Move::from_uci("e1h1").unwrap()));
+        assert!(chess.get_fen().starts_with("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq"));
+
+        let written = pgn::write_pgn(&[], &chess, "*");
+        assert!(written.contains("[Variant \"Chess960\"]") && written.contains("4. O-O"));
+        assert!(pgn::parse_pgn(&written).unwrap()[0].chess.get_fen() == chess.get_fen());
+    }
+
+    // pawns only step one square, a white pawn on the fifth rank wins and the FEN ends with the number of checks given
+    struct Slow;
+
+    #[derive(Clone)]
+    struct SlowChecks(u8);
+
+    impl variant::VariantState for SlowChecks {
+        fn clone_state(&self) -> Box<dyn variant::VariantState> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn is_double_push(board: &Board, chess_move: Move) -> bool {
+        chess_move.get_start().abs_diff(chess_move.get_end()) == 16
+            && board.get_piece_type_at_pos(chess_move.get_start()) == PieceType::Pawn
+    }
+
+    impl variant::Variant for Slow {
+        fn get_name(&self) -> &str {
+            "Slow"
+        }
+
+        fn get_start_fen(&self) -> String {
+            format!("{} 0", variant::STANDARD_FEN)
+        }
+
+        fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+            board.generate_legal_moves().into_iter().filter(|chess_move| !is_double_push(board, *chess_move)).collect()
+        }
+
+        fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+            board.is_standard_legal(chess_move) && !is_double_push(board, chess_move)
+        }
+
+        fn play_move(&self, board: &mut Board, chess_move: Move) {
+            let side = board.get_playing_side();
+            board.apply_move(chess_move);
+            let white_checks = side == Side::White && board.is_in_check();
+            if let Some(checks) = board.get_variant_state_mut::<SlowChecks>().filter(|_| white_checks) {
+                checks.0 += 1;
+            }
+        }
+
+        fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+            let fifth_rank = (32..40).any(|index| board.get_piece_at_pos(index).is_some_and(|piece| piece.get_piece_type() == PieceType::Pawn && piece.get_color() == Side::White));
+            fifth_rank.then_some(Outcome::loss(Side::Black, Termination::Variant))
+        }
+
+        fn parse_fen(&self, fen: &str, state: &mut Option<Box<dyn variant::VariantState>>) -> Result<String, String> {
+            let (fen, checks) = fen.rsplit_once(' ').ok_or("Missing checks")?;
+            *state = Some(Box::new(SlowChecks(checks.parse().map_err(|_| "Invalid checks")?)));
+            Ok(fen.to_string())
+        }
+
+        fn write_fen(&self, board: &Board, fen: String) -> String {
+            format!("{} {}", fen, board.get_variant_state::<SlowChecks>().map_or(0, |checks| checks.0))
+        }
+    }
+
+    #[test]
+    fn variant_rules() {
+        let mut chess = Chess::new_variant(Arc::new(Slow)).unwrap();
+        assert!(chess.get_legal_moves().len() == 12);
+        assert!(!chess.play_move(Move::from_uci("e2e4").unwrap()));
+        assert!(chess.get_moves(notation_to_index("E2")) == vec![(4, 2)]);
+
+        for uci in ["e2e3", "d7d6", "e3e4", "d6d5", "f1b5", "c7c6", "e4e5"] {
+            assert!(chess.play_move(Move::from_uci(uci).unwrap()), "{}", uci);
+        }
+        assert!(chess.get_state() == State::VariantEnd);
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+        assert!(chess.get_fen().ends_with(" b KQkq - 0 4 1"));
+
+        let written = pgn::write_pgn(&[], &chess, "1-0");
+        assert!(written.contains("[Variant \"Slow\"]") && !written.contains("[FEN"));
+
+        // the engine goes for the win of the variant
+        let chess = Chess::try_from_fen_variant("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1 0".to_string(), Arc::new(Slow)).unwrap();
+        let limits = search::SearchLimits { depth: Some(3), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+        assert!(result.best_move == Some(Move::from_uci("e4e5").unwrap()));
+        assert!(search::mate_in(result.score) == Some(1));
+    }
+
+    #[test]
+    fn promotion_through_variant() {
+        let mut chess = Chess::from_fen("8/1P5k/8/8/8/8/8/K7 w - - 0 1".to_string());
+        assert!(chess.make_move(notation_to_index("B7"), notation_to_index("B8")));
+        assert!(chess.get_state() == State::Promotion);
+        chess.promote(PieceType::Knight);
+        assert!(chess.get_fen() == "1N6/7k/8/8/8/8/8/K7 b - - 0 1");
+        assert!(chess.get_history() == vec![Move::from_uci("b7b8n").unwrap()]);
+        assert!(chess.get_variant().get_name() == "Standard");
+    }
+
+    #[test]
+    fn crazyhouse_pockets_and_fen() {
+        let mut chess = Chess::new_variant(Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.perft(4) == 197281);
+        for san in ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()), "{}", san);
+        }
+        assert!(chess.get_pocket(Side::White)[PieceType::Pawn.to_value()] == 1);
+        assert!(chess.get_fen() == "rnb1kbnr/ppp1pppp/8/q7/8/2N5/PPPP1PPP/R1BQKBNR[Pp] w KQkq - 2 4");
+
+        let drop = chess.parse_san("@d5").unwrap();
+        assert!(drop == Move::new_drop(PieceType::Pawn, square_from_str("d5").unwrap()));
+        assert!(chess.to_san(drop) == Some("P@d5".to_string()));
+        assert!(chess.play_move(Move::from_uci("P@d5").unwrap()));
+        assert!(chess.get_pocket(Side::White) == [0; 6]);
+        assert!(!chess.play_move(Move::from_uci("P@a1").unwrap()));
+        assert!(!chess.play_move(Move::from_uci("N@e4").unwrap()));
+
+        // promoted pieces are marked and go back into the pocket as pawns
+        let mut chess = Chess::try_from_fen_variant("7k/1P6/8/8/8/8/1r6/4K3[] w - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.play_move(Move::from_uci("b7b8q").unwrap()));
+        assert!(chess.get_fen() == "1Q~5k/8/8/8/8/8/1r6/4K3[] b - - 0 1");
+        // the pocket may also be written as a ninth rank
+        let mut chess = Chess::try_from_fen_variant("1Q~5k/8/8/8/8/8/1r6/4K3/ b - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.play_move(chess.parse_san("Rxb8").unwrap()));
+        assert!(chess.get_fen() == "1r5k/8/8/8/8/8/8/4K3[p] w - - 0 2");
+
+        // a promoted queen is not the same position as a queen that was never a pawn
+        let crazyhouse = |fen: &str| Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        let (promoted, queen) = (crazyhouse("1Q~5k/8/8/8/8/8/1r6/4K3[] b - - 0 1"), crazyhouse("1Q5k/8/8/8/8/8/1r6/4K3[] b - - 0 1"));
+        assert!(promoted.get_zobrist_key() != queen.get_zobrist_key());
+        assert!(crazyhouse("1Q5k/8/8/8/8/8/1r6/4K3 b - - 0 1").get_zobrist_key() == Chess::from_fen("1Q5k/8/8/8/8/8/1r6/4K3 b - - 0 1".to_string()).get_zobrist_key());
+        assert!(crazyhouse("7k/8/8/8/8/8/8/4KQ~2[] b - - 0 1").get_fen() == "7k/8/8/8/8/8/8/4KQ~2[] b - - 0 1");
+
+        assert!(Chess::try_from_fen_variant("8/8/8/8/8/8/8/4K2k[Kq] w - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).is_err());
+    }
+
+    #[test]
+    fn crazyhouse_drops_in_check() {
+        // a lone knight drop counts every empty square
+        let chess = Chess::try_from_fen_variant("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.get_legal_moves().len() == 5 + 62);
+        let chess = Chess::try_from_fen_variant("4k3/8/8/8/8/8/8/4K3[P] w - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.get_legal_moves().len() == 5 + 48);
+
+        // the check is only blocked by drops between the rook and the king
+        let chess = Chess::try_from_fen_variant("k7/8/8/8/8/8/PP6/K6r[N] w - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.get_state() == State::Check);
+        assert!(chess.get_legal_moves().len() == 6);
+        assert!(chess.get_legal_moves().iter().all(|chess_move| chess_move.get_drop() == Some(PieceType::Knight) && chess_move.get_end() < 8));
+
+        // pawns are not dropped on the first rank, so this is mate
+        let chess = Chess::try_from_fen_variant("k7/8/8/8/8/8/PP6/K6r[P] w - - 0 1".to_string(), Arc::new(variant::Crazyhouse)).unwrap();
+        assert!(chess.get_state() == State::Checkmate);
+
+        let mut chess = Chess::new_variant(Arc::new(variant::Crazyhouse)).unwrap();
+        for san in ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+", "Qe2", "Qxe2+", "Bxe2", "Q@e4"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()), "{}", san);
+        }
+        let written = pgn::write_pgn(&[], &chess, "*");
+        assert!(written.contains("[Variant \"Crazyhouse\"]") && written.contains("Q@e4"));
+        let game = &pgn::parse_pgn(&written).unwrap()[0];
+        assert!(game.chess.get_fen() == chess.get_fen());
+    }
+
+    #[test]
+    fn perft_atomic() {
+        let positions: [(&str, [u64; 3]); 5] = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", [20, 400, 8902]),
+            ("rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1", [40, 1238, 45237]),
+            // castling next to the enemy king
+            ("8/8/8/8/8/8/2k5/rR4KR w KQ - 0 1", [18, 180, 4364]),
+            ("r3k1rR/5K2/8/8/8/8/8/8 b kq - 0 1", [25, 282, 6753]),
+            ("Rr2k1rR/3K4/3p4/8/8/8/7P/8 w kq - 0 1", [21, 465, 10631]),
+        ];
+
+        for (fen, counts) in positions {
+            let chess = Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::Atomic)).unwrap();
+            for (depth, count) in counts.iter().enumerate() {
+                assert!(chess.perft(depth as u32 + 1) == *count, "{} at depth {}", fen, depth + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn atomic_explosions() {
+        let atomic = || -> Arc<dyn variant::Variant> { Arc::new(variant::Atomic) };
+
+        // the capture takes the knights and the queen next to d5 with it, but not the pawns
+        let mut chess = Chess::try_from_fen_variant("4k3/8/2npn3/3q4/2P1P3/8/8/3QK3 w - - 0 1".to_string(), atomic()).unwrap();
+        assert!(chess.play_move(chess.parse_san("exd5").unwrap()));
+        assert!(chess.get_fen() == "4k3/8/3p4/8/2P5/8/8/3QK3 b - - 0 1");
+
+        // kings do not capture, and a capture next to the own king is not allowed
+        let chess = Chess::try_from_fen_variant("8/8/8/8/8/2k5/3p4/3QK3 w - - 0 1".to_string(), atomic()).unwrap();
+        assert!(chess.get_state() == State::Check);
+        assert!(!chess.get_legal_moves().contains(&Move::from_uci("e1d2").unwrap()));
+        assert!(!chess.get_legal_moves().contains(&Move::from_uci("d1d2").unwrap()));
+
+        // touching kings are never in check
+        let chess = Chess::try_from_fen_variant("8/8/8/8/8/8/3kK3/r7 w - - 0 1".to_string(), atomic()).unwrap();
+        assert!(chess.get_state() == State::Playing);
+        assert!(chess.get_legal_moves().contains(&Move::from_uci("e2d1").unwrap()));
+        assert!(!chess.get_legal_moves().contains(&Move::from_uci("e2f1").unwrap()));
+
+        // exploding the king ends the game
+        let mut chess = Chess::new_variant(atomic()).unwrap();
+        for san in ["Nf3", "f6", "Ne5", "a6", "Nxd7"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()), "{}", san);
+        }
+        assert!(chess.get_state() == State::VariantEnd);
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+        assert!(chess.get_legal_moves().is_empty());
+        assert!(chess.get_fen() == "rn3bnr/1pp1p1pp/p4p2/8/8/8/PPPPPPPP/RNBQKB1R b KQ - 0 3");
+
+        // the engine goes for the explosion
+        let chess = Chess::try_from_fen_variant("4k3/3p4/8/8/8/8/8/3RK3 w - - 0 1".to_string(), atomic()).unwrap();
+        let limits = search::SearchLimits { depth: Some(2), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+        assert!(result.best_move == Some(Move::from_uci("d1d7").unwrap()));
+    }
+
+    #[test]
+    fn antichess_rules() {
+        let antichess = || -> Arc<dyn variant::Variant> { Arc::new(variant::Antichess) };
+        let chess = Chess::new_variant(antichess()).unwrap();
+        assert!(chess.perft(4) == 153299);
+
+        // captures are compulsory and kings are taken like any other piece
+        let mut chess = Chess::try_from_fen_variant("8/8/8/8/8/8/3p4/4K3 w KQkq - 0 1".to_string(), antichess()).unwrap();
+        assert!(chess.get_fen() == "8/8/8/8/8/8/3p4/4K3 w - - 0 1");
+        assert!(chess.get_state() == State::Playing);
+        assert!(chess.get_legal_moves() == vec![Move::from_uci("e1d2").unwrap()]);
+        assert!(chess.to_san(Move::from_uci("e1d2").unwrap()) == Some("Kxd2".to_string()));
+
+        // black has nothing left, which wins
+        assert!(chess.play_move(Move::from_uci("e1d2").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::White, Termination::Variant)));
+
+        // pawns promote to a king as well
+        let mut chess = Chess::try_from_fen_variant("8/4P3/8/8/8/8/8/k7 w - - 0 1".to_string(), antichess()).unwrap();
+        assert!(chess.get_legal_moves().len() == 5);
+        assert!(chess.play_move(chess.parse_san("e8=K").unwrap()));
+        assert!(chess.get_fen() == "4K3/8/8/8/8/8/8/k7 b - - 0 1");
+
+        // a stalemated side wins
+        let chess = Chess::try_from_fen_variant("8/8/8/8/8/p7/P7/8 w - - 0 1".to_string(), antichess()).unwrap();
+        assert!(chess.get_state() == State::VariantEnd);
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+
+        // bishops on different square colors never meet
+        let chess = Chess::try_from_fen_variant("8/8/8/8/8/8/1b6/B7 w - - 0 1".to_string(), antichess()).unwrap();
+        assert!(chess.get_outcome().is_none());
+        let chess = Chess::try_from_fen_variant("8/8/8/8/8/8/b7/B7 w - - 0 1".to_string(), antichess()).unwrap();
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::InsufficientMaterial)));
+    }
+
+    #[test]
+    fn three_check() {
+        let mut chess = Chess::new_variant(Arc::new(variant::ThreeCheck)).unwrap();
+        assert!(chess.get_fen() == "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0");
+        for san in ["e4", "e5", "Bc4", "Nc6", "Bxf7+", "Kxf7", "Qh5+", "g6", "Qxg6+"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()));
+        }
+        assert!(chess.get_checks(Side::White) == 3);
+        assert!(chess.get_checks(Side::Black) == 0);
+        // the checks are kept by the variant, other variants have no such state
+        assert!(chess.board.get_variant_state::<variant::ThreeCheckState>().is_some_and(|state| state.get_checks(Side::White) == 3));
+        assert!(chess.board.get_variant_state::<variant::CrazyhouseState>().is_none());
+        assert!(Chess::new().board.get_variant_state::<variant::ThreeCheckState>().is_none());
+        assert!(chess.get_state() == State::VariantEnd);
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+
+        // checks given at the end, or checks remaining in the X-FEN form
+        let fen = "4k3/8/8/8/8/8/8/4K2R w K - 0 1 +2+1";
+        let chess = Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::ThreeCheck)).unwrap();
+        assert!(chess.get_fen() == fen);
+        assert!(chess.get_checks(Side::White) == 2);
+        let chess = Chess::try_from_fen_variant("4k3/8/8/8/8/8/8/4K2R w K - 1+2 0 1".to_string(), Arc::new(variant::ThreeCheck)).unwrap();
+        assert!(chess.get_fen() == fen);
+        assert!(Chess::try_from_fen_variant("4k3/8/8/8/8/8/8/4K2R w K - 0 1 +a+1".to_string(), Arc::new(variant::ThreeCheck)).is_err());
+
+        // the third check wins even if it could be answered
+        let mut chess = Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::ThreeCheck)).unwrap();
+        assert!(chess.play_move(chess.parse_san("Rh8+").unwrap()));
+        assert!(chess.get_fen() == "4k2R/8/8/8/8/8/8/4K3 b - - 1 1 +3+1");
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+
+        // a lone king never gives a check
+        let chess = Chess::try_from_fen_variant("4k3/8/8/8/8/8/8/4KN2 w - - 0 1 +0+0".to_string(), Arc::new(variant::ThreeCheck)).unwrap();
+        assert!(chess.get_state() == State::Playing);
+    }
+
+    #[test]
+    fn king_of_the_hill() {
+        let mut chess = Chess::new_variant(variant::from_name("King of the Hill").unwrap()).unwrap();
+        for san in ["e4", "d5", "Ke2", "Kd7", "Ke3", "Kd6", "exd5"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()));
+        }
+        assert!(chess.get_state() == State::Playing);
+        assert!(chess.play_move(chess.parse_san("Kxd5").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::White, Termination::Variant)));
+
+        // kings alone are still able to reach the center
+        let chess = Chess::try_from_fen_variant("k7/8/8/8/8/8/8/7K w - - 0 1".to_string(), Arc::new(variant::KingOfTheHill)).unwrap();
+        assert!(chess.get_state() == State::Playing);
+        let chess = Chess::try_from_fen_variant("k7/8/8/4K3/8/8/8/8 b - - 0 1".to_string(), Arc::new(variant::KingOfTheHill)).unwrap();
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+    }
+
+    #[test]
+    fn horde() {
+        let horde = || -> Arc<dyn variant::Variant> { Arc::new(variant::Horde) };
+        for (fen, nodes) in [
+            ("rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1", [8, 128, 1274]),
+            ("4k3/pp4q1/3P2p1/8/P3PP2/PPP2r2/PPP5/PPPP4 b - - 0 1", [30, 241, 6633]),
+            ("k7/5p2/4p2P/3p2P1/2p2P2/1p2P2P/p2P2P1/2P2P2 w - - 0 1", [13, 172, 2205]),
+        ] {
+            let chess = Chess::try_from_fen_variant(fen.to_string(), horde()).unwrap();
+            for (depth, nodes) in nodes.into_iter().enumerate() {
+                assert!(chess.perft(depth as u32 + 1) == nodes);
+            }
+        }
+
+        // pawns on the first rank move two squares, but are not taken en passant
+        let mut chess = Chess::try_from_fen_variant("k7/8/8/8/8/1p6/8/2P5 w - - 0 1".to_string(), horde()).unwrap();
+        assert!(chess.play_move(Move::from_uci("c1c3").unwrap()));
+        assert!(chess.get_fen() == "k7/8/8/8/8/1pP5/8/8 b - - 0 1");
+
+        // capturing the last white piece wins for black
+        let mut chess = Chess::try_from_fen_variant("k7/8/8/8/8/8/1p6/2P5 b - - 0 1".to_string(), horde()).unwrap();
+        assert!(chess.get_state() == State::Playing);
+        assert!(chess.play_move(Move::from_uci("b2c1q").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::White, Termination::Variant)));
+
+        // white still wins by checkmate
+        let chess = Chess::try_from_fen_variant("k7/PP6/1PP5/8/8/8/8/8 b - - 0 1".to_string(), horde()).unwrap();
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Checkmate)));
+    }
+
+    #[test]
+    fn racing_kings() {
+        let racing_kings = || -> Arc<dyn variant::Variant> { Arc::new(variant::RacingKings) };
+        for (fen, nodes) in [
+            ("8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1", [21, 421, 11264]),
+            ("4brn1/2K2k2/8/8/8/8/8/8 w - - 0 1", [6, 33, 178]),
+        ] {
+            let chess = Chess::try_from_fen_variant(fen.to_string(), racing_kings()).unwrap();
+            for (depth, nodes) in nodes.into_iter().enumerate() {
+                assert!(chess.perft(depth as u32 + 1) == nodes);
+            }
+        }
+
+        // giving check is not allowed
+        let chess = Chess::try_from_fen_variant("8/8/8/8/8/k7/7R/7K w - - 0 1".to_string(), racing_kings()).unwrap();
+        assert!(chess.get_legal_moves().len() == 13);
+        assert!(!chess.get_legal_moves().iter().any(|chess_move| chess_move.get_end() / 8 == 2 || chess_move.get_end() % 8 == 0));
+
+        // black reaches the goal first
+        let mut chess = Chess::try_from_fen_variant("8/1k6/8/8/8/8/6K1/8 b - - 0 1".to_string(), racing_kings()).unwrap();
+        assert!(chess.play_move(chess.parse_san("Kb8").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::White, Termination::Variant)));
+
+        // white reaches the goal, but black catches up and draws
+        let mut chess = Chess::try_from_fen_variant("8/1K4k1/8/8/8/8/8/8 w - - 0 1".to_string(), racing_kings()).unwrap();
+        assert!(chess.play_move(chess.parse_san("Kb8").unwrap()));
+        assert!(chess.get_state() == State::Playing);
+        assert!(chess.play_move(chess.parse_san("Kg8").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::draw(Termination::Variant)));
+
+        // white wins when black is too far away to catch up
+        let mut chess = Chess::try_from_fen_variant("8/1K6/6k1/8/8/8/8/8 w - - 0 1".to_string(), racing_kings()).unwrap();
+        assert!(chess.play_move(chess.parse_san("Kb8").unwrap()));
+        assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+    }
+
+    #[test]
+    fn dark_chess_view() {
+        let mut chess = Chess::new();
+        assert!(fog::get_visible_squares(&chess, Side::White).to_number() == 0xFFFFFFFF);
+        let view = fog::get_dark_view(&chess, Side::White);
+        assert!(view.get_piece_type_at_pos(notation_to_index("E2")) == PieceType::Pawn);
+        assert!((32..64).all(|index| view.get_piece_type_at_pos(index) == PieceType::Empty));
+
+        for san in ["e4", "d5", "Nf3"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()));
+        }
+        let view = fog::get_dark_view(&chess, Side::White);
+        assert!(view.get_piece_at_pos(notation_to_index("D5")).unwrap().get_color() == Side::Black);
+        assert!(view.get_piece_type_at_pos(notation_to_index("D7")) == PieceType::Empty);
+        assert!(view.get_piece_type_at_pos(notation_to_index("E8")) == PieceType::Empty);
+        assert!(view.get_playing_side() == Side::Black);
+
+        // the pawn on d5 sees the one on e4, the queen looks down the open file
+        let view = fog::get_dark_view(&chess, Side::Black);
+        assert!(view.get_piece_type_at_pos(notation_to_index("E4")) == PieceType::Pawn);
+        assert!(view.get_piece_type_at_pos(notation_to_index("D2")) == PieceType::Empty);
+        assert!(view.get_piece_type_at_pos(notation_to_index("F3")) == PieceType::Empty);
+    }
+
+    #[test]
+    fn kriegspiel_referee() {
+        let mut referee = fog::Referee::new(Chess::new());
+        let legal = |capture, checks| fog::Announcement::Legal { capture, checks, outcome: None };
+        assert!(referee.try_move(Move::from_uci("e2e5").unwrap()) == fog::Announcement::Illegal);
+        assert!(referee.get_playing_side() == Side::White);
+        assert!(referee.try_move(Move::from_uci("e2e4").unwrap()) == legal(None, vec![]));
+        assert!(referee.try_move(Move::from_uci("d7d5").unwrap()) == legal(None, vec![]));
+        assert!(referee.try_move(Move::from_uci("e4d5").unwrap()) == legal(Some(notation_to_index("D5")), vec![]));
+        assert!(referee.get_chess().get_fen() == "rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2");
+
+        // en passant is announced on the square of the taken pawn, not on the empty square behind it
+        assert!(referee.try_move(Move::from_uci("c7c5").unwrap()) == legal(None, vec![]));
+        assert!(referee.try_move(Move::from_uci("d5c6").unwrap()) == legal(Some(notation_to_index("C5")), vec![]));
+        let mut referee = fog::Referee::new(Chess::from_fen("rnbqkbnr/ppp1pppp/8/3P4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2".to_string()));
+
+        // each side only sees its own pieces
+        let view = referee.get_view(Side::Black);
+        assert!(view.get_piece_type_at_pos(notation_to_index("D5")) == PieceType::Empty);
+        assert!(view.get_piece_type_at_pos(notation_to_index("E1")) == PieceType::Empty);
+        assert!(view.get_piece_type_at_pos(notation_to_index("E8")) == PieceType::King);
+
+        // the long diagonal of e8 is e8-a4, the short one e8-h5
+        assert!(referee.try_move(Move::from_uci("e7e6").unwrap()) == legal(None, vec![]));
+        assert!(referee.try_move(Move::from_uci("f1b5").unwrap()) == legal(None, vec![fog::CheckDirection::LongDiagonal]));
+
+        for (fen, chess_move, checks) in [
+            ("4k3/8/8/8/8/8/8/4K2R w - - 0 1", "h1h8", vec![fog::CheckDirection::Rank]),
+            ("3k4/8/8/8/8/4R3/8/4K3 w - - 0 1", "e3d3", vec![fog::CheckDirection::File]),
+            ("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1", "e4f6", vec![fog::CheckDirection::Knight]),
+            ("4k3/5N2/8/7B/8/8/8/4K3 w - - 0 1", "f7d6", vec![fog::CheckDirection::ShortDiagonal, fog::CheckDirection::Knight]),
+        ] {
+            let mut referee = fog::Referee::new(Chess::from_fen(fen.to_string()));
+            match referee.try_move(Move::from_uci(chess_move).unwrap()) {
+                fog::Announcement::Legal { checks: given, .. } => assert!(given.len() == checks.len() && checks.iter().all(|check| given.contains(check))),
+                fog::Announcement::Illegal => panic!("{} is legal in {}", chess_move, fen),
+            }
+        }
+
+        // larger boards have longer diagonals, and every leap that is not along a line counts as a knight
+        let capablanca = variant::from_name("Capablanca").unwrap();
+        for (fen, chess_move, check) in [
+            ("9k/10/10/6B3/10/10/10/KR8 w - - 0 1", "g5h6", fog::CheckDirection::LongDiagonal),
+            ("8k1/10/8B1/10/10/10/10/KR8 w - - 0 1", "i6j7", fog::CheckDirection::ShortDiagonal),
+            ("4k5/10/10/2A7/10/10/10/4K5 w - - 0 1", "c5d6", fog::CheckDirection::Knight),
+            ("4k5/10/10/10/2L7/10/10/4K5 w - - 0 1", "c4f5", fog::CheckDirection::Knight),
+        ] {
+            let mut referee = fog::Referee::new(Chess::try_from_fen_variant(fen.to_string(), capablanca.clone()).unwrap());
+            let chess_move = Move::from_uci_with(chess_move, Dimensions::new(10, 8).unwrap()).unwrap();
+            assert!(referee.try_move(chess_move) == legal(None, vec![check]));
+        }
+
+        // the announcement tells when the game is over
+        let mut referee = fog::Referee::new(Chess::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1".to_string()));
+        let outcome = Some(Outcome::loss(Side::Black, Termination::Checkmate));
+        assert!(referee.try_move(Move::from_uci("h1h8").unwrap()) == fog::Announcement::Legal { capture: None, checks: vec![fog::CheckDirection::Rank], outcome });
+    }
+
+    #[test]
+    fn bughouse() {
+        let mut game = bughouse::BughouseGame::new();
+        assert!(game.get_board(0).get_fen() == "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1");
+        for (board, uci) in [(0, "e2e4"), (1, "d2d4"), (0, "d7d5"), (0, "e4d5"), (1, "g8f6"), (0, "d8d5")] {
+            assert!(game.play_move(board, Move::from_uci(uci).unwrap()));
+        }
+
+        // captures go to the partner, never to the capturing side
+        assert!(game.get_board(0).get_fen() == "rnb1kbnr/ppp1pppp/8/3q4/8/8/PPPP1PPP/RNBQKBNR[] w KQkq - 0 3");
+        assert!(game.get_board(1).get_fen() == "rnbqkb1r/pppppppp/5n2/8/3P4/8/PPP1PPPP/RNBQKBNR[Pp] w KQkq - 1 2");
+
+        // the pawns from the first board are dropped on the second
+        assert!(!game.play_move(1, Move::from_uci("N@e5").unwrap()));
+        assert!(game.play_move(1, Move::from_uci("P@e5").unwrap()));
+        assert!(game.play_move(1, Move::from_uci("P@e4").unwrap()));
+        assert!(game.get_board(1).get_pocket(Side::White) == [0; 6] && game.get_board(1).get_pocket(Side::Black) == [0; 6]);
+        assert!(!game.play_move(2, Move::from_uci("e2e4").unwrap()));
+
+        // mate on one board ends the game for both teams, unless a piece in hand blocks it
+        let second = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1";
+        let mut game = bughouse::BughouseGame::from_fens("6k1/5ppp/8/8/8/8/8/K2R4[n] w - - 0 1", second).unwrap();
+        assert!(game.play_move(0, Move::from_uci("d1d8").unwrap()));
+        assert!(game.get_outcome().is_none());
+        let mut game = bughouse::BughouseGame::from_fens("6k1/5ppp/8/8/8/8/8/K2R4[] w - - 0 1", second).unwrap();
+        assert!(game.play_move(0, Move::from_uci("d1d8").unwrap()));
+        let outcome = game.get_outcome().unwrap();
+        assert!(outcome.winner == Some(bughouse::Team::First) && outcome.board == 0 && outcome.termination == Termination::Checkmate);
+        assert!(!game.play_move(1, Move::from_uci("e2e4").unwrap()));
+
+        let time = clock::ManualTime::default();
+        let control = clock::TimeControl::sudden_death(Duration::from_secs(60));
+        let mut game = bughouse::BughouseGame::new();
+        game.set_clocks(clock::Clock::with_time_source(control.clone(), Arc::new(time.clone())), clock::Clock::with_time_source(control, Arc::new(time.clone())));
+        assert!(game.play_move(0, Move::from_uci("e2e4").unwrap()));
+        time.advance(Duration::from_secs(30));
+        assert!(game.play_move(0, Move::from_uci("e7e5").unwrap()));
+        time.advance(Duration::from_secs(31));
+
+        // white on the second board never moved
+        let outcome = game.get_outcome().unwrap();
+        assert!(outcome.winner == Some(bughouse::Team::First) && outcome.board == 1 && outcome.termination == Termination::TimeForfeit);
+        assert!(!game.play_move(0, Move::from_uci("d2d4").unwrap()));
+
+        // the first board to end decides the game, the clocks stop so no flag falls afterwards
+        let time = clock::ManualTime::default();
+        let control = clock::TimeControl::sudden_death(Duration::from_secs(60));
+        let mut game = bughouse::BughouseGame::from_fens(second, "6k1/5ppp/8/8/8/8/8/K2R4[] w - - 0 1").unwrap();
+        game.set_clocks(clock::Clock::with_time_source(control.clone(), Arc::new(time.clone())), clock::Clock::with_time_source(control, Arc::new(time.clone())));
+        assert!(game.play_move(1, Move::from_uci("d1d8").unwrap()));
+        time.advance(Duration::from_secs(61));
+        let outcome = game.get_outcome().unwrap();
+        assert!(outcome.winner == Some(bughouse::Team::Second) && outcome.board == 1 && outcome.termination == Termination::Checkmate);
+        assert!(game.get_board(0).get_clock().unwrap().get_flagged().is_none());
+        assert!(!game.play_move(0, Move::from_uci("e2e4").unwrap()));
+    }
+
+    #[test]
+    fn betza_notation() {
+        let d4 = notation_to_index("D4");
+        let count = |notation: &str| Betza::parse(notation).unwrap().get_moves(Dimensions::STANDARD, d4, Side::White, 0, 0).count_ones();
+        assert!(count("BN") == 21);
+        assert!(count("RN") == 22);
+        assert!(count("QN") == 35);
+        assert!(count("C") == 8);
+        assert!(count("gQ") == 0);
+        assert!(count("K") == 8 && count("WF") == 8);
+        assert!(count("NN") == 12 && count("W2") == 8);
+        // two modifiers narrow the steps down, unless they are opposite directions
+        let moves = |notation: &str| Betza::parse(notation).unwrap().get_moves(Dimensions::STANDARD, d4, Side::White, 0, 0);
+        assert!(moves("flF") == 1 << notation_to_index("C5"));
+        assert!(moves("brF") == 1 << notation_to_index("E3"));
+        assert!(moves("flbrF") == 1 << notation_to_index("C5") | 1 << notation_to_index("E3"));
+        assert!(moves("fbW") == 1 << notation_to_index("D5") | 1 << notation_to_index("D3"));
+        assert!(moves("frN") == 1 << notation_to_index("E6") | 1 << notation_to_index("F5"));
+        assert!(count("fF") == 2 && count("vN") == 4 && count("fsN") == 6);
+        assert!(Betza::parse("").is_err() && Betza::parse("X").is_err() && Betza::parse("zW").is_err() && Betza::parse("m").is_err());
+
+        // a pawn without its double step, it moves forward and captures diagonally forward
+        let pawn = Betza::parse("mfWcfF").unwrap();
+        let e2 = notation_to_index("E2");
+        let opponent = 1 << notation_to_index("D3") | 1 << notation_to_index("E3");
+        assert!(pawn.get_moves(Dimensions::STANDARD, e2, Side::White, 0, opponent) == 1 << notation_to_index("D3"));
+        assert!(pawn.get_moves(Dimensions::STANDARD, e2, Side::Black, 0, 0) == 1 << notation_to_index("E1"));
+        assert!(pawn.get_attacks(Dimensions::STANDARD, e2, Side::White, 0) == 1 << notation_to_index("D3") | 1 << notation_to_index("F3"));
+
+        // the grasshopper lands right behind the first piece in its way
+        let grasshopper = Betza::parse("gQ").unwrap();
+        let e4 = notation_to_index("E4");
+        let hurdle = 1 << notation_to_index("E6");
+        assert!(grasshopper.get_moves(Dimensions::STANDARD, e4, Side::White, hurdle, 0) == 1 << notation_to_index("E7"));
+        assert!(grasshopper.get_moves(Dimensions::STANDARD, e4, Side::White, 0, hurdle) == 1 << notation_to_index("E7"));
+    }
+
+    #[test]
+    fn fairy_pieces() {
+        let chess = Chess::from_fen("4k3/8/8/8/3A4/8/8/4K3 w - - 0 1".to_string());
+        assert!(chess.get_legal_moves().len() == 26);
+        assert!(chess.get_fen() == "4k3/8/8/8/3A4/8/8/4K3 w - - 0 1");
+        assert!(chess.to_san(Move::from_uci("d4e6").unwrap()) == Some("Ae6".to_string()));
+        assert!(chess.get_state() == State::Playing);
+
+        // fairy pieces give check, pin and get captured like any other piece
+        assert!(Chess::from_fen("4k3/8/8/8/4C3/8/8/4K3 b - - 0 1".to_string()).get_state() == State::Check);
+        assert!(Chess::from_fen("4k3/8/8/5L2/8/8/8/4K3 b - - 0 1".to_string()).get_state() == State::Check);
+        assert!(Chess::from_fen("4k3/4c3/8/8/8/8/4N3/4K3 w - - 0 1".to_string()).get_legal_moves().len() == 4);
+        assert!(Chess::from_fen("k7/8/8/8/8/8/2m5/K7 w - - 0 1".to_string()).get_state() == State::Checkmate);
+
+        // the grasshopper needs a hurdle, so moving the hurdle away ends its check
+        let chess = Chess::from_fen("4k3/4p3/8/8/4G3/8/8/4K3 b - - 0 1".to_string());
+        assert!(chess.get_state() == State::Check);
+        assert!(chess.get_legal_moves().contains(&Move::from_uci("e7e6").unwrap()));
+        let mut chess = Chess::from_fen("4k3/8/4p3/8/4G3/8/8/4K3 w - - 0 1".to_string());
+        assert!(chess.get_moves(notation_to_index("E4")).len() == 1);
+        assert!(chess.play_move(chess.parse_san("Ge7").unwrap()));
+        assert!(chess.get_state() == State::Playing);
+
+        // the king is not its own hurdle, once it moved on along the line nothing is in the way
+        let chess = Chess::from_fen("8/8/8/8/4k3/8/8/K3G3 b - - 0 1".to_string());
+        assert!(chess.get_legal_moves().contains(&Move::from_uci("e4e5").unwrap()));
+        assert!(chess.get_legal_moves().len() == 8);
+        let chess = Chess::from_fen("8/8/8/8/3k4/4n3/8/K3G3 b - - 0 1".to_string());
+        assert!(!chess.get_legal_moves().contains(&Move::from_uci("d4e4").unwrap()));
+
+        // a lone amazon is enough to win
+        assert!(!Chess::from_fen("4k3/8/8/8/8/8/8/3MK3 w - - 0 1".to_string()).has_insufficient_material(Side::White));
+    }
+
+    #[test]
+    fn board_dimensions() {
+        let grand = Dimensions::new(10, 10).unwrap();
+        assert!(grand.square_from_str("a10") == Some(90) && grand.square_to_string(90) == "a10");
+        assert!(grand.square_from_str("k1").is_none() && grand.square_from_str("a11").is_none() && grand.square_from_str("a01").is_none());
+        assert!(Dimensions::new(17, 8).is_err() && Dimensions::new(12, 12).is_err() && Dimensions::new(0, 8).is_err());
+        assert!(Dimensions::default() == Dimensions::STANDARD);
+
+        let promotion = Move::from_uci_with("a9a10q", grand).unwrap();
+        assert!(promotion.get_start() == 80 && promotion.get_end() == 90 && promotion.get_promotion() == Some(PieceType::Queen));
+        assert!(promotion.to_uci_with(grand) == "a9a10q");
+        assert!(Move::from_uci_with("a9a10qq", grand).is_err() && Move::from_uci_with("a9a10p", grand).is_err());
+        assert!(Move::from_uci_with("k1k2", grand).is_err() && Move::from_uci_with("a1", grand).is_err());
+        assert!(Move::from_uci("e7e8c").unwrap().get_promotion() == Some(PieceType::Chancellor));
+
+        // empty squares of ten files are written with two digits
+        let variant = variant::from_name("Capablanca").unwrap();
+        let fen = "r3k4r/10/10/4Pp4/10/10/10/R3K4R w - f6 0 1";
+        let chess = Chess::try_from_fen_variant(fen.to_string(), variant.clone()).unwrap();
+        assert!(chess.get_fen() == fen);
+        assert!(chess.board.get_dimensions().get_width() == 10);
+        assert!(chess.get_legal_moves().contains(&Move::from_uci_with("e5f6", chess.board.get_dimensions()).unwrap()));
+        assert!(Chess::try_from_fen_variant("r3k4r/11/10/10/10/10/10/R3K4R w - - 0 1".to_string(), variant.clone()).is_err());
+        assert!(Chess::try_from_fen_variant("r3k4r/10/10/10/10/10/R3K4R w - - 0 1".to_string(), variant.clone()).is_err());
+        assert!(Chess::try_from_fen_variant("r3k4r/010/10/10/10/10/10/10/R3K4R w - - 0 1".to_string(), variant).is_err());
+    }
+
+    #[test]
+    fn capablanca() {
+        let chess = Chess::new_variant(Arc::new(variant::Capablanca)).unwrap();
+        assert!(chess.perft(1) == 28);
+        assert!(chess.perft(2) == 784);
+        assert!(chess.perft(3) == 25228);
+
+        // the king castles three squares from the f-file
+        let mut chess = Chess::try_from_fen_variant("r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1".to_string(), Arc::new(variant::Capablanca)).unwrap();
+        assert!(chess.to_san(Move::from_uci_with("f1i1", chess.board.get_dimensions()).unwrap()) == Some("O-O".to_string()));
+        assert!(chess.play_move(chess.parse_san("O-O").unwrap()));
+        assert!(chess.play_move(chess.parse_san("O-O-O").unwrap()));
+        assert!(chess.get_fen() == "2kr5r/10/10/10/10/10/10/R6RK1 w - - 2 2");
+
+        // pawns promote to the new pieces as well
+        let chess = Chess::try_from_fen_variant("5k4/P9/10/10/10/10/10/5K4 w - - 0 1".to_string(), Arc::new(variant::Capablanca)).unwrap();
+        assert!(chess.to_san(Move::from_uci_with("a7a8c", chess.board.get_dimensions()).unwrap()) == Some("a8=C+".to_string()));
+        assert!(chess.get_legal_moves().len() == 5 + 6);
+
+        // the search plays on the larger board as well
+        let limits = search::SearchLimits { depth: Some(3), ..Default::default() };
+        let result = search::Search::new(limits, Arc::new(AtomicBool::new(false))).run(&chess.board);
+        assert!(result.best_move.unwrap().get_promotion().is_some());
+    }
+
+    #[test]
+    fn grand_chess() {
+        let chess = Chess::new_variant(Arc::new(variant::Grand)).unwrap();
+        assert!(chess.perft(1) == 65);
+        assert!(chess.perft(2) == 4225);
+        assert!(chess.perft(3) == 259514);
+
+        // pawns move two squares from the third rank
+        let mut chess = Chess::new_variant(Arc::new(variant::Grand)).unwrap();
+        for san in ["e5", "a6", "e6", "d6"] {
+            assert!(chess.play_move(chess.parse_san(san).unwrap()));
+        }
+        assert!(chess.get_fen() == "r8r/1nbqkcabn1/1pp1pppppp/10/p2pP5/10/10/PPPP1PPPPP/1NBQKCABN1/R8R w - d7 0 3");
+        assert!(chess.play_move(chess.parse_san("exd7").unwrap()));
+
+        // promotion is optional on the eighth and ninth rank and only to pieces that were captured
+        let fen = "k9/10/10/4P5/10/10/10/10/10/K5RR2 w - - 0 1";
+        let chess = Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::Grand)).unwrap();
+        let pawn: Vec<_> = chess.get_legal_moves().into_iter().filter(|chess_move| chess_move.get_start() == 64).collect();
+        assert!(pawn.len() == 6);
+        assert!(chess.to_san(pawn[1]) == Some("e8=Q".to_string()));
+        assert!(chess.get_legal_moves().iter().all(|chess_move| chess_move.get_promotion() != Some(PieceType::Rook)));
+
+        // on the last rank a pawn has to promote, and with nothing to promote to it is stuck
+        let fen = "k9/4P5/10/10/10/10/10/10/10/QCAK6 w - - 0 1";
+        let chess = Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::Grand)).unwrap();
+        let dimensions = chess.board.get_dimensions();
+        assert!(chess.get_legal_moves().iter().filter(|chess_move| chess_move.get_start() == 84).count() == 3);
+        let mut board = chess.board.clone();
+        assert!(!board.make_move(Move::from_uci_with("e9e10", dimensions).unwrap()));
+        assert!(!board.make_move(Move::from_uci_with("e9e10q", dimensions).unwrap()));
+        assert!(board.make_move(Move::from_uci_with("e9e10r", dimensions).unwrap()));
+        let fen = "k9/4P5/10/10/10/10/10/10/10/QCAKRRBBNN w - - 0 1";
+        let chess = Chess::try_from_fen_variant(fen.to_string(), Arc::new(variant::Grand)).unwrap();
+        assert!(chess.get_moves(84).is_empty());
+    }
+
+    #[test]
+    fn cli_session() {
+        use crate::cli::{Command, Session};
+
+        let mut session = Session::new();
+        let mut run = |line: &str| Command::parse(line).and_then(|command| session.execute(&command));
+
+        assert!(run("e4") == Ok("e4".to_string()));
+        assert!(run("move e7 e5") == Ok("e5".to_string()));
+        assert!(run("g1f3") == Ok("Nf3".to_string()));
+        assert!(run("Ke3") == Err("Illegal move: Ke3".to_string()));
+        assert!(run("e2e5") == Err("Illegal move: e2e5".to_string()));
+        assert!(run("perft x") == Err("Invalid depth: x".to_string()));
+        assert!(run("pinned x").is_err());
+        assert!(run("frobnicate the board").is_err());
+        assert!(run("undo") == Ok("Took back g1f3".to_string()));
+        assert!(run("fen") == Ok("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2".to_string()));
+        assert!(run("perft 1").unwrap().starts_with("29 nodes"));
+
+        // loading a FEN starts a new game instead of only replacing the board
+        assert!(run("fen 4k3/8/8/8/8/8/8/4K2R w K - 0 1").is_ok());
+        assert!(run("undo").is_err());
+        assert!(run("fen not a fen").is_err());
+        assert!(run("O-O") == Ok("O-O".to_string()));
+
+        // each rook is pinned against its own king
+        assert!(run("fen 4k3/4r3/8/8/8/8/4R3/4K3 w - - 0 1").is_ok());
+        let rook_on = |rank: usize| (1..=8).rev()
+            .map(|y| format!("{}|{}\n", y, if y == rank { "00001000" } else { "00000000" }))
+            .collect::<String>() + "  ABCDEFGH\n";
+        assert!(run("pinned w") == Ok(rook_on(2)));
+        assert!(run("pinned b") == Ok(rook_on(7)));
+
+        assert!(run("fen 6k1/5ppp/8/8/8/8/8/R6K w - - 0 1").is_ok());
+        assert!(run("Ra8") == Ok("Ra8#\nGame over: 1-0 by Checkmate".to_string()));
+        assert!(run("moves") == Ok("No legal moves".to_string()));
+        assert!(run("pgn").unwrap().ends_with("1. Ra8# 1-0"));
+
+        assert!(run("new capablanca").unwrap().contains("a b c d e f g h i j"));
+        assert!(run("white").unwrap().starts_with("8|0000000000\n7|0000000000\n"));
+        assert!(run("white").unwrap().ends_with("2|1111111111\n1|1111111111\n  ABCDEFGHIJ\n"));
+        assert!(run("new nothing").is_err());
+        assert!(run("hint 2").is_ok());
+
+        let mut session = Session::new();
+        let json = session.execute_json(" e4 ").unwrap();
+        assert!(json.starts_with("{\"command\":\"e4\",\"ok\":true,\"output\":\"e4\",\"error\":null,"));
+        assert!(json.contains("\"turn\":\"Black\",\"state\":\"Playing\",\"result\":null,\"moves\":[\""));
+        // the fields are split by eight commas and the twenty replies by nineteen
+        assert!(json.contains("\"e7e5\"") && json.matches(',').count() == 8 + 19);
+        assert!(session.execute_json("e4").unwrap_err().contains("\"ok\":false,\"output\":null,\"error\":\"Illegal move: e4\""));
+    }
+
+    #[test]
+    fn board_rendering() {
+        use crate::render::{PieceStyle, RenderOptions};
+
+        let mut chess = Chess::new();
+        assert!(chess.to_string().starts_with("8 r n b q k b n r\n7 p p p p p p p p\n"));
+        assert!(chess.to_string().ends_with("1 R N B Q K B N R\n  a b c d e f g h\n"));
+
+        let black = RenderOptions { perspective: Side::Black, coordinates: false, ..Default::default() };
+        assert!(chess.render(&black).starts_with("R N B K Q B N R\nP P P P P P P P\n"));
+        let unicode = RenderOptions { style: PieceStyle::Unicode, ..Default::default() };
+        assert!(chess.render(&unicode).starts_with("8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜\n7 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟\n6 · · ·"));
+
+        // highlights only show up as colours
+        assert!(!chess.to_string().contains('\x1b'));
+        let g1 = square_from_str("g1").unwrap();
+        let destinations = chess.render(&RenderOptions { destinations: Some(g1), ..Default::default() });
+        assert!(destinations.contains("3 . . . . . \x1b[42m.\x1b[0m . \x1b[42m.\x1b[0m\n"));
+
+        for notation in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            chess.play_move(Move::from_uci(notation).unwrap());
+        }
+        assert!(chess.render_compact() == "rnb.kbnr/pppp.ppp/......../....p.../......Pq/.....P../PPPPP..P/RNBQKBNR w");
+        let options = RenderOptions { last_move: chess.get_history().last().copied(), check: true, ..Default::default() };
+        let text = chess.render(&options);
+        assert!(text.contains("8 r n b \x1b[43m.\x1b[0m k"));
+        assert!(text.contains("P P P P P . . P\n1 R N B Q \x1b[41mK\x1b[0m B N R\n"));
+
+        let grand = Chess::new_variant(Arc::new(variant::Grand)).unwrap();
+        assert!(grand.to_string().starts_with("10 r . . . . . . . . r\n 9 . n b q k c a b n ."));
+        assert!(grand.to_string().ends_with("   a b c d e f g h i j\n"));
+    }
+
+    #[test]
+    fn svg_diagram() {
+        use crate::svg::{self, Arrow, MarkColor, SquareMark, SvgOptions};
+
+        let mut chess = Chess::new();
+        let svg = chess.to_svg(&SvgOptions::default());
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("viewBox=\"0 0 360 360\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // no external assets, every piece uses a path defined in the diagram
+        assert!(svg.matches("<use xlink:href=\"#").count() == 32);
+        assert!(svg.matches("<g id=").count() == 12);
+        assert!(!svg.contains("href=\"http") && !svg.contains("<image"));
+        // the white king on e1 and the file names on the bottom rank
+        assert!(svg.contains("<use xlink:href=\"#white-king\" transform=\"translate(180 315) scale(0.45)\"/>"));
+        assert!(svg.contains(">h</text>") && svg.contains(">8</text>"));
+
+        chess.play_move(Move::from_uci("e2e4").unwrap());
+        let (arrows, marks) = svg::parse_annotations("{ [%cal Gg1f3,Rd8h4] [%csl Yf7] }", Dimensions::STANDARD).unwrap();
+        assert!(arrows[1] == Arrow { from: square_from_str("d8").unwrap(), to: square_from_str("h4").unwrap(), color: MarkColor::Red });
+        assert!(marks == vec![SquareMark { square: square_from_str("f7").unwrap(), color: MarkColor::Yellow }]);
+        assert!(svg::parse_annotations("[%cal Xe2e4]", Dimensions::STANDARD).is_err());
+        assert!(svg::parse_annotations("[%csl Gz9]", Dimensions::STANDARD).is_err());
+
+        let options = SvgOptions {
+            square_size: 50,
+            light: "#eeeeee".to_string(),
+            dark: "#8877aa".to_string(),
+            coordinates: false,
+            perspective: Side::Black,
+            last_move: chess.get_history().last().copied(),
+            arrows,
+            marks,
+            ..Default::default()
+        };
+        let svg = chess.to_svg(&options);
+        assert!(svg.contains("fill=\"#eeeeee\"") && svg.contains("fill=\"#8877aa\""));
+        assert!(!svg.contains("</text>"));
+        assert!(svg.matches("<polygon").count() == 2);
+        // flipped, so the white king is on the top row and e4 is on the fourth row
+        assert!(svg.contains("<use xlink:href=\"#white-king\" transform=\"translate(150 0) scale(0.5)\"/>"));
+        assert!(svg.contains("<rect x=\"150\" y=\"150\" width=\"50\" height=\"50\" fill=\"#9bc700\" fill-opacity=\"0.4\"/>"));
+
+        let grand = Chess::new_variant(Arc::new(variant::Grand)).unwrap();
+        let svg = grand.to_svg(&SvgOptions::default());
+        assert!(svg.contains("viewBox=\"0 0 450 450\"") && svg.contains(">C</text>") && svg.contains(">10</text>"));
+    }
+
     // #[test]
     // fn cli() {
     //     cli::start();
     // }
 }
-
-fn notation_to_index(move_notation: &'static str) -> usize {
-    let mut chars = move_notation.chars();
-    let file = (chars.next().unwrap() as u8) - b'A';
-    let rank = (chars.next().unwrap() as u8) - b'1';
-    (rank * 8 + file) as usize
-}
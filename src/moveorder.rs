@@ -0,0 +1,232 @@
+use crate::search::MAX_PLY;
+use crate::{eval, Board, Move, PieceType, NUM_INDECES};
+
+// history scores are halved once any of them gets above this
+const HISTORY_MAX: i32 = 1 << 20;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+/// The stages of the move picker, in the order that the moves are returned
+///
+/// # HashMove: The best move from the transposition table or the principal variation
+/// # Captures: Captures that do not lose material by static exchange evaluation, most valuable
+/// victim first and least valuable attacker second
+/// # Promotions: Promotions that do not capture
+/// # Killers: Quiet moves that caused a beta cutoff at the same ply
+/// # Quiets: The remaining moves ordered by the history heuristic
+/// # BadCaptures: Captures that lose material, the least losing first
+///
+pub enum Stage {
+    HashMove,
+    Captures,
+    Promotions,
+    Killers,
+    Quiets,
+    BadCaptures,
+}
+
+/// Two quiet moves per ply that caused a beta cutoff
+pub struct Killers {
+    moves: Vec<[Option<Move>; 2]>,
+}
+
+impl Default for Killers {
+    fn default() -> Self {
+        Killers::new()
+    }
+}
+
+impl Killers {
+    pub fn new() -> Killers {
+        Killers {
+            moves: vec![[None; 2]; MAX_PLY],
+        }
+    }
+
+    /// Remember a move that caused a beta cutoff at ply, pushing out the oldest one
+    pub fn store(&mut self, ply: usize, chess_move: Move) {
+        if ply >= MAX_PLY {
+            return;
+        }
+
+        let killers = &mut self.moves[ply];
+        if killers[0] != Some(chess_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(chess_move);
+        }
+    }
+
+    /// Get the killer moves of ply, the newest first
+    pub fn get(&self, ply: usize) -> [Option<Move>; 2] {
+        self.moves.get(ply).copied().unwrap_or([None; 2])
+    }
+
+    /// Forget all killer moves
+    pub fn clear(&mut self) {
+        self.moves.fill([None; 2]);
+    }
+}
+
+/// Score of quiet moves by how often they caused a beta cutoff, indexed by start and end index
+pub struct History {
+    table: Vec<[i32; NUM_INDECES]>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            table: vec![[0; NUM_INDECES]; NUM_INDECES],
+        }
+    }
+
+    /// Reward a quiet move that caused a beta cutoff at depth, deeper cutoffs count more
+    pub fn add(&mut self, chess_move: Move, depth: i32) {
+        let score = &mut self.table[chess_move.get_start()][chess_move.get_end()];
+        *score += depth * depth;
+
+        if *score > HISTORY_MAX {
+            self.age();
+        }
+    }
+
+    /// Get the history score of a move
+    pub fn get(&self, chess_move: Move) -> i32 {
+        self.table[chess_move.get_start()][chess_move.get_end()]
+    }
+
+    /// Halve all scores so that new cutoffs weigh more than old ones
+    pub fn age(&mut self) {
+        for row in self.table.iter_mut() {
+            for score in row.iter_mut() {
+                *score /= 2;
+            }
+        }
+    }
+
+    /// Forget all scores
+    pub fn clear(&mut self) {
+        self.table.fill([0; NUM_INDECES]);
+    }
+}
+
+/// Returns the moves of a legal move list one at a time, best guess first
+///
+/// Moves are sorted lazily, so when a beta cutoff happens early the rest never get sorted
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new();
+/// let killers = Killers::new();
+/// let history = History::new();
+/// let picker = MovePicker::new(&chess.board, chess.get_legal_moves(), None, killers.get(0), &history);
+/// for chess_move in picker {
+///     println!("{}", chess_move);
+/// }
+/// ```
+pub struct MovePicker {
+    moves: Vec<(Move, Stage, i32)>,
+    index: usize,
+    stage: Option<Stage>,
+}
+
+impl MovePicker {
+    /// Create a move picker over moves, which should be the legal moves of board
+    pub fn new(board: &Board, moves: Vec<Move>, hash_move: Option<Move>, killers: [Option<Move>; 2], history: &History) -> MovePicker {
+        let moves = moves.into_iter().map(|chess_move| {
+            let (stage, score) = if Some(chess_move) == hash_move {
+                (Stage::HashMove, 0)
+            } else if board.is_capture(chess_move) {
+                capture_stage(board, chess_move)
+            } else if let Some(promotion) = chess_move.get_promotion() {
+                (Stage::Promotions, eval::piece_value(promotion))
+            } else if let Some(index) = killers.iter().position(|killer| *killer == Some(chess_move)) {
+                (Stage::Killers, -(index as i32))
+            } else {
+                (Stage::Quiets, history.get(chess_move))
+            };
+
+            (chess_move, stage, score)
+        }).collect();
+
+        MovePicker {
+            moves,
+            index: 0,
+            stage: None,
+        }
+    }
+
+    /// Create a move picker that only returns the captures and promotions of moves
+    pub fn new_captures(board: &Board, moves: Vec<Move>) -> MovePicker {
+        let moves = moves.into_iter().filter_map(|chess_move| {
+            if board.is_capture(chess_move) {
+                let (stage, score) = capture_stage(board, chess_move);
+                Some((chess_move, stage, score))
+            } else {
+                chess_move.get_promotion().map(|promotion| (chess_move, Stage::Promotions, eval::piece_value(promotion)))
+            }
+        }).collect();
+
+        MovePicker {
+            moves,
+            index: 0,
+            stage: None,
+        }
+    }
+
+    /// Get the stage of the move that was returned last
+    pub fn get_stage(&self) -> Option<Stage> {
+        self.stage
+    }
+
+    /// Get the number of moves that have not been returned yet
+    pub fn remaining(&self) -> usize {
+        self.moves.len() - self.index
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.moves.len() {
+            return None;
+        }
+
+        // selection sort one step at a time, lowest stage first and highest score within a stage
+        let best = (self.index..self.moves.len())
+            .min_by_key(|i| (self.moves[*i].1, -self.moves[*i].2))
+            .unwrap();
+        self.moves.swap(self.index, best);
+
+        let (chess_move, stage, _) = self.moves[self.index];
+        self.index += 1;
+        self.stage = Some(stage);
+
+        Some(chess_move)
+    }
+}
+
+fn capture_stage(board: &Board, chess_move: Move) -> (Stage, i32) {
+    let see = board.see(chess_move);
+    match see >= 0 {
+        true => (Stage::Captures, mvv_lva(board, chess_move)),
+        false => (Stage::BadCaptures, see),
+    }
+}
+
+/// Most valuable victim, least valuable attacker
+fn mvv_lva(board: &Board, chess_move: Move) -> i32 {
+    let victim = match board.get_piece_type_at_pos(chess_move.get_end()) {
+        PieceType::Empty => PieceType::Pawn, // en passant
+        victim => victim,
+    };
+    let attacker = board.get_piece_type_at_pos(chess_move.get_start());
+    let promotion = chess_move.get_promotion().map_or(0, eval::piece_value);
+
+    eval::piece_value(victim) * 10 - eval::piece_value(attacker) / 10 + promotion
+}
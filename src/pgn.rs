@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use crate::variant::{self, Standard, Variant};
+use crate::{Chess, Side};
+
+// longest line of movetext that is written
+const LINE_LENGTH: usize = 80;
+
+/// A game read from PGN
+///
+/// # headers: The tag pairs in the order they were written
+/// # chess: The game with every move played, starting from the FEN tag if there is one
+/// # result: The result written after the moves, * if the game is not finished
+///
+pub struct PgnGame {
+    pub headers: Vec<(String, String)>,
+    pub chess: Chess,
+    pub result: String,
+}
+
+impl PgnGame {
+    /// Get the value of a header tag
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Read all games of a PGN file
+///
+/// Comments, variations and numeric annotations are skipped
+///
+/// # Example:
+/// ```
+/// for game in pgn::parse_pgn(&std::fs::read_to_string("games.pgn")?)? {
+///     println!("{} {}", game.get_header("White").unwrap_or("?"), game.result);
+/// }
+/// ```
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, String> {
+    let mut games = vec![];
+    let mut headers = vec![];
+    let mut movetext = String::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('%') {
+            continue; // escaped line
+        }
+
+        if let Some(tag) = line.strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) {
+            // a header after movetext starts the next game
+            if !movetext.trim().is_empty() {
+                games.push(parse_game(std::mem::take(&mut headers), &movetext)?);
+                movetext.clear();
+            }
+
+            let (key, value) = tag.split_once(' ').ok_or(format!("Invalid PGN tag: {}", line))?;
+            headers.push((key.to_string(), value.trim().trim_matches('"').to_string()));
+        } else {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    if !movetext.trim().is_empty() || !headers.is_empty() {
+        games.push(parse_game(headers, &movetext)?);
+    }
+
+    Ok(games)
+}
+
+fn parse_game(headers: Vec<(String, String)>, movetext: &str) -> Result<PgnGame, String> {
+    let fen = headers.iter().find(|(key, _)| key == "FEN").map(|(_, value)| value.clone());
+    let variant: Arc<dyn Variant> = match headers.iter().find(|(key, _)| key == "Variant") {
+        Some((_, name)) => variant::from_name(name).ok_or(format!("Unknown variant: {}", name))?,
+        None => Arc::new(Standard),
+    };
+    let mut chess = match fen {
+        Some(fen) => Chess::try_from_fen_variant(fen, variant)?,
+        None => Chess::new_variant(variant)?,
+    };
+    let is_chess960 = headers.iter().any(|(key, value)| key == "Variant" && value.to_lowercase().contains("960"));
+    chess.set_chess960(is_chess960);
+    let mut result = "*".to_string();
+
+    let mut depth = 0; // of nested variations
+    let mut in_comment = false;
+    let mut in_line_comment = false;
+    let mut token = String::new();
+    let mut tokens = vec![];
+
+    for c in movetext.chars() {
+        match c {
+            '\n' if in_line_comment => in_line_comment = false,
+            _ if in_line_comment => {},
+            '}' if in_comment => in_comment = false,
+            _ if in_comment => {},
+            '{' => in_comment = true,
+            ';' => in_line_comment = true,
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth > 0 => {},
+            c if c.is_whitespace() => tokens.push(std::mem::take(&mut token)),
+            c => token.push(c),
+        }
+    }
+    tokens.push(token);
+
+    for token in tokens.iter().filter(|token| !token.is_empty()) {
+        if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token.as_str()) {
+            result = token.clone();
+            continue;
+        }
+
+        // move numbers like 12. or 12... are glued to the move sometimes
+        let token = match token.starts_with("0-0") {
+            true => token.as_str(),
+            false => token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.'),
+        };
+        if token.is_empty() || token.starts_with('$') {
+            continue;
+        }
+
+        let chess_move = chess.parse_san(token)?;
+        chess.play_move(chess_move);
+    }
+
+    Ok(PgnGame { headers, chess, result })
+}
+
+/// Write a game as PGN
+///
+/// The SetUp and FEN tags are added when the game does not start from the standard position, and
+/// the Result tag is added when it is missing
+///
+/// # Example:
+/// ```
+/// let headers = vec![("White".to_string(), "me".to_string()), ("Black".to_string(), "you".to_string())];
+/// std::fs::write("game.pgn", pgn::write_pgn(&headers, &chess, "1-0"))?;
+/// ```
+pub fn write_pgn(headers: &[(String, String)], chess: &Chess, result: &str) -> String {
+    let start_fen = chess.get_start_fen();
+    let mut pgn = String::new();
+
+    for (key, value) in headers {
+        pgn.push_str(&format!("[{} \"{}\"]\n", key, value.replace('"', "'")));
+    }
+    if !headers.iter().any(|(key, _)| key == "Result") {
+        pgn.push_str(&format!("[Result \"{}\"]\n", result));
+    }
+    if !headers.iter().any(|(key, _)| key == "Variant") {
+        if chess.is_chess960() {
+            pgn.push_str("[Variant \"Chess960\"]\n");
+        } else if chess.get_variant().get_name() != Standard.get_name() {
+            pgn.push_str(&format!("[Variant \"{}\"]\n", chess.get_variant().get_name()));
+        }
+    }
+    if start_fen != chess.get_variant().get_start_fen() && !headers.iter().any(|(key, _)| key == "FEN") {
+        pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", start_fen));
+    }
+    pgn.push('\n');
+
+    let mut replay = Chess::try_from_fen_variant(start_fen.clone(), chess.get_variant().clone()).unwrap();
+    replay.set_chess960(chess.is_chess960());
+    let mut move_number: u32 = start_fen.split_whitespace().nth(5).and_then(|number| number.parse().ok()).unwrap_or(1);
+    let mut tokens = vec![];
+
+    for (ply, chess_move) in chess.get_history().into_iter().enumerate() {
+        match replay.get_playing_side() {
+            Side::White => tokens.push(format!("{}.", move_number)),
+            Side::Black if ply == 0 => tokens.push(format!("{}...", move_number)),
+            Side::Black => {},
+        }
+        if replay.get_playing_side() == Side::Black {
+            move_number += 1;
+        }
+
+        tokens.push(replay.to_san(chess_move).unwrap_or_else(|| chess_move.to_uci_with(replay.board.get_dimensions())));
+        replay.play_move(chess_move);
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push_str("\n\n");
+
+    pgn
+}
+
+/// Read the positions of an EPD file, the operations after the position are ignored
+///
+/// # Example:
+/// ```
+/// let openings = pgn::parse_epd("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - id \"e4\";")?;
+/// ```
+pub fn parse_epd(text: &str) -> Result<Vec<Chess>, String> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<_> = line.split_whitespace().take(4).collect();
+            if fields.len() < 4 {
+                return Err(format!("Invalid EPD line: {}", line));
+            }
+            Chess::try_from_fen(format!("{} 0 1", fields.join(" ")))
+        })
+        .collect()
+}
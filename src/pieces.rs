@@ -1,5 +1,5 @@
 use core::fmt::Display;
-use crate::{bitboard, board::*, BitBoard};
+use crate::{board::*, BitBoard};
 
 pub(crate) mod pawn;
 mod knight;
@@ -7,9 +7,12 @@ mod bishop;
 mod rook;
 mod queen;
 mod king;
+mod fairy;
+pub use fairy::Betza;
+pub(crate) use fairy::get_attacks as get_fairy_attacks;
 
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 /// The types of pieces
 ///
 /// This is represents what is on a slot of the board and can therefore be Empty as well
@@ -20,9 +23,17 @@ pub enum PieceType {
     Bishop,
     Rook,
     Queen,
-    King
+    King,
+    Archbishop,
+    Chancellor,
+    Amazon,
+    Camel,
+    Grasshopper,
 }
 
+/// Fairy pieces, which move by their Betza notation
+pub(crate) const FAIRY_PIECES: [PieceType; 5] = [PieceType::Archbishop, PieceType::Chancellor, PieceType::Amazon, PieceType::Camel, PieceType::Grasshopper];
+
 impl PieceType {
     pub fn to_value(self) -> usize {
         (self as usize) - 1
@@ -36,24 +47,85 @@ impl PieceType {
             3 => PieceType::Rook,
             4 => PieceType::Queen,
             5 => PieceType::King,
+            6 => PieceType::Archbishop,
+            7 => PieceType::Chancellor,
+            8 => PieceType::Amazon,
+            9 => PieceType::Camel,
+            10 => PieceType::Grasshopper,
             _ => PieceType::Empty
         }
     }
+
+    /// Get the lowercase FEN letter of a piece type
+    pub fn to_char(self) -> char {
+        match self {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+            PieceType::Archbishop => 'a',
+            PieceType::Chancellor => 'c',
+            PieceType::Amazon => 'm',
+            PieceType::Camel => 'l',
+            PieceType::Grasshopper => 'g',
+            PieceType::Empty => '.',
+        }
+    }
+
+    /// Get the piece type of a FEN letter, regardless of case
+    pub fn from_char(c: char) -> PieceType {
+        match c.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            'a' => PieceType::Archbishop,
+            'c' => PieceType::Chancellor,
+            'm' => PieceType::Amazon,
+            'l' => PieceType::Camel,
+            'g' => PieceType::Grasshopper,
+            _ => PieceType::Empty,
+        }
+    }
+
+    /// Get the movement rules of a fairy piece type in Betza notation, None for the standard pieces
+    ///
+    /// # Example:
+    /// ```
+    /// assert!(PieceType::Archbishop.get_betza() == Some("BN"));
+    /// ```
+    pub fn get_betza(self) -> Option<&'static str> {
+        match self {
+            PieceType::Archbishop => Some("BN"),
+            PieceType::Chancellor => Some("RN"),
+            PieceType::Amazon => Some("QN"),
+            PieceType::Camel => Some("C"),
+            PieceType::Grasshopper => Some("gQ"),
+            _ => None,
+        }
+    }
 }
 
+#[derive(Copy, Clone, Debug)]
 /// All information about a piece on the board
 pub struct Piece {
     piece: PieceType,
     color: Side,
-    pos: (i8, i8)
+    pos: (i8, i8),
+    index: usize,
 }
 
 impl Piece {
-    pub(crate) fn new(piece: PieceType, color: Side, x: i8, y: i8) -> Self {
+    pub(crate) fn new(piece: PieceType, color: Side, x: i8, y: i8, index: usize) -> Self {
         Piece {
             piece,
             color,
-            pos: (x, y)
+            pos: (x, y),
+            index,
         }
     }
 
@@ -65,7 +137,7 @@ impl Piece {
             PieceType::Rook => rook::is_allowed_move(self, board, index),
             PieceType::Queen => queen::is_allowed_move(self, board, index),
             PieceType::King => king::is_allowed_move(self, board, index),
-            _ => false
+            _ => fairy::is_allowed_move(self, board, index),
         }
     }
 
@@ -77,10 +149,23 @@ impl Piece {
             PieceType::Rook => rook::get_allowed_moves(self, board),
             PieceType::Queen => queen::get_allowed_moves(self, board),
             PieceType::King => king::get_allowed_moves(self, board),
-            _ => bitboard::EMPTY,
+            _ => fairy::get_allowed_moves(self, board),
         }    
     }
 
+    /// Get the moves of the piece without looking at the safety of its own king
+    pub(crate) fn get_pseudo_legal_moves(&self, board: &Board) -> BitBoard {
+        match self.piece {
+            PieceType::Pawn => pawn::get_pseudo_legal_moves(self, board),
+            PieceType::King => king::get_pseudo_legal_moves(self, board),
+            PieceType::Empty | PieceType::Knight | PieceType::Bishop | PieceType::Rook | PieceType::Queen => {
+                let side = board.get_sides_board(self.color);
+                self.get_attacked_square(board).filter_on(|index| !side.get(index))
+            },
+            _ => fairy::get_pseudo_legal_moves(self, board),
+        }
+    }
+
     pub(crate) fn get_attacked_square(&self, board: &Board) -> BitBoard {
         match self.piece {
             PieceType::Pawn => pawn::get_attacked_squares(self, board),
@@ -89,7 +174,7 @@ impl Piece {
             PieceType::Rook => rook::get_attacked_squares(self, board),
             PieceType::Queen => queen::get_attacked_squares(self, board),
             PieceType::King => king::get_attacked_squares(self, board),
-            _ => bitboard::EMPTY,
+            _ => fairy::get_attacked_squares(self, board),
         }
     }
 
@@ -100,7 +185,7 @@ impl Piece {
 
     /// Get the slot/index/square that this piece is on
     pub fn get_occupied_slot(&self) -> usize {
-        (self.pos.1 * 8 + self.pos.0) as usize
+        self.index
     }
 
     /// Get the coordinate of this piece
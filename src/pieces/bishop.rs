@@ -1,4 +1,4 @@
-use crate::{bitboard, BitBoard, CoordinateIterator};
+use crate::{BitBoard, CoordinateIterator};
 use super::{Board, Piece};
 
 pub(crate) fn is_allowed_move(piece: &Piece, board: &Board, index: usize) -> bool {
@@ -12,18 +12,20 @@ pub(crate) fn get_allowed_moves(piece: &Piece, board: &Board) -> BitBoard {
 
 pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
     let pos = piece.get_pos_as_usize();
+    let dimensions = board.get_dimensions();
 
     let action = |bitboard: &mut BitBoard, x, y| {
-        if !board.is_empty(y * 8 + x) {
-            bitboard.set(y * 8 + x, true);
+        let index = dimensions.get_index(x, y);
+        if !board.is_empty(index) {
+            bitboard.set(index, true);
             return true;
         }
-        bitboard.set(y * 8 + x, true);
+        bitboard.set(index, true);
         false
     };
 
-    board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (-1, 1)), action) // NORTH-WEST
-        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (1, 1)), action) // NORTH-EAST
-        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (1, -1)), action) // SOUTH-EAST
-        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (-1, -1)), action) // SOUTH-WEST
+    board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (-1, 1), dimensions), action) // NORTH-WEST
+        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (1, 1), dimensions), action) // NORTH-EAST
+        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (1, -1), dimensions), action) // SOUTH-EAST
+        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (-1, -1), dimensions), action) // SOUTH-WEST
 }
@@ -0,0 +1,286 @@
+use std::sync::OnceLock;
+
+use crate::{bitboard, BitBoard, Dimensions};
+use super::{Board, Piece, PieceType, Side};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// What a movement is allowed to do on the square it ends on
+///
+/// # Any: Move to an empty square or capture
+/// # Move: Only move to an empty square
+/// # Capture: Only capture
+///
+enum Mode {
+    Any,
+    Move,
+    Capture,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// One leap or ride of a piece
+///
+/// # steps: The steps from white's point of view, with the y going up the board
+/// # range: How many times the step is repeated, 1 for a leap and 0 for a ride up to the edge
+/// # mode: What the movement is allowed to do on its last square
+/// # hop: The movement goes over the first piece on its line and ends right behind it
+///
+struct Movement {
+    steps: Vec<(isize, isize)>,
+    range: usize,
+    mode: Mode,
+    hop: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// Movement rules of a piece written in Betza notation
+///
+/// The atoms W, F, D, N, A, H, C, Z and G are leaps, K, R, B and Q are short for WF, WW, FF and
+/// WWFF. A doubled atom like NN rides, and a number after an atom limits how far it rides, where
+/// 0 means up to the edge. Lowercase modifiers in front of an atom change it, m only moves, c only
+/// captures, g hops over the first piece in the way like a grasshopper and f, b, l, r, v and s keep
+/// the steps going forward, backward, left, right, vertical or sideways. A forward or backward
+/// modifier next to a left or right one narrows it down, so flF is only the forward left step,
+/// while fbF or vN keep the steps of either direction.
+///
+/// # Example:
+/// ```
+/// // a piece that moves like a knight and captures like a rook
+/// let betza = Betza::parse("mNcR").unwrap();
+/// let d4 = square_from_str("d4").unwrap();
+/// assert!(betza.get_moves(Dimensions::STANDARD, d4, Side::White, 0, 0).count_ones() == 8);
+/// ```
+pub struct Betza {
+    movements: Vec<Movement>,
+}
+
+impl Betza {
+    /// Parse movement rules written in Betza notation
+    pub fn parse(notation: &str) -> Result<Betza, String> {
+        let chars: Vec<_> = notation.chars().collect();
+        let mut movements = vec![];
+        let mut i = 0;
+
+        while i < chars.len() {
+            let (mut mode, mut hop, mut directions) = (Mode::Any, false, Vec::<Vec<char>>::new());
+            while i < chars.len() && chars[i].is_ascii_lowercase() {
+                match chars[i] {
+                    'm' => mode = Mode::Move,
+                    'c' => mode = Mode::Capture,
+                    'g' => hop = true,
+                    'f' | 'b' | 'l' | 'r' | 'v' | 's' => add_direction(&mut directions, chars[i]),
+                    c => return Err(format!("Invalid Betza modifier: {}", c)),
+                }
+                i += 1;
+            }
+
+            let atom = *chars.get(i).ok_or(format!("Missing Betza atom at the end of: {}", notation))?;
+            let (leaps, mut range) = match atom {
+                'K' => (vec![(1, 0), (1, 1)], 1),
+                'R' => (vec![(1, 0)], 0),
+                'B' => (vec![(1, 1)], 0),
+                'Q' => (vec![(1, 0), (1, 1)], 0),
+                _ => (vec![get_leap(atom).ok_or(format!("Invalid Betza atom: {}", atom))?], 1),
+            };
+            i += 1;
+
+            // a doubled atom or a number makes it ride
+            if chars.get(i) == Some(&atom) {
+                range = 0;
+                i += 1;
+            }
+            let digits: String = chars[i..].iter().take_while(|c| c.is_ascii_digit()).collect();
+            if !digits.is_empty() {
+                range = digits.parse().map_err(|_| format!("Invalid Betza range: {}", digits))?;
+                i += digits.len();
+            }
+
+            for leap in leaps {
+                let steps = get_steps(leap).into_iter()
+                    .filter(|step| directions.is_empty() || directions.iter().any(|group| group.iter().all(|direction| is_towards(*step, *direction))))
+                    .collect();
+                movements.push(Movement { steps, range, mode, hop });
+            }
+        }
+
+        if movements.is_empty() {
+            return Err("Empty Betza notation".to_string());
+        }
+
+        Ok(Betza { movements })
+    }
+
+    /// Get the squares a piece of side on index is able to move to on a board of dimensions, with
+    /// a1 as the lowest bit
+    ///
+    /// own and opponent are the squares of the pieces of side and of its opponent.
+    pub fn get_moves(&self, dimensions: Dimensions, index: usize, side: Side, own: u128, opponent: u128) -> u128 {
+        let mut moves = 0;
+        self.walk(dimensions, index, side, own | opponent, |target, mode| {
+            let target_bit = 1 << target;
+            let allowed = matches!((opponent & target_bit != 0, mode), (_, Mode::Any) | (true, Mode::Capture) | (false, Mode::Move));
+            if allowed && own & target_bit == 0 {
+                moves |= target_bit;
+            }
+        });
+
+        moves
+    }
+
+    /// Get the squares a piece of side on index attacks, with occupied as the pieces in the way
+    pub fn get_attacks(&self, dimensions: Dimensions, index: usize, side: Side, occupied: u128) -> u128 {
+        let mut attacks = 0;
+        self.walk(dimensions, index, side, occupied, |target, mode| {
+            if mode != Mode::Move {
+                attacks |= 1 << target;
+            }
+        });
+
+        attacks
+    }
+
+    /// Call visit with every square a movement ends on and the mode of the movement
+    fn walk(&self, dimensions: Dimensions, index: usize, side: Side, occupied: u128, mut visit: impl FnMut(usize, Mode)) {
+        let square = |n: isize, (dx, dy): (isize, isize)| dimensions.get_offset(index, n * dx, n * dy);
+        let longest = dimensions.get_width().max(dimensions.get_height()) as isize;
+
+        for movement in &self.movements {
+            let range = if movement.range == 0 { longest } else { movement.range as isize };
+            for step in &movement.steps {
+                // black pieces look down the board
+                let step = match side {
+                    Side::White => *step,
+                    Side::Black => (-step.0, -step.1),
+                };
+
+                let mut n = 1;
+                while let Some(target) = square(n, step) {
+                    let is_occupied = occupied & (1 << target) != 0;
+                    if movement.hop {
+                        if is_occupied {
+                            if let Some(landing) = square(n + 1, step) {
+                                visit(landing, movement.mode);
+                            }
+                            break;
+                        }
+                    } else {
+                        visit(target, movement.mode);
+                        if is_occupied {
+                            break;
+                        }
+                    }
+
+                    if n >= range {
+                        break;
+                    }
+                    n += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Get the leap of a Betza atom as the larger and smaller distance
+fn get_leap(atom: char) -> Option<(isize, isize)> {
+    match atom {
+        'W' => Some((1, 0)),
+        'F' => Some((1, 1)),
+        'D' => Some((2, 0)),
+        'N' => Some((2, 1)),
+        'A' => Some((2, 2)),
+        'H' => Some((3, 0)),
+        'C' => Some((3, 1)),
+        'Z' => Some((3, 2)),
+        'G' => Some((3, 3)),
+        _ => None,
+    }
+}
+
+/// Get the up to eight different steps of a leap in every direction
+fn get_steps((a, b): (isize, isize)) -> Vec<(isize, isize)> {
+    let mut steps = vec![];
+    for (dx, dy) in [(a, b), (b, a)] {
+        for (sx, sy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            if !steps.contains(&(dx * sx, dy * sy)) {
+                steps.push((dx * sx, dy * sy));
+            }
+        }
+    }
+
+    steps
+}
+
+/// Add a direction modifier to the groups of directions, a step has to go towards every direction
+/// of one of the groups
+///
+/// f or b and l or r that follow each other make one group, every other modifier starts a new one.
+fn add_direction(groups: &mut Vec<Vec<char>>, direction: char) {
+    let is_vertical = |c: &char| matches!(c, 'f' | 'b');
+    let is_horizontal = |c: &char| matches!(c, 'l' | 'r');
+
+    if let Some(group) = groups.last_mut() {
+        let joins = match direction {
+            'f' | 'b' => group.iter().all(is_horizontal) && !group.iter().any(is_vertical),
+            'l' | 'r' => group.iter().all(is_vertical) && !group.iter().any(is_horizontal),
+            _ => false,
+        };
+        if joins {
+            group.push(direction);
+            return;
+        }
+    }
+
+    groups.push(vec![direction]);
+}
+
+fn is_towards((dx, dy): (isize, isize), direction: char) -> bool {
+    match direction {
+        'f' => dy > 0,
+        'b' => dy < 0,
+        'l' => dx < 0,
+        'r' => dx > 0,
+        'v' => dy.abs() > dx.abs(),
+        's' => dx.abs() > dy.abs(),
+        _ => false,
+    }
+}
+
+/// Get the parsed movement rules of a fairy piece type
+fn get_betza(piece: PieceType) -> Option<&'static Betza> {
+    static PARSED: OnceLock<Vec<(PieceType, Betza)>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        super::FAIRY_PIECES.iter()
+            .map(|piece| (*piece, Betza::parse(piece.get_betza().unwrap()).unwrap()))
+            .collect()
+    }).iter().find(|(fairy, _)| *fairy == piece).map(|(_, betza)| betza)
+}
+
+pub(crate) fn is_allowed_move(piece: &Piece, board: &Board, index: usize) -> bool {
+    get_allowed_moves(piece, board).get(index)
+}
+
+pub(crate) fn get_allowed_moves(piece: &Piece, board: &Board) -> BitBoard {
+    board.filter_king_safety(get_pseudo_legal_moves(piece, board), piece)
+}
+
+pub(crate) fn get_pseudo_legal_moves(piece: &Piece, board: &Board) -> BitBoard {
+    let Some(betza) = get_betza(piece.get_piece_type()) else {
+        return bitboard::EMPTY;
+    };
+
+    let side = piece.get_color();
+    let own = board.get_sides_board(side).to_number();
+    let opponent = board.get_opponent_board(side).to_number();
+    BitBoard::from_number(betza.get_moves(board.get_dimensions(), piece.get_occupied_slot(), side, own, opponent))
+}
+
+pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
+    get_attacks(piece.get_piece_type(), board.get_dimensions(), piece.get_occupied_slot(), piece.get_color(), board.all_pieces_bitboard())
+}
+
+/// Get the squares a fairy piece attacks from index, with occupied as the pieces in the way
+pub(crate) fn get_attacks(piece: PieceType, dimensions: Dimensions, index: usize, side: Side, occupied: BitBoard) -> BitBoard {
+    match get_betza(piece) {
+        Some(betza) => BitBoard::from_number(betza.get_attacks(dimensions, index, side, occupied.to_number())),
+        None => bitboard::EMPTY,
+    }
+}
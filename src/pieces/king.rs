@@ -1,33 +1,90 @@
-use crate::{bitboard, BitBoard, CoordinateIterator,};
-use super::{Board, CastlingAbility, Piece};
+use crate::{bitboard, BitBoard};
+use super::{Board, CastlingSide, Piece, PieceType};
 
 pub(crate) fn is_allowed_move(piece: &Piece, board: &Board, index: usize) -> bool {
     get_allowed_moves(piece, board).get(index)
 }
 
 pub(crate) fn get_allowed_moves(piece: &Piece, board: &Board) -> BitBoard {
-    let castling = board.get_castling(piece.get_color());
-    let attacked = board.get_side_computed_boards(piece.get_color()).2;
-    let combined_board = board.get_combined_piece_board();
-    let (x, y) = piece.get_pos_as_usize();
-    
-    // x != 4 since that is the king square and there is obviously a piece there
-    let is_not_empty_or_attacked = |(x, y)| attacked.get(y * 8 + x) || (x != 4 && combined_board.get(y * 8 + x));
+    let side = board.get_sides_board(piece.get_color());
+    board.filter_king_safety(get_attacked_squares(piece, board).filter_on(|index| !side.get(index)), piece)
+        | get_castling_moves(piece, board)
+}
 
+/// Get the steps and castling moves of the king without looking at the squares it steps on
+pub(crate) fn get_pseudo_legal_moves(piece: &Piece, board: &Board) -> BitBoard {
     let side = board.get_sides_board(piece.get_color());
-    let mut bitboard = get_attacked_squares(piece, board).filter_on(|index| !side.get(index));
+    get_attacked_squares(piece, board).filter_on(|index| !side.get(index)) | get_castling_moves(piece, board)
+}
 
-    // has king side castlingability that those slots are open and not attacked
-    if castling.has(CastlingAbility::King) && !CoordinateIterator::from_inclusive_to(piece.get_pos_as_usize(), (6, y)).any(is_not_empty_or_attacked) {
-        bitboard.set(y * 8 + 6, true);
+fn get_castling_moves(piece: &Piece, board: &Board) -> BitBoard {
+    let mut bitboard = bitboard::EMPTY;
+    for castling_side in [CastlingSide::King, CastlingSide::Queen] {
+        if let Some(index) = get_castling_move(piece, board, castling_side) {
+            bitboard.set(index, true);
+        }
     }
 
-    // has queen side castlingability that those slots are open and not attacked
-    if castling.has(CastlingAbility::Queen) && !CoordinateIterator::from_inclusive_to(piece.get_pos_as_usize(), (2, y)).any(is_not_empty_or_attacked) {
-        bitboard.set(y * 8 + 2, true);
+    bitboard
+}
+
+/// Get the end index of castling towards castling_side if it is legal
+///
+/// The king always ends on the g- or c-file and the rook next to it, also in Chess960, on wider
+/// boards the king ends on the file next to the corner and the rook next to it again. Every
+/// square the king and rook pass or end on has to be empty apart from the two of them, and the
+/// king is not allowed to castle out of, through or into check.
+fn get_castling_move(piece: &Piece, board: &Board, castling_side: CastlingSide) -> Option<usize> {
+    let color = piece.get_color();
+    let opponent = color.get_opposite();
+    let rook_file = board.get_castling(color).get_rook_file(castling_side)?;
+    let (king_file, y) = piece.get_pos_as_usize();
+    let (king, rook) = (piece.get_occupied_slot(), board.get_index(rook_file, y));
+    if !board.get_piece_board(PieceType::Rook, color).get(rook) {
+        return None;
     }
 
-    board.filter_king_safety(bitboard, piece)
+    let (king_to, rook_to) = board.get_castling_files(castling_side);
+
+    let mut occupied = board.all_pieces_bitboard();
+    occupied.set(king, false);
+    occupied.set(rook, false);
+
+    let files = [king_file, king_to, rook_file, rook_to];
+    let (first, last) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+    if (first..=last).any(|file| occupied.get(board.get_index(file, y))) {
+        return None;
+    }
+
+    // squares next to the enemy king are safe in variants where touching kings are never in check
+    let enemy_king = board.get_piece_board(PieceType::King, opponent);
+    let touching_safe = board.get_variant().is_touching_kings_safe();
+    let is_attacked = |index: usize, occupied: BitBoard| {
+        let ((x, y), dimensions) = (board.get_coordinates(index), board.get_dimensions());
+        let touching = enemy_king.into_iter().map(|king| dimensions.get_coordinates(king)).any(|(kx, ky)| kx.abs_diff(x) <= 1 && ky.abs_diff(y) <= 1);
+        !(touching_safe && touching) && board.is_attacked_by(index, opponent, occupied, board.get_sides_board(opponent))
+    };
+
+    // the king does not block attacks on the squares behind it, but the rook still does, the
+    // square the king ends on is checked below with the rook on its new square
+    let mut without_king = board.all_pieces_bitboard();
+    without_king.set(king, false);
+    let path = (king_file.min(king_to)..=king_file.max(king_to)).filter(|file| *file != king_to || *file == king_file);
+    if path.map(|file| board.get_index(file, y)).any(|index| is_attacked(index, without_king)) {
+        return None;
+    }
+
+    // the rook on its new square might block an attack on the king, or have been blocking one
+    occupied.set(board.get_index(king_to, y), true);
+    occupied.set(board.get_index(rook_to, y), true);
+    if is_attacked(board.get_index(king_to, y), occupied) {
+        return None;
+    }
+
+    match board.is_chess960() {
+        true => Some(rook),
+        false => Some(board.get_index(king_to, y)),
+    }
 }
 
 pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
@@ -65,7 +122,7 @@ pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
 
 
     board.check_and_set_piece_iter(list.iter().map(|(x, y)| (*x, *y)), |bitboard, x, y| {
-        bitboard.set(y * 8 + x, true);
+        bitboard.set(board.get_index(x, y), true);
         false
     })
 }
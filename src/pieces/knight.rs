@@ -1,4 +1,4 @@
-use crate::{board, BitBoard};
+use crate::BitBoard;
 use super::{Board, Piece};
 
 pub(crate) fn is_allowed_move(piece: &Piece, board: &Board, index: usize) -> bool {
@@ -51,7 +51,7 @@ pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
     }
 
     board.check_and_set_piece_iter(list.iter().map(|(x, y)| (*x, *y)), |bitboard, x, y| {
-        bitboard.set(y * 8 + x, true);
+        bitboard.set(board.get_index(x, y), true);
         false
     })
 }
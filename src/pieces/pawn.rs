@@ -6,15 +6,26 @@ pub(crate) fn is_allowed_move(piece: &Piece, board: &Board, index: usize) -> boo
 }
 
 pub(crate) fn get_allowed_moves(piece: &Piece, board: &Board) -> BitBoard {
+    board.filter_king_safety(get_pseudo_legal_moves(piece, board), piece)
+}
+
+/// Get the pushes and captures of the pawn, en passant included, without looking at king safety
+pub(crate) fn get_pseudo_legal_moves(piece: &Piece, board: &Board) -> BitBoard {
     let opponent = board.get_opponent_board(piece.get_color());
 
     let attacked_bitboard = if let Some(ep_index) = board.get_ep_target() {
-        get_attacked_squares(piece, board).filter_on(|index| index.abs_diff(ep_index as usize) == 8 || opponent.get(index))
+        // the en passant square is the one behind the pawn that just moved
+        let width = board.get_dimensions().get_width();
+        let ep_square = match piece.color {
+            Side::White => ep_index as usize + width,
+            Side::Black => ep_index as usize - width,
+        };
+        get_attacked_squares(piece, board).filter_on(|index| index == ep_square || opponent.get(index))
     } else {
         get_attacked_squares(piece, board).filter_on(|index| opponent.get(index))
     };
 
-    board.filter_king_safety(get_move_bitboard(piece, board) | attacked_bitboard, piece)
+    get_move_bitboard(piece, board) | attacked_bitboard
 }
 
 pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
@@ -24,8 +35,8 @@ pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
         Side::Black => y as isize - 1
     } as usize;
 
-    // bounds check: not within 0..7
-    if !Board::is_inbounds(x, attack_level) {
+    // bounds check: not on the board
+    if !board.is_inbounds(x, attack_level) {
         return bitboard::EMPTY;
     }
 
@@ -33,22 +44,24 @@ pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
     if x > 0 {
         list.push((x - 1, attack_level));
     }
-    if x < 7 {
+    if x + 1 < board.get_dimensions().get_width() {
         list.push((x + 1, attack_level));
     }
     let it = list.iter().map(|(x, y)| (*x, *y));
 
     board.check_and_set_piece_iter(it, |bitboard, x, y| {
-        bitboard.set(y * 8 + x, true);
+        bitboard.set(board.get_index(x, y), true);
         false
     })
 }
 
 fn get_move_bitboard(piece: &Piece, board: &Board) -> BitBoard {
     let (x, y) = piece.get_pos_as_usize();
+    // pawns behind their start rank only exist in Horde, where they may move two squares as well
+    let (pawn_rank, height) = (board.get_variant().get_pawn_rank(), board.get_dimensions().get_height());
     let end = match piece.color {
-        Side::White => (x, if y == 1 {3} else {y + 1}),
-        Side::Black => (x, if y == 6 {4} else {y - 1}),
+        Side::White => (x, if y <= pawn_rank {y + 2} else {y + 1}),
+        Side::Black => (x, if y + pawn_rank + 1 >= height {y - 2} else {y - 1}),
     };
 
     board.check_and_set_piece_iter(CoordinateIterator::from_to(piece.get_pos_as_usize(), end), 
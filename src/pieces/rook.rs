@@ -12,18 +12,20 @@ pub(crate) fn get_allowed_moves(piece: &Piece, board: &Board) -> BitBoard {
 
 pub(crate) fn get_attacked_squares(piece: &Piece, board: &Board) -> BitBoard {
     let pos = piece.get_pos_as_usize();
+    let dimensions = board.get_dimensions();
 
     let action = |bitboard: &mut BitBoard, x, y| {
-        if !board.is_empty(y * 8 + x) {
-            bitboard.set(y * 8 + x, true);
+        let index = dimensions.get_index(x, y);
+        if !board.is_empty(index) {
+            bitboard.set(index, true);
             return true;
         }
-        bitboard.set(y * 8 + x, true);
+        bitboard.set(index, true);
         false
     };
 
-    board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (0, 1)), action) // NORTH
-        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (1, 0)), action) // EAST
-        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (0, -1)), action) // SOUTH
-        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (-1, 0)), action) // WEST
+    board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (0, 1), dimensions), action) // NORTH
+        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (1, 0), dimensions), action) // EAST
+        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (0, -1), dimensions), action) // SOUTH
+        | board.check_and_set_piece_iter(CoordinateIterator::from_delta(pos, (-1, 0), dimensions), action) // WEST
 }
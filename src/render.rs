@@ -0,0 +1,217 @@
+use std::fmt::Display;
+
+use crate::{Board, Chess, Move, PieceType, Side};
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_LAST_MOVE: &str = "\x1b[43m";
+const ANSI_CHECK: &str = "\x1b[41m";
+const ANSI_DESTINATION: &str = "\x1b[42m";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+/// How pieces are drawn
+///
+/// # Ascii: FEN letters, uppercase for white and lowercase for black
+/// # Unicode: Chess figurines, the fairy pieces keep their FEN letters
+///
+pub enum PieceStyle {
+    #[default]
+    Ascii,
+    Unicode,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Options for drawing a board as text
+///
+/// The highlights are drawn as ANSI background colours, without any highlight the text is plain.
+///
+/// # style: How pieces are drawn
+/// # coordinates: Write the ranks on the left and the files below the board
+/// # perspective: The side at the bottom of the board
+/// # last_move: The move whose squares are highlighted
+/// # check: Highlight the king of the side to move if it is in check
+/// # destinations: The square of a piece whose legal destinations are highlighted
+///
+pub struct RenderOptions {
+    pub style: PieceStyle,
+    pub coordinates: bool,
+    pub perspective: Side,
+    pub last_move: Option<Move>,
+    pub check: bool,
+    pub destinations: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            style: PieceStyle::Ascii,
+            coordinates: true,
+            perspective: Side::White,
+            last_move: None,
+            check: false,
+            destinations: None,
+        }
+    }
+}
+
+impl Board {
+    /// Draw the board as text, one line per rank
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// let options = RenderOptions { style: PieceStyle::Unicode, perspective: Side::Black, ..Default::default() };
+    /// println!("{}", chess.board.render(&options));
+    /// ```
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let dimensions = self.get_dimensions();
+        let (width, height) = (dimensions.get_width(), dimensions.get_height());
+        let label_width = height.to_string().len();
+        let highlights = self.get_highlights(options);
+
+        let files: Vec<_> = match options.perspective {
+            Side::White => (0..width).collect(),
+            Side::Black => (0..width).rev().collect(),
+        };
+        let ranks: Vec<_> = match options.perspective {
+            Side::White => (0..height).rev().collect(),
+            Side::Black => (0..height).collect(),
+        };
+
+        let mut text = String::new();
+        for y in ranks {
+            if options.coordinates {
+                text.push_str(&format!("{:>1$} ", y + 1, label_width));
+            }
+
+            for (i, x) in files.iter().enumerate() {
+                let index = dimensions.get_index(*x, y);
+                if i > 0 {
+                    text.push(' ');
+                }
+
+                let symbol = self.get_symbol(index, options.style);
+                match highlights.iter().find(|(square, _)| *square == index) {
+                    Some((_, colour)) => text.push_str(&format!("{}{}{}", colour, symbol, ANSI_RESET)),
+                    None => text.push(symbol),
+                }
+            }
+            text.push('\n');
+        }
+
+        if options.coordinates {
+            text.push_str(&" ".repeat(label_width + 1));
+            let names: Vec<_> = files.iter().map(|x| ((*x as u8 + b'a') as char).to_string()).collect();
+            text.push_str(&names.join(" "));
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Draw the board on one line for logs, the ranks from the top are split by / and empty
+    /// squares are dots, followed by the side to move
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// assert!(chess.board.render_compact() == "rnbqkbnr/pppppppp/......../......../......../......../PPPPPPPP/RNBQKBNR w");
+    /// ```
+    pub fn render_compact(&self) -> String {
+        let dimensions = self.get_dimensions();
+        let ranks: Vec<String> = (0..dimensions.get_height()).rev()
+            .map(|y| (0..dimensions.get_width()).map(|x| self.get_symbol(dimensions.get_index(x, y), PieceStyle::Ascii)).collect())
+            .collect();
+
+        let side = match self.get_playing_side() {
+            Side::White => 'w',
+            Side::Black => 'b',
+        };
+        format!("{} {}", ranks.join("/"), side)
+    }
+
+    fn get_symbol(&self, index: usize, style: PieceStyle) -> char {
+        let Some(piece) = self.get_piece_at_pos(index) else {
+            return match style {
+                PieceStyle::Ascii => '.',
+                PieceStyle::Unicode => '·',
+            };
+        };
+
+        let (piece_type, side) = (piece.get_piece_type(), piece.get_color());
+        match (style, get_figurine(piece_type, side)) {
+            (PieceStyle::Unicode, Some(figurine)) => figurine,
+            _ if side == Side::White => piece_type.to_char().to_ascii_uppercase(),
+            _ => piece_type.to_char(),
+        }
+    }
+
+    /// Get the highlighted squares with their colour, later highlights of a square win
+    fn get_highlights(&self, options: &RenderOptions) -> Vec<(usize, &'static str)> {
+        let mut highlights = vec![];
+
+        if let Some(index) = options.destinations {
+            for chess_move in self.get_legal_moves().iter().filter(|chess_move| chess_move.get_start() == index && chess_move.get_drop().is_none()) {
+                highlights.push((chess_move.get_end(), ANSI_DESTINATION));
+            }
+        }
+        if let Some(chess_move) = options.last_move {
+            if chess_move.get_drop().is_none() {
+                highlights.push((chess_move.get_start(), ANSI_LAST_MOVE));
+            }
+            highlights.push((chess_move.get_end(), ANSI_LAST_MOVE));
+        }
+        if options.check && self.is_in_check() {
+            for king in self.get_piece_board(PieceType::King, self.get_playing_side()) {
+                highlights.push((king, ANSI_CHECK));
+            }
+        }
+
+        highlights.reverse();
+        highlights
+    }
+}
+
+fn get_figurine(piece: PieceType, side: Side) -> Option<char> {
+    let figurines = match side {
+        Side::White => ['♙', '♘', '♗', '♖', '♕', '♔'],
+        Side::Black => ['♟', '♞', '♝', '♜', '♛', '♚'],
+    };
+
+    match piece {
+        PieceType::Pawn | PieceType::Knight | PieceType::Bishop | PieceType::Rook | PieceType::Queen | PieceType::King => {
+            Some(figurines[piece.to_value()])
+        },
+        _ => None,
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&RenderOptions::default()))
+    }
+}
+
+impl Chess {
+    /// Draw the board as text, see Board::render
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// let last_move = chess.get_history().last().copied();
+    /// println!("{}", chess.render(&RenderOptions { last_move, check: true, ..Default::default() }));
+    /// ```
+    pub fn render(&self, options: &RenderOptions) -> String {
+        self.board.render(options)
+    }
+
+    /// Draw the board on one line, see Board::render_compact
+    pub fn render_compact(&self) -> String {
+        self.board.render_compact()
+    }
+}
+
+impl Display for Chess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
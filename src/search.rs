@@ -0,0 +1,440 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::moveorder::{History, Killers, MovePicker, Stage};
+use crate::transposition::{Bound, TranspositionTable};
+use crate::{eval, Board, Move};
+
+/// Score of a checkmate at the root, mates further away score lower by one per ply
+pub const MATE: i32 = 30000;
+/// Bound that is larger than any score the search is able to return
+pub const INFINITY: i32 = 32000;
+/// The deepest the search will go, quiescence search and check extensions included
+pub const MAX_PLY: usize = 128;
+
+// how often the clock is looked at, in nodes
+const TIME_CHECK_INTERVAL: u64 = 64;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Limits for when the search should stop
+///
+/// A search without any limits runs until the stop flag is set or MAX_PLY is reached
+///
+/// # depth: Maximum depth in plies for iterative deepening
+/// # nodes: Maximum number of nodes to search
+/// # movetime: Maximum time to search
+///
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The outcome of a finished iteration of the search
+///
+/// # best_move: The move to play, None if the side to move has no legal moves
+/// # score: Score in centipawns from the side to move's view, see `mate_in` for mate scores
+/// # depth: The depth of the last completed iteration
+/// # pv: The principal variation, starting with best_move
+/// # nodes: Number of nodes searched in total
+/// # time: Time spent searching
+/// # multipv: Rank of this line when searching for multiple lines, 1 is the best line
+///
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u32,
+    pub pv: Vec<Move>,
+    pub nodes: u64,
+    pub time: Duration,
+    pub multipv: usize,
+}
+
+/// Negamax alpha-beta search with iterative deepening
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new();
+/// let limits = SearchLimits { depth: Some(4), ..Default::default() };
+/// let mut search = Search::new(limits, Arc::new(AtomicBool::new(false)));
+/// let result = search.run(&chess.board);
+/// println!("Best move: {}", result.best_move.unwrap());
+/// ```
+pub struct Search {
+    limits: SearchLimits,
+    stop: Arc<AtomicBool>,
+    table: Arc<TranspositionTable>,
+    killers: Killers,
+    history: History,
+    multipv: usize,
+    excluded: Vec<Move>, // root moves already reported by an earlier multipv line
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+}
+
+impl Search {
+    /// Create a new search that stops at the limits or when the stop flag gets set
+    pub fn new(limits: SearchLimits, stop: Arc<AtomicBool>) -> Search {
+        Search::with_table(limits, stop, Arc::new(TranspositionTable::default()))
+    }
+
+    /// Create a new search that shares a transposition table, so that results are kept between
+    /// searches
+    ///
+    /// # Example:
+    /// ```
+    /// let table = Arc::new(TranspositionTable::new(64));
+    /// let mut search = Search::with_table(limits, stop, table.clone());
+    /// search.run(&chess.board);
+    /// println!("hashfull {}", table.hashfull());
+    /// ```
+    pub fn with_table(limits: SearchLimits, stop: Arc<AtomicBool>, table: Arc<TranspositionTable>) -> Search {
+        Search {
+            limits,
+            stop,
+            table,
+            killers: Killers::new(),
+            history: History::new(),
+            multipv: 1,
+            excluded: vec![],
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+        }
+    }
+
+    /// Search the board and return the result of the deepest completed iteration
+    pub fn run(&mut self, board: &Board) -> SearchResult {
+        self.run_with_info(board, |_| {})
+    }
+
+    /// Search the board and call info with the result after every completed iteration
+    ///
+    /// # Example:
+    /// ```
+    /// let result = search.run_with_info(&chess.board, |info| {
+    ///     println!("depth {} score {} nodes {}", info.depth, info.score, info.nodes);
+    /// });
+    /// ```
+    pub fn run_with_info<F>(&mut self, board: &Board, mut info: F) -> SearchResult
+        where F: FnMut(&SearchResult)
+    {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+        self.table.new_search();
+        self.killers.clear();
+        self.history.age();
+
+        let mut result = SearchResult { multipv: 1, ..Default::default() };
+        let root_moves = board.get_legal_moves();
+        if root_moves.is_empty() {
+            result.score = if board.is_in_check() { -MATE } else { 0 };
+            return result;
+        }
+        // always have a move to play, even if the first iteration gets stopped
+        result.best_move = Some(root_moves[0]);
+        result.pv = vec![root_moves[0]];
+
+        let line_count = self.multipv.clamp(1, root_moves.len());
+        let mut lines = vec![result; line_count];
+        let max_depth = self.limits.depth.unwrap_or(MAX_PLY as u32).clamp(1, MAX_PLY as u32 / 2);
+
+        'deepening: for depth in 1..=max_depth {
+            self.excluded.clear();
+
+            for (line, line_result) in lines.iter_mut().enumerate() {
+                let mut pv = vec![];
+                let prev_pv = line_result.pv.clone();
+                let score = self.negamax(board, depth as i32, 0, -INFINITY, INFINITY, &prev_pv, &mut pv);
+
+                if self.stopped || pv.is_empty() {
+                    break 'deepening;
+                }
+
+                // the next line searches every move except the best moves of the lines before it
+                self.excluded.push(pv[0]);
+                *line_result = SearchResult {
+                    best_move: Some(pv[0]),
+                    score,
+                    depth,
+                    pv,
+                    nodes: self.nodes,
+                    time: self.start.elapsed(),
+                    multipv: line + 1,
+                };
+                info(line_result);
+            }
+
+            // lines with better scores are shown first
+            lines.sort_by_key(|line| -line.score);
+            for (index, line) in lines.iter_mut().enumerate() {
+                line.multipv = index + 1;
+            }
+
+            // no need to look further once a forced mate has been found
+            if line_count == 1 && mate_in(lines[0].score).is_some_and(|moves| moves > 0 && (moves * 2 - 1) as u32 <= depth) {
+                break;
+            }
+        }
+
+        self.excluded.clear();
+        let mut result = lines.swap_remove(0);
+        result.nodes = self.nodes;
+        result.time = self.start.elapsed();
+        result
+    }
+
+    /// Change the limits of the next runs, the table and move ordering are kept
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    /// Search for the best count moves at the root instead of only the best one, every line is
+    /// given to the info callback with its multipv number
+    pub fn set_multipv(&mut self, count: usize) {
+        self.multipv = count.max(1);
+    }
+
+    /// Number of nodes searched so far
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        if self.stop.load(Ordering::Relaxed)
+            || self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+            || (self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) && self.limits.movetime.is_some_and(|time| self.start.elapsed() >= time))
+        {
+            self.stopped = true;
+        }
+
+        self.stopped
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(&mut self, board: &Board, mut depth: i32, ply: usize, mut alpha: i32, beta: i32, prev_pv: &[Move], pv: &mut Vec<Move>) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+
+        if ply > 0 {
+            if let Some(score) = variant_score(board, ply) {
+                return score;
+            }
+            if board.get_moves_to_50() >= 100 || board.get_variant().is_insufficient_material(board) {
+                return 0;
+            }
+        }
+
+        let in_check = board.is_in_check();
+        // look one ply further when in check so that mates are not hidden behind the horizon
+        if in_check && ply < MAX_PLY / 2 {
+            depth += 1;
+        }
+
+        if depth <= 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        self.nodes += 1;
+
+        let key = board.get_zobrist_key();
+        let entry = self.table.probe(key);
+        if let Some(entry) = entry {
+            if ply > 0 && entry.get_depth() >= depth {
+                let score = entry.get_score(ply);
+                match entry.get_bound() {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        let moves = board.get_legal_moves();
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        // the move from the previous principal variation goes before the one from the table
+        let hash_move = prev_pv.first().copied().or(entry.and_then(|entry| entry.get_best_move()));
+        let picker = MovePicker::new(board, moves, hash_move, self.killers.get(ply), &self.history);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = vec![];
+        for chess_move in picker {
+            if ply == 0 && self.excluded.contains(&chess_move) {
+                continue;
+            }
+
+            let mut child = board.clone();
+            child.make_move(chess_move);
+
+            let next_pv = match prev_pv.first() {
+                Some(pv_move) if *pv_move == chess_move => &prev_pv[1..],
+                _ => &[],
+            };
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, next_pv, &mut child_pv);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(chess_move);
+            }
+
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(chess_move);
+                pv.extend_from_slice(&child_pv);
+            }
+
+            if alpha >= beta {
+                if !board.is_capture(chess_move) && chess_move.get_promotion().is_none() {
+                    self.killers.store(ply, chess_move);
+                    self.history.add(chess_move, depth);
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        // a fail low does not know which move is best
+        let best_move = if bound == Bound::Upper { None } else { best_move };
+        // a root search without some of the moves does not have the real score of the position
+        if ply > 0 || self.excluded.is_empty() {
+            self.table.store(key, depth, bound, best_score, best_move, ply);
+        }
+
+        best_score
+    }
+
+    fn quiescence(&mut self, board: &Board, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+
+        self.nodes += 1;
+
+        if let Some(score) = variant_score(board, ply) {
+            return score;
+        }
+
+        // in check there is no standing pat, every evasion is searched and having none is mate
+        let in_check = ply < MAX_PLY - 1 && board.is_in_check();
+        if !in_check {
+            let stand_pat = eval::evaluate(board);
+            if stand_pat >= beta || ply >= MAX_PLY - 1 {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+        }
+
+        let moves = board.get_legal_moves();
+        if in_check && moves.is_empty() {
+            return -MATE + ply as i32;
+        }
+
+        let mut picker = match in_check {
+            true => MovePicker::new(board, moves, None, [None, None], &self.history),
+            false => MovePicker::new_captures(board, moves),
+        };
+        while let Some(chess_move) = picker.next() {
+            // captures that lose material are not going to raise alpha
+            if !in_check && picker.get_stage() == Some(Stage::BadCaptures) {
+                break;
+            }
+
+            let mut child = board.clone();
+            child.make_move(chess_move);
+            let score = -self.quiescence(&child, ply + 1, -beta, -alpha);
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+}
+
+/// Score of a position where the variant ended the game, from the view of the side to move
+fn variant_score(board: &Board, ply: usize) -> Option<i32> {
+    let outcome = board.get_variant().get_outcome(board)?;
+
+    Some(match outcome.result.get_winner() {
+        Some(side) if side == board.get_playing_side() => MATE - ply as i32,
+        Some(_) => -MATE + ply as i32,
+        None => 0,
+    })
+}
+
+/// Decide how long to search for a move given the time left on the clock
+///
+/// moves_to_go is the number of moves until the next time control, without it the remaining
+/// time is spread over an estimated number of moves. Some time is always kept in reserve.
+///
+/// # Example:
+/// ```
+/// let movetime = allocate_time(Duration::from_secs(60), Duration::from_secs(1), None);
+/// let limits = SearchLimits { movetime: Some(movetime), ..Default::default() };
+/// ```
+pub fn allocate_time(time_left: Duration, increment: Duration, moves_to_go: Option<u32>) -> Duration {
+    let reserve = (time_left / 20).min(Duration::from_millis(1000));
+    let usable = time_left.saturating_sub(reserve);
+    let moves = moves_to_go.unwrap_or(30).clamp(1, 50);
+
+    (usable / moves + increment * 3 / 4).min(usable)
+}
+
+/// Check if a score means that one of the sides has a forced mate
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE - MAX_PLY as i32
+}
+
+/// Get the number of moves until mate, negative when the side to move is getting mated
+///
+/// # Example:
+/// ```
+/// if let Some(moves) = mate_in(result.score) {
+///     println!("Mate in {}", moves);
+/// }
+/// ```
+pub fn mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+
+    let plies = MATE - score.abs();
+    Some(match score > 0 {
+        true => (plies + 1) / 2,
+        false => -(plies / 2),
+    })
+}
+
@@ -0,0 +1,141 @@
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Wins, draws and losses of a match, from the view of the first engine
+///
+/// A win scores one, a draw a half and a loss nothing
+///
+/// # Example:
+/// ```
+/// let mut score = MatchScore::default();
+/// score.add(Some(true));
+/// score.add(None);
+/// println!("{:?}", score.get_elo());
+/// ```
+pub struct MatchScore {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Decision of a sequential probability ratio test
+///
+/// # AcceptH0: The Elo difference is at most elo0
+/// # AcceptH1: The Elo difference is at least elo1
+/// # Continue: More games are needed
+///
+pub enum SprtDecision {
+    AcceptH0,
+    AcceptH1,
+    Continue,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Sequential probability ratio test between two Elo differences
+///
+/// The log likelihood ratio uses the normal approximation of the score, so draws are taken
+/// into account through the variance.
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Sprt { elo0: 0.0, elo1: 5.0, alpha: 0.05, beta: 0.05 }
+    }
+}
+
+impl MatchScore {
+    /// Add a game, Some(true) for a win, Some(false) for a loss and None for a draw
+    pub fn add(&mut self, win: Option<bool>) {
+        match win {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    /// Get the number of games played
+    pub fn get_games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Get the average score per game, between 0 and 1
+    pub fn get_score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.get_games().max(1) as f64
+    }
+
+    /// Get the Elo difference and the half width of its 95% confidence interval
+    ///
+    /// # Return: None before any games are played or when every game has the same result
+    pub fn get_elo(&self) -> Option<(f64, f64)> {
+        let (mean, variance) = self.mean_and_variance()?;
+        if mean <= 0.0 || mean >= 1.0 || variance <= 0.0 {
+            return None;
+        }
+
+        let deviation = (variance / self.get_games() as f64).sqrt();
+        let low = score_to_elo((mean - 1.96 * deviation).max(f64::EPSILON));
+        let high = score_to_elo((mean + 1.96 * deviation).min(1.0 - f64::EPSILON));
+
+        Some((score_to_elo(mean), (high - low) / 2.0))
+    }
+
+    fn mean_and_variance(&self) -> Option<(f64, f64)> {
+        let games = self.get_games() as f64;
+        if games == 0.0 {
+            return None;
+        }
+
+        let mean = self.get_score();
+        let variance = (self.wins as f64 * (1.0 - mean).powi(2)
+            + self.draws as f64 * (0.5 - mean).powi(2)
+            + self.losses as f64 * mean.powi(2)) / games;
+
+        Some((mean, variance))
+    }
+}
+
+impl Sprt {
+    /// Get the log likelihood ratio of the results
+    pub fn get_llr(&self, score: &MatchScore) -> f64 {
+        let (mean, variance) = match score.mean_and_variance() {
+            Some((mean, variance)) if variance > 0.0 => (mean, variance),
+            _ => return 0.0,
+        };
+        let (score0, score1) = (elo_to_score(self.elo0), elo_to_score(self.elo1));
+
+        score.get_games() as f64 * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Get the lower and upper bound of the log likelihood ratio
+    pub fn get_bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Decide if the test is finished
+    pub fn get_decision(&self, score: &MatchScore) -> SprtDecision {
+        let llr = self.get_llr(score);
+        let (lower, upper) = self.get_bounds();
+
+        if llr >= upper {
+            SprtDecision::AcceptH1
+        } else if llr <= lower {
+            SprtDecision::AcceptH0
+        } else {
+            SprtDecision::Continue
+        }
+    }
+}
+
+/// Convert an average score per game to an Elo difference
+pub fn score_to_elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Convert an Elo difference to the expected score per game
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
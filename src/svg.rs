@@ -0,0 +1,387 @@
+use crate::{Board, Chess, Dimensions, Move, PieceType, Side};
+
+// the piece paths are drawn on a square of this size
+const PIECE_BOX: f64 = 100.0;
+
+const LAST_MOVE_COLOR: &str = "#9bc700";
+const CHECK_COLOR: &str = "#e01818";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Colour of an arrow or a highlighted square, the letters used by [%cal] and [%csl]
+///
+/// # Green: G
+/// # Red: R
+/// # Yellow: Y
+/// # Blue: B
+///
+pub enum MarkColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl MarkColor {
+    /// Get the colour of a [%cal] or [%csl] letter
+    pub fn from_char(c: char) -> Option<MarkColor> {
+        match c {
+            'G' => Some(MarkColor::Green),
+            'R' => Some(MarkColor::Red),
+            'Y' => Some(MarkColor::Yellow),
+            'B' => Some(MarkColor::Blue),
+            _ => None,
+        }
+    }
+
+    fn to_svg(self) -> &'static str {
+        match self {
+            MarkColor::Green => "#15781b",
+            MarkColor::Red => "#882020",
+            MarkColor::Yellow => "#e68f00",
+            MarkColor::Blue => "#003088",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// An arrow from the centre of one square to another
+///
+/// # from: The square the arrow starts on
+/// # to: The square the arrow points to
+/// # color: The colour of the arrow
+///
+pub struct Arrow {
+    pub from: usize,
+    pub to: usize,
+    pub color: MarkColor,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// A square filled with a colour
+///
+/// # square: The highlighted square
+/// # color: The colour of the square
+///
+pub struct SquareMark {
+    pub square: usize,
+    pub color: MarkColor,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+/// Options for drawing a board as an SVG diagram
+///
+/// # square_size: The width and height of a square in pixels
+/// # light: The colour of the light squares, any SVG colour
+/// # dark: The colour of the dark squares, any SVG colour
+/// # coordinates: Write the files on the bottom rank and the ranks on the left file
+/// # perspective: The side at the bottom of the board, Black flips the board
+/// # last_move: The move whose squares are highlighted
+/// # check: Highlight the king of the side to move if it is in check
+/// # arrows: The arrows drawn over the pieces
+/// # marks: The highlighted squares
+///
+pub struct SvgOptions {
+    pub square_size: u32,
+    pub light: String,
+    pub dark: String,
+    pub coordinates: bool,
+    pub perspective: Side,
+    pub last_move: Option<Move>,
+    pub check: bool,
+    pub arrows: Vec<Arrow>,
+    pub marks: Vec<SquareMark>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            square_size: 45,
+            light: "#f0d9b5".to_string(),
+            dark: "#b58863".to_string(),
+            coordinates: true,
+            perspective: Side::White,
+            last_move: None,
+            check: false,
+            arrows: vec![],
+            marks: vec![],
+        }
+    }
+}
+
+/// Read the arrows of [%cal] and the squares of [%csl] in a PGN comment
+///
+/// # Example:
+/// ```
+/// let (arrows, marks) = svg::parse_annotations("[%cal Ge2e4,Rd1h5] [%csl Yf7]", Dimensions::STANDARD).unwrap();
+/// let options = SvgOptions { arrows, marks, ..Default::default() };
+/// ```
+pub fn parse_annotations(comment: &str, dimensions: Dimensions) -> Result<(Vec<Arrow>, Vec<SquareMark>), String> {
+    let (mut arrows, mut marks) = (vec![], vec![]);
+
+    for (command, is_arrow) in [("[%cal", true), ("[%csl", false)] {
+        let mut rest = comment;
+        while let Some(start) = rest.find(command) {
+            rest = &rest[start + command.len()..];
+            let end = rest.find(']').ok_or(format!("Missing ] after {}", command))?;
+
+            for mark in rest[..end].split(',').map(str::trim).filter(|mark| !mark.is_empty()) {
+                let invalid = || format!("Invalid {} annotation: {}", &command[1..], mark);
+                let mut chars = mark.chars();
+                let color = chars.next().and_then(MarkColor::from_char).ok_or_else(invalid)?;
+                let squares = chars.as_str();
+
+                if is_arrow {
+                    // the first square ends where the letter of the second one starts
+                    let split = squares.char_indices().skip(1).find(|(_, c)| c.is_ascii_alphabetic()).ok_or_else(invalid)?.0;
+                    let from = dimensions.square_from_str(&squares[..split]).ok_or_else(invalid)?;
+                    let to = dimensions.square_from_str(&squares[split..]).ok_or_else(invalid)?;
+                    arrows.push(Arrow { from, to, color });
+                } else {
+                    let square = dimensions.square_from_str(squares).ok_or_else(invalid)?;
+                    marks.push(SquareMark { square, color });
+                }
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    Ok((arrows, marks))
+}
+
+impl Board {
+    /// Draw the board as a standalone SVG diagram, the pieces are embedded as paths
+    ///
+    /// # Example:
+    /// ```
+    /// let chess = Chess::new();
+    /// let options = SvgOptions { square_size: 60, perspective: Side::Black, ..Default::default() };
+    /// std::fs::write("board.svg", chess.board.to_svg(&options))?;
+    /// ```
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let dimensions = self.get_dimensions();
+        let size = options.square_size as f64;
+        let (width, height) = (dimensions.get_width() as f64 * size, dimensions.get_height() as f64 * size);
+        let corner = |index: usize| get_corner(dimensions, index, options.perspective, size);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\">\n",
+            width, height, width, height,
+        );
+
+        let pieces: Vec<_> = (0..dimensions.get_num_squares()).filter_map(|index| self.get_piece_at_pos(index)).collect();
+        let mut kinds: Vec<_> = pieces.iter().map(|piece| (piece.get_piece_type(), piece.get_color())).collect();
+        kinds.sort_by_key(|(piece, side)| (piece.to_value(), *side == Side::Black));
+        kinds.dedup();
+        svg.push_str("<defs>\n");
+        for (piece, side) in kinds {
+            svg.push_str(&get_piece_definition(piece, side));
+        }
+        svg.push_str("</defs>\n");
+
+        svg.push_str(&format!("<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", width, height, escape(&options.light)));
+        for index in 0..dimensions.get_num_squares() {
+            let (x, y) = dimensions.get_coordinates(index);
+            if is_dark(x, y) {
+                svg.push_str(&get_square(corner(index), size, &escape(&options.dark), 1.0));
+            }
+        }
+
+        let mut highlights = vec![];
+        if let Some(chess_move) = options.last_move {
+            if chess_move.get_drop().is_none() {
+                highlights.push((chess_move.get_start(), LAST_MOVE_COLOR, 0.4));
+            }
+            highlights.push((chess_move.get_end(), LAST_MOVE_COLOR, 0.4));
+        }
+        if options.check && self.is_in_check() {
+            for king in self.get_piece_board(PieceType::King, self.get_playing_side()) {
+                highlights.push((king, CHECK_COLOR, 0.6));
+            }
+        }
+        for mark in &options.marks {
+            highlights.push((mark.square, mark.color.to_svg(), 0.6));
+        }
+        for (index, color, opacity) in highlights.into_iter().filter(|(index, _, _)| *index < dimensions.get_num_squares()) {
+            svg.push_str(&get_square(corner(index), size, color, opacity));
+        }
+
+        if options.coordinates {
+            svg.push_str(&self.get_coordinates_svg(options));
+        }
+
+        for piece in &pieces {
+            let (x, y) = corner(piece.get_occupied_slot());
+            svg.push_str(&format!(
+                "<use xlink:href=\"#{}\" transform=\"translate({} {}) scale({})\"/>\n",
+                get_piece_id(piece.get_piece_type(), piece.get_color()), x, y, size / PIECE_BOX,
+            ));
+        }
+
+        for arrow in options.arrows.iter().filter(|arrow| arrow.from != arrow.to && arrow.from.max(arrow.to) < dimensions.get_num_squares()) {
+            svg.push_str(&get_arrow(corner(arrow.from), corner(arrow.to), size, arrow.color));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Get the names of the files on the bottom rank and of the ranks on the left file, each in
+    /// the colour of the other kind of square
+    fn get_coordinates_svg(&self, options: &SvgOptions) -> String {
+        let dimensions = self.get_dimensions();
+        let size = options.square_size as f64;
+        let font_size = size * 0.22;
+        let (bottom, left) = match options.perspective {
+            Side::White => (0, 0),
+            Side::Black => (dimensions.get_height() - 1, dimensions.get_width() - 1),
+        };
+        let color = |x: usize, y: usize| escape(if is_dark(x, y) { &options.light } else { &options.dark });
+
+        let mut svg = String::new();
+        for x in 0..dimensions.get_width() {
+            let (left_edge, top) = get_corner(dimensions, dimensions.get_index(x, bottom), options.perspective, size);
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"end\" fill=\"{}\">{}</text>\n",
+                left_edge + size * 0.96, top + size * 0.96, font_size, color(x, bottom), (x as u8 + b'a') as char,
+            ));
+        }
+        for y in 0..dimensions.get_height() {
+            let (left_edge, top) = get_corner(dimensions, dimensions.get_index(left, y), options.perspective, size);
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+                left_edge + size * 0.04, top + font_size, font_size, color(left, y), y + 1,
+            ));
+        }
+
+        svg
+    }
+}
+
+impl Chess {
+    /// Draw the board as a standalone SVG diagram, see Board::to_svg
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        self.board.to_svg(options)
+    }
+}
+
+/// Check if the square on file x and rank y is dark, a1 is dark
+fn is_dark(x: usize, y: usize) -> bool {
+    (x + y).is_multiple_of(2)
+}
+
+/// Get the top left corner of a square in pixels
+fn get_corner(dimensions: Dimensions, index: usize, perspective: Side, size: f64) -> (f64, f64) {
+    let (x, y) = dimensions.get_coordinates(index);
+    let (column, row) = match perspective {
+        Side::White => (x, dimensions.get_height() - 1 - y),
+        Side::Black => (dimensions.get_width() - 1 - x, y),
+    };
+
+    (column as f64 * size, row as f64 * size)
+}
+
+fn get_square((x, y): (f64, f64), size: f64, color: &str, opacity: f64) -> String {
+    let opacity = match opacity < 1.0 {
+        true => format!(" fill-opacity=\"{}\"", opacity),
+        false => String::new(),
+    };
+    format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"{}/>\n", x, y, size, size, color, opacity)
+}
+
+/// Get an arrow from the centre of one square to the centre of another as a polygon
+fn get_arrow(from: (f64, f64), to: (f64, f64), size: f64, color: MarkColor) -> String {
+    let (start, tip) = ((from.0 + size / 2.0, from.1 + size / 2.0), (to.0 + size / 2.0, to.1 + size / 2.0));
+    let (dx, dy) = (tip.0 - start.0, tip.1 - start.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = (dx / length, dy / length);
+    let (nx, ny) = (-uy, ux);
+
+    let (shaft, head_width, head_length) = (size * 0.075, size * 0.225, size * 0.45);
+    let base = (tip.0 - ux * head_length, tip.1 - uy * head_length);
+    let points = [
+        (start.0 + nx * shaft, start.1 + ny * shaft),
+        (base.0 + nx * shaft, base.1 + ny * shaft),
+        (base.0 + nx * head_width, base.1 + ny * head_width),
+        tip,
+        (base.0 - nx * head_width, base.1 - ny * head_width),
+        (base.0 - nx * shaft, base.1 - ny * shaft),
+        (start.0 - nx * shaft, start.1 - ny * shaft),
+    ];
+    let points: Vec<_> = points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+
+    format!("<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"0.8\"/>\n", points.join(" "), color.to_svg())
+}
+
+fn get_piece_id(piece: PieceType, side: Side) -> String {
+    format!("{:?}-{:?}", side, piece).to_lowercase()
+}
+
+/// Get the outline of a piece, drawn in the colour of its side, and the details drawn on it in the
+/// colour of the other side
+fn get_piece_paths(piece: PieceType) -> Option<(&'static str, &'static str)> {
+    Some(match piece {
+        PieceType::Pawn => (
+            "M39 30A11 11 0 1 1 61 30A11 11 0 1 1 39 30ZM42 42H58L66 74H34ZM28 74H72V84H28Z",
+            "",
+        ),
+        PieceType::Knight => (
+            "M30 84H74C74 60 72 36 58 26L54 14L46 24L40 18L38 30C30 36 22 48 20 58C20 64 26 66 30 62C34 58 40 56 46 50C46 62 32 68 30 84Z",
+            "M38 38A3 3 0 1 1 44 38A3 3 0 1 1 38 38ZM24 56L28 54",
+        ),
+        PieceType::Bishop => (
+            "M45 14A5 5 0 1 1 55 14A5 5 0 1 1 45 14ZM50 20C34 34 32 52 38 62H62C68 52 66 34 50 20ZM36 62H64L66 76H34ZM26 76H74V84H26Z",
+            "M50 32V50M42 41H58",
+        ),
+        PieceType::Rook => (
+            "M26 18H36V26H45V18H55V26H64V18H74V36L66 42V70L74 76V84H26V76L34 70V42L26 36Z",
+            "M34 42H66M34 70H66",
+        ),
+        PieceType::Queen => (
+            "M22 30L32 68H68L78 30L68 50L64 22L57 46L50 18L43 46L36 22L32 50ZM30 68H70L72 84H28Z\
+             M18 30A4 4 0 1 1 26 30A4 4 0 1 1 18 30ZM32 22A4 4 0 1 1 40 22A4 4 0 1 1 32 22Z\
+             M46 18A4 4 0 1 1 54 18A4 4 0 1 1 46 18ZM60 22A4 4 0 1 1 68 22A4 4 0 1 1 60 22Z\
+             M74 30A4 4 0 1 1 82 30A4 4 0 1 1 74 30Z",
+            "M32 74H68",
+        ),
+        PieceType::King => (
+            "M46 8H54V16H62V24H54V32H46V24H38V16H46ZM30 70C18 56 22 36 38 36C46 36 50 44 50 44C50 44 54 36 62 36C78 36 82 56 70 70ZM30 70H70L72 84H28Z",
+            "M50 44V68M32 76H68",
+        ),
+        _ => return None,
+    })
+}
+
+/// Get the symbol of a piece that is used by every square it stands on
+fn get_piece_definition(piece: PieceType, side: Side) -> String {
+    let (fill, detail) = match side {
+        Side::White => ("#fff", "#000"),
+        Side::Black => ("#000", "#fff"),
+    };
+    let id = get_piece_id(piece, side);
+
+    match get_piece_paths(piece) {
+        Some((outline, details)) => {
+            let mut definition = format!(
+                "<g id=\"{}\" stroke-width=\"3\" stroke-linejoin=\"round\" stroke-linecap=\"round\">\
+                 <path d=\"{}\" fill=\"{}\" stroke=\"#000\"/>",
+                id, outline, fill,
+            );
+            if !details.is_empty() {
+                definition.push_str(&format!("<path d=\"{}\" fill=\"none\" stroke=\"{}\"/>", details, detail));
+            }
+            definition.push_str("</g>\n");
+            definition
+        },
+        // the fairy pieces are a disc with their letter
+        None => format!(
+            "<g id=\"{}\"><path d=\"M24 54A26 26 0 1 1 76 54A26 26 0 1 1 24 54Z\" fill=\"{}\" stroke=\"#000\" stroke-width=\"3\"/>\
+             <text x=\"50\" y=\"66\" font-family=\"sans-serif\" font-size=\"34\" font-weight=\"bold\" text-anchor=\"middle\" fill=\"{}\">{}</text></g>\n",
+            id, fill, detail, piece.to_char().to_ascii_uppercase(),
+        ),
+    }
+}
+
+/// Escape a value written inside an attribute
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
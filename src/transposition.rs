@@ -0,0 +1,233 @@
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::search::{MATE, MAX_PLY};
+use crate::Move;
+
+/// Size of a newly created transposition table in megabytes
+pub const DEFAULT_HASH_MB: usize = 16;
+
+// entries that share an index, the first is kept for deep searches and the second is always replaced
+const BUCKET_SIZE: usize = 2;
+// the buckets are split into shards that are locked on their own, so threads searching together
+// rarely wait for each other
+const SHARDS: usize = 64;
+
+type Bucket = [Option<TableEntry>; BUCKET_SIZE];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// What the stored score says about the real score of the position
+///
+/// # Exact: The score is the real score
+/// # Lower: The search failed high, the real score is at least the stored score
+/// # Upper: The search failed low, the real score is at most the stored score
+///
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// A stored search result of a position
+pub struct TableEntry {
+    key: u64,
+    best_move: Option<Move>,
+    score: i32,
+    depth: i32,
+    bound: Bound,
+    age: u8,
+}
+
+impl TableEntry {
+    /// Get the best move found for the position
+    pub fn get_best_move(&self) -> Option<Move> {
+        self.best_move
+    }
+
+    /// Get the score relative to the root of the search at ply
+    pub fn get_score(&self, ply: usize) -> i32 {
+        score_from_table(self.score, ply)
+    }
+
+    /// Get the depth the position was searched to
+    pub fn get_depth(&self) -> i32 {
+        self.depth
+    }
+
+    /// Get what kind of bound the score is
+    pub fn get_bound(&self) -> Bound {
+        self.bound
+    }
+
+    /// Get the age of the search the entry was stored in
+    pub fn get_age(&self) -> u8 {
+        self.age
+    }
+}
+
+/// Fixed size hash table of search results, indexed by the Zobrist key of the position
+///
+/// The table is shared by reference between threads, every shard of it has its own lock.
+///
+/// # Example:
+/// ```
+/// let table = TranspositionTable::new(64);
+/// table.new_search();
+/// table.store(key, 4, Bound::Exact, 25, Some(chess_move), 0);
+/// if let Some(entry) = table.probe(key) {
+///     println!("{:?}", entry.get_best_move());
+/// }
+/// ```
+pub struct TranspositionTable {
+    shards: Vec<Mutex<Vec<Bucket>>>,
+    age: AtomicU8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    /// Create a transposition table that uses about mb megabytes of memory
+    pub fn new(mb: usize) -> TranspositionTable {
+        TranspositionTable {
+            shards: (0..SHARDS).map(|_| Mutex::new(vec![[None; BUCKET_SIZE]; shard_size(mb)])).collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    /// Change the size of the table, this clears all entries
+    ///
+    /// Every shard is resized under its own lock, so searches sharing the table keep using it.
+    pub fn resize(&self, mb: usize) {
+        for shard in self.shards.iter() {
+            *shard.lock().unwrap() = vec![[None; BUCKET_SIZE]; shard_size(mb)];
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.lock().unwrap().fill([None; BUCKET_SIZE]);
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Mark the start of a new search so that entries of old searches get replaced first
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Look up the entry of a position
+    pub fn probe(&self, key: u64) -> Option<TableEntry> {
+        let buckets = self.lock_shard(key);
+
+        buckets[get_index(key, &buckets)].iter()
+            .flatten()
+            .find(|entry| entry.key == key)
+            .copied()
+    }
+
+    /// Store a search result, score is relative to the root of the search at ply
+    pub fn store(&self, key: u64, depth: i32, bound: Bound, score: i32, best_move: Option<Move>, ply: usize) {
+        let age = self.age.load(Ordering::Relaxed);
+        let mut buckets = self.lock_shard(key);
+        let index = get_index(key, &buckets);
+        let bucket = &mut buckets[index];
+
+        // keep the best move of an earlier search of the position if this one did not find any
+        let best_move = best_move.or_else(|| bucket.iter().flatten().find(|entry| entry.key == key).and_then(|entry| entry.best_move));
+        let entry = TableEntry {
+            key,
+            best_move,
+            score: score_to_table(score, ply),
+            depth,
+            bound,
+            age,
+        };
+
+        // the same position is always updated in place
+        if let Some(slot) = bucket.iter_mut().find(|slot| slot.is_some_and(|old| old.key == key)) {
+            if slot.is_some_and(|old| old.age == age && old.depth > depth && bound != Bound::Exact) {
+                return;
+            }
+            *slot = Some(entry);
+            return;
+        }
+
+        // the depth preferred slot takes empty, old or shallower entries, everything else goes in the
+        // always replace slot
+        let replace_deep = match bucket[0] {
+            None => true,
+            Some(old) => old.age != age || old.depth <= depth,
+        };
+
+        if replace_deep {
+            bucket[1] = bucket[0];
+            bucket[0] = Some(entry);
+        } else {
+            bucket[1] = Some(entry);
+        }
+    }
+
+    /// Get how full the table is with entries of the current search in permille, sampled from
+    /// about a thousand entries at the start of every shard
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let (mut total, mut current) = (0, 0);
+
+        for shard in self.shards.iter() {
+            let buckets = shard.lock().unwrap();
+            for slot in buckets.iter().flatten().take(1000 / SHARDS) {
+                total += 1;
+                if slot.is_some_and(|entry| entry.age == age) {
+                    current += 1;
+                }
+            }
+        }
+
+        current * 1000 / total.max(1)
+    }
+
+    /// Lock the shard of a key, chosen by its high bits
+    fn lock_shard(&self, key: u64) -> MutexGuard<'_, Vec<Bucket>> {
+        self.shards[((key >> 32) % SHARDS as u64) as usize].lock().unwrap()
+    }
+}
+
+/// Get the buckets in every shard of a table of mb megabytes
+fn shard_size(mb: usize) -> usize {
+    (mb * 1024 * 1024 / size_of::<Bucket>() / SHARDS).max(1)
+}
+
+/// Get the bucket of a key in its shard from its low bits
+fn get_index(key: u64, buckets: &[Bucket]) -> usize {
+    (key % buckets.len() as u64) as usize
+}
+
+/// Mate scores are stored relative to the position instead of the root so that they stay correct
+/// when the position is found at another ply
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score <= -(MATE - MAX_PLY as i32) {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score <= -(MATE - MAX_PLY as i32) {
+        score + ply as i32
+    } else {
+        score
+    }
+}
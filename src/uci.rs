@@ -0,0 +1,392 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::variant::Variant;
+use crate::{Chess, Dimensions, Move, Side};
+
+/// How long the engine gets to answer a command, and how long it may go over its search time
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Score reported by an engine, from the view of the side to move
+///
+/// # Centipawns: Material score in centipawns
+/// # Mate: Mate in the number of moves, negative if the side to move gets mated
+///
+pub enum Score {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The parsed fields of an info line, fields that were not sent are None or empty
+pub struct EngineInfo {
+    pub depth: Option<u32>,
+    pub multipv: Option<u32>,
+    pub score: Option<Score>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The reply of an engine to go
+///
+/// # best_move: The move to play, None if the engine sent bestmove 0000 or (none)
+/// # ponder: The move the engine expects as an answer
+/// # info: The last info line with a score of every multipv line, the best line first
+///
+pub struct EngineResult {
+    pub best_move: Option<Move>,
+    pub ponder: Option<Move>,
+    pub info: Vec<EngineInfo>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Arguments of the go command, the ones that are None are not sent
+pub struct GoLimits {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+impl GoLimits {
+    fn to_command(self) -> String {
+        let mut command = "go".to_string();
+        let times = [("wtime", self.wtime), ("btime", self.btime), ("winc", self.winc), ("binc", self.binc), ("movetime", self.movetime)];
+        for (name, time) in times {
+            if let Some(time) = time {
+                command.push_str(&format!(" {} {}", name, time.as_millis()));
+            }
+        }
+        if let Some(movestogo) = self.movestogo {
+            command.push_str(&format!(" movestogo {}", movestogo));
+        }
+        if let Some(depth) = self.depth {
+            command.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(nodes) = self.nodes {
+            command.push_str(&format!(" nodes {}", nodes));
+        }
+
+        command
+    }
+}
+
+/// A UCI engine running in its own process
+///
+/// Every command waits for the engine to answer, and fails when it takes longer than the timeout.
+/// A search may take the time it was given plus the timeout before it fails.
+///
+/// # Example:
+/// ```
+/// let mut engine = UciEngine::spawn("stockfish", &[])?;
+/// let mut chess = Chess::new();
+/// engine.set_position(&chess)?;
+/// let result = engine.go(GoLimits { movetime: Some(Duration::from_millis(100)), ..Default::default() })?;
+/// chess.play_move(result.best_move.unwrap());
+/// ```
+pub struct UciEngine {
+    process: Child,
+    input: ChildStdin,
+    output: Receiver<String>,
+    name: Option<String>,
+    author: Option<String>,
+    side: Side,
+    dimensions: Dimensions,
+    chess960: bool,  // value of UCI_Chess960 the engine has
+    variant: String, // value of UCI_Variant the engine has
+    timeout: Duration,
+}
+
+impl UciEngine {
+    /// Start the engine at path and do the uci handshake
+    pub fn spawn(path: &str, args: &[&str]) -> Result<UciEngine, String> {
+        let mut process = Command::new(path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Unable to start engine {}: {}", path, error))?;
+
+        let input = process.stdin.take().ok_or("Unable to open engine input")?;
+        let stdout = process.stdout.take().ok_or("Unable to open engine output")?;
+
+        // lines are read in their own thread so that waiting for them can time out
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if sender.send(line).is_err() { break },
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut engine = UciEngine {
+            process,
+            input,
+            output,
+            name: None,
+            author: None,
+            side: Side::White,
+            dimensions: Dimensions::STANDARD,
+            chess960: false,
+            variant: "chess".to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        };
+
+        engine.send("uci")?;
+        let deadline = Instant::now() + engine.timeout;
+        loop {
+            let line = engine.read_line(deadline)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = Some(name.trim().to_string());
+            } else if let Some(author) = line.strip_prefix("id author ") {
+                engine.author = Some(author.trim().to_string());
+            } else if line.trim() == "uciok" {
+                return Ok(engine);
+            }
+        }
+    }
+
+    /// Get the name the engine sent during the handshake
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Get the author the engine sent during the handshake
+    pub fn get_author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// Set how long the engine has to answer
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Set an engine option and wait until the engine is ready again
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.is_ready()
+    }
+
+    /// Wait until the engine answers isready
+    pub fn is_ready(&mut self) -> Result<(), String> {
+        self.send("isready")?;
+        let deadline = Instant::now() + self.timeout;
+        while self.read_line(deadline)?.trim() != "readyok" {}
+
+        Ok(())
+    }
+
+    /// Tell the engine that the next position is from a new game
+    pub fn new_game(&mut self) -> Result<(), String> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    /// Send the start position and the moves of the history of chess
+    ///
+    /// UCI_Chess960 and UCI_Variant are set first when the game needs other values than the engine
+    /// has, so that the engine reads the castling moves and the FEN the way they are meant.
+    pub fn set_position(&mut self, chess: &Chess) -> Result<(), String> {
+        if chess.is_chess960() != self.chess960 {
+            self.set_option("UCI_Chess960", &chess.is_chess960().to_string())?;
+            self.chess960 = chess.is_chess960();
+        }
+        let variant = get_uci_variant(chess.get_variant().as_ref());
+        if variant != self.variant {
+            self.set_option("UCI_Variant", &variant)?;
+            self.variant = variant;
+        }
+
+        // startpos is the start position of the variant the engine was set to
+        let start_fen = chess.get_start_fen();
+        let mut command = match start_fen == chess.get_variant().get_start_fen() {
+            true => "position startpos".to_string(),
+            false => format!("position fen {}", start_fen),
+        };
+
+        let dimensions = chess.board.get_dimensions();
+        let history = chess.get_history();
+        if !history.is_empty() {
+            let moves: Vec<_> = history.iter().map(|chess_move| chess_move.to_uci_with(dimensions)).collect();
+            command.push_str(&format!(" moves {}", moves.join(" ")));
+        }
+
+        self.side = chess.get_playing_side();
+        self.dimensions = dimensions;
+        self.send(&command)
+    }
+
+    /// Search the position that was set last and wait for the best move
+    ///
+    /// When the engine uses up its time plus the timeout it is told to stop, and if it still does
+    /// not answer the process gets killed.
+    pub fn go(&mut self, limits: GoLimits) -> Result<EngineResult, String> {
+        self.go_with_info(limits, |_| {})
+    }
+
+    /// Same as go, but calls info with every info line that was sent
+    pub fn go_with_info<F>(&mut self, limits: GoLimits, mut info: F) -> Result<EngineResult, String>
+    where
+        F: FnMut(&EngineInfo),
+    {
+        let clock = match self.side {
+            Side::White => limits.wtime,
+            Side::Black => limits.btime,
+        };
+        let search_time = limits.movetime.or(clock).unwrap_or(Duration::ZERO);
+        let mut deadline = Instant::now() + search_time + self.timeout;
+
+        self.send(&limits.to_command())?;
+
+        let mut result = EngineResult::default();
+        let mut stopped = false;
+        loop {
+            let line = match self.read_line(deadline) {
+                Ok(line) => line,
+                Err(_) if !stopped => {
+                    // the engine gets the timeout again to answer stop, info lines may come first
+                    stopped = true;
+                    self.send("stop")?;
+                    deadline = Instant::now() + self.timeout;
+                    continue;
+                },
+                Err(error) => {
+                    let _ = self.process.kill();
+                    return Err(format!("Engine did not stop searching: {}", error));
+                },
+            };
+
+            if let Some(parsed) = parse_info_with(&line, self.dimensions) {
+                info(&parsed);
+                if parsed.score.is_some() {
+                    let index = parsed.multipv.unwrap_or(1).max(1) as usize - 1;
+                    if result.info.len() <= index {
+                        result.info.resize(index + 1, EngineInfo::default());
+                    }
+                    result.info[index] = parsed;
+                }
+            } else if let Some(best_move) = line.trim().strip_prefix("bestmove") {
+                let tokens: Vec<_> = best_move.split_whitespace().collect();
+                result.best_move = tokens.first().and_then(|notation| Move::from_uci_with(notation, self.dimensions).ok());
+                if tokens.get(1) == Some(&"ponder") {
+                    result.ponder = tokens.get(2).and_then(|notation| Move::from_uci_with(notation, self.dimensions).ok());
+                }
+
+                return Ok(result);
+            }
+        }
+    }
+
+    /// Tell the engine to quit and wait for it to exit, killing it if it does not in time
+    pub fn quit(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let _ = self.send("quit");
+        let deadline = Instant::now() + self.timeout.min(Duration::from_secs(1));
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.process.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        writeln!(self.input, "{}", command)
+            .and_then(|_| self.input.flush())
+            .map_err(|error| format!("Unable to write to engine: {}", error))
+    }
+
+    fn read_line(&self, deadline: Instant) -> Result<String, String> {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        match self.output.recv_timeout(wait) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err("Engine timed out".to_string()),
+            Err(RecvTimeoutError::Disconnected) => Err("Engine closed its output".to_string()),
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if let Ok(None) = self.process.try_wait() {
+            self.shutdown();
+        }
+    }
+}
+
+/// Get the value of UCI_Variant for a variant, the names multi variant engines use
+fn get_uci_variant(variant: &dyn Variant) -> String {
+    let name: String = variant.get_name().chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    match name.as_str() {
+        "standard" => "chess".to_string(),
+        "threecheck" => "3check".to_string(),
+        _ => name,
+    }
+}
+
+/// Parse an info line sent by an engine for a standard board
+///
+/// # Return: None if line is not an info line
+///
+/// # Example:
+/// ```
+/// let info = parse_info("info depth 3 score cp 25 nodes 1200 pv e2e4 e7e5").unwrap();
+/// assert!(info.score == Some(Score::Centipawns(25)));
+/// ```
+pub fn parse_info(line: &str) -> Option<EngineInfo> {
+    parse_info_with(line, Dimensions::STANDARD)
+}
+
+/// Parse an info line sent by an engine, the moves of the pv are on a board of dimensions
+pub fn parse_info_with(line: &str, dimensions: Dimensions) -> Option<EngineInfo> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("info") {
+        return None;
+    }
+
+    let mut info = EngineInfo::default();
+    while let Some(token) = tokens.next() {
+        match token {
+            "depth" => info.depth = tokens.next().and_then(|depth| depth.parse().ok()),
+            "multipv" => info.multipv = tokens.next().and_then(|multipv| multipv.parse().ok()),
+            "nodes" => info.nodes = tokens.next().and_then(|nodes| nodes.parse().ok()),
+            "time" => info.time = tokens.next().and_then(|time| time.parse().ok()).map(Duration::from_millis),
+            "score" => {
+                let value = tokens.clone().nth(1).and_then(|value| value.parse().ok());
+                info.score = match (tokens.next(), value) {
+                    (Some("cp"), Some(value)) => Some(Score::Centipawns(value)),
+                    (Some("mate"), Some(value)) => Some(Score::Mate(value)),
+                    _ => None,
+                };
+                tokens.next();
+            },
+            // the pv and string take up the rest of the line
+            "pv" => {
+                info.pv = tokens.by_ref().map_while(|notation| Move::from_uci_with(notation, dimensions).ok()).collect();
+            },
+            "string" => break,
+            _ => {},
+        }
+    }
+
+    Some(info)
+}
@@ -0,0 +1,213 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::{Board, Dimensions, Move, Outcome, PieceType, Side};
+
+mod crazyhouse;
+pub use crazyhouse::{Crazyhouse, CrazyhouseState};
+pub(crate) use crazyhouse::get_pocket_piece;
+mod bughouse;
+pub use bughouse::Bughouse;
+mod atomic;
+pub use atomic::Atomic;
+mod antichess;
+pub use antichess::Antichess;
+mod threecheck;
+pub use threecheck::{ThreeCheck, ThreeCheckState};
+mod kingofthehill;
+pub use kingofthehill::KingOfTheHill;
+mod horde;
+pub use horde::Horde;
+mod racingkings;
+pub use racingkings::RacingKings;
+mod capablanca;
+pub use capablanca::Capablanca;
+mod grand;
+pub use grand::Grand;
+
+/// FEN of the standard start position
+pub const STANDARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Rules of a chess variant
+///
+/// Every hook defaults to the rules of standard chess, so a variant only overrides the rules it
+/// changes. The board keeps the variant it is played with, and move generation, move making, the
+/// game state and FEN parsing all go through it.
+///
+/// # Example:
+/// ```
+/// // pawns are not allowed to move two squares
+/// struct NoDoublePush;
+///
+/// fn is_double_push(board: &Board, chess_move: Move) -> bool {
+///     chess_move.get_start().abs_diff(chess_move.get_end()) == 16
+///         && board.get_piece_type_at_pos(chess_move.get_start()) == PieceType::Pawn
+/// }
+///
+/// impl Variant for NoDoublePush {
+///     fn get_name(&self) -> &str {
+///         "No double push"
+///     }
+///
+///     fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+///         board.is_standard_legal(chess_move) && !is_double_push(board, chess_move)
+///     }
+///
+///     fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+///         board.generate_legal_moves().into_iter().filter(|chess_move| !is_double_push(board, *chess_move)).collect()
+///     }
+/// }
+///
+/// let chess = Chess::new_variant(Arc::new(NoDoublePush)).unwrap();
+/// assert!(chess.get_legal_moves().len() == 12);
+/// ```
+pub trait Variant: Send + Sync {
+    /// Get the name of the variant as written in the PGN Variant tag
+    fn get_name(&self) -> &str;
+
+    /// Get the FEN of the start position
+    fn get_start_fen(&self) -> String {
+        STANDARD_FEN.to_string()
+    }
+
+    /// Get the number of files and ranks of the board
+    fn get_dimensions(&self) -> Dimensions {
+        Dimensions::STANDARD
+    }
+
+    /// Get the rank the pawns of white start on, counted from 0, black pawns start on the same
+    /// rank seen from the other side. Pawns on or behind it are able to move two squares.
+    fn get_pawn_rank(&self) -> usize {
+        1
+    }
+
+    /// Get the piece types a pawn is able to promote to on the last rank
+    fn get_promotion_pieces(&self) -> &[PieceType] {
+        &[PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight]
+    }
+
+    /// Get all legal moves for the side to move
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        board.generate_legal_moves()
+    }
+
+    /// Check if a move is legal for the side to move
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        board.is_standard_legal(chess_move)
+    }
+
+    /// Play a legal move, including any effects the variant adds to it
+    fn play_move(&self, board: &mut Board, chess_move: Move) {
+        board.apply_move(chess_move);
+    }
+
+    /// Check if the side to move is in check
+    fn is_in_check(&self, board: &Board) -> bool {
+        board.is_standard_check()
+    }
+
+    /// Check if a king next to the enemy king is safe from every attack, which lets it castle
+    /// through and into attacked squares next to the enemy king
+    fn is_touching_kings_safe(&self) -> bool {
+        false
+    }
+
+    /// Get the outcome of a game that ended by a rule of the variant, checked before checkmate
+    /// and stalemate
+    fn get_outcome(&self, _board: &Board) -> Option<Outcome> {
+        None
+    }
+
+    /// Check if side does not have enough material left to ever win
+    fn has_insufficient_material(&self, board: &Board, side: Side) -> bool {
+        board.has_insufficient_material(side)
+    }
+
+    /// Check if neither side is able to win anymore
+    fn is_insufficient_material(&self, board: &Board) -> bool {
+        board.is_insufficient_material()
+    }
+
+    /// Read the state the variant adds to a FEN into state, which the board keeps for the variant
+    ///
+    /// # Return: The FEN of the standard part of the position
+    fn parse_fen(&self, fen: &str, _state: &mut Option<Box<dyn VariantState>>) -> Result<String, String> {
+        Ok(fen.to_string())
+    }
+
+    /// Add the state of the variant to the FEN of the standard part of the position
+    fn write_fen(&self, _board: &Board, fen: String) -> String {
+        fen
+    }
+}
+
+/// State of a position that a variant adds to the board, like the pieces in hand of Crazyhouse
+///
+/// The variant creates the state while reading a FEN, the board keeps it through every move and
+/// gives it back with Board::get_variant_state.
+///
+/// # Example:
+/// ```
+/// #[derive(Clone)]
+/// struct Captures(u32);
+///
+/// impl VariantState for Captures {
+///     fn clone_state(&self) -> Box<dyn VariantState> {
+///         Box::new(self.clone())
+///     }
+/// }
+/// ```
+pub trait VariantState: Any + Send + Sync {
+    /// Copy the state for a copy of the board
+    fn clone_state(&self) -> Box<dyn VariantState>;
+
+    /// Get the pieces in hand of side in variants with drops, indexed by piece type value
+    fn get_pocket(&self, _side: Side) -> [u8; 6] {
+        [0; 6]
+    }
+
+    /// Get the part of the Zobrist key of the position that comes from the state, 0 for an
+    /// empty state so that it leaves the key of standard positions unchanged
+    fn get_zobrist_key(&self) -> u64 {
+        0
+    }
+}
+
+impl Clone for Box<dyn VariantState> {
+    fn clone(&self) -> Self {
+        self.clone_state()
+    }
+}
+
+/// Get a variant from its name as written in the PGN Variant tag, case, spaces and dashes are ignored
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new_variant(variant::from_name("Crazyhouse").unwrap()).unwrap();
+/// ```
+pub fn from_name(name: &str) -> Option<Arc<dyn Variant>> {
+    let name: String = name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    match name.as_str() {
+        "standard" | "chess" | "fromposition" | "chess960" | "fischerandom" | "fischerrandom" => Some(Arc::new(Standard)),
+        "crazyhouse" => Some(Arc::new(Crazyhouse)),
+        "bughouse" => Some(Arc::new(Bughouse)),
+        "atomic" => Some(Arc::new(Atomic)),
+        "antichess" | "giveaway" | "losingchess" | "losers" => Some(Arc::new(Antichess)),
+        "threecheck" | "3check" => Some(Arc::new(ThreeCheck)),
+        "kingofthehill" | "koth" => Some(Arc::new(KingOfTheHill)),
+        "horde" => Some(Arc::new(Horde)),
+        "racingkings" => Some(Arc::new(RacingKings)),
+        "capablanca" | "capablancachess" => Some(Arc::new(Capablanca)),
+        "grand" | "grandchess" => Some(Arc::new(Grand)),
+        _ => None,
+    }
+}
+
+/// Standard chess
+pub struct Standard;
+
+impl Variant for Standard {
+    fn get_name(&self) -> &str {
+        "Standard"
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{Board, Move, Outcome, PieceType, Side, Termination};
+
+use super::{Variant, VariantState};
+
+/// Antichess, also called losing chess, the side that loses all its pieces or is stalemated wins
+///
+/// Capturing is compulsory, the king is an ordinary piece that is able to be captured and pawns
+/// may promote to a king. There is no check and no castling.
+///
+/// # Example:
+/// ```
+/// let mut chess = Chess::new_variant(Arc::new(Antichess)).unwrap();
+/// for san in ["e3", "b5"] {
+///     chess.play_move(chess.parse_san(san).unwrap());
+/// }
+/// // the bishop has to take
+/// assert!(chess.get_legal_moves() == vec![Move::from_uci("f1b5").unwrap()]);
+/// ```
+pub struct Antichess;
+
+impl Variant for Antichess {
+    fn get_name(&self) -> &str {
+        "Antichess"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1".to_string()
+    }
+
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        let mut moves = vec![];
+        for chess_move in board.generate_pseudo_legal_moves() {
+            // every promotion is also able to be a promotion to a king
+            if chess_move.get_promotion() == Some(PieceType::Queen) {
+                moves.push(Move::new(chess_move.get_start(), chess_move.get_end(), Some(PieceType::King)));
+            }
+            moves.push(chess_move);
+        }
+
+        if moves.iter().any(|chess_move| board.is_capture(*chess_move)) {
+            moves.retain(|chess_move| board.is_capture(*chess_move));
+        }
+
+        moves
+    }
+
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        self.get_legal_moves(board).contains(&chess_move)
+    }
+
+    fn is_in_check(&self, _board: &Board) -> bool {
+        false
+    }
+
+    fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+        let side = board.get_playing_side();
+        if board.get_sides_board(side).count() == 0 || self.get_legal_moves(board).is_empty() {
+            return Some(Outcome::loss(side.get_opposite(), Termination::Variant));
+        }
+
+        None
+    }
+
+    // losing every piece wins, so having few pieces is never a reason to lose on time
+    fn has_insufficient_material(&self, _board: &Board, _side: Side) -> bool {
+        false
+    }
+
+    // a bishop against a bishop on the other square color are never able to take each other
+    fn is_insufficient_material(&self, board: &Board) -> bool {
+        let white = board.get_sides_board(Side::White);
+        let black = board.get_sides_board(Side::Black);
+        let bishops = board.get_piece_board(PieceType::Bishop, Side::White) | board.get_piece_board(PieceType::Bishop, Side::Black);
+        if white.count() != 1 || black.count() != 1 || (white | black).to_number() != bishops.to_number() {
+            return false;
+        }
+
+        let color = |index: usize| {
+            let (x, y) = board.get_coordinates(index);
+            (x + y) % 2
+        };
+        white.into_iter().map(color).ne(black.into_iter().map(color))
+    }
+
+    // there is no castling, so castling rights in the FEN are ignored
+    fn parse_fen(&self, fen: &str, _state: &mut Option<Box<dyn VariantState>>) -> Result<String, String> {
+        let mut fields: Vec<_> = fen.split_whitespace().collect();
+        if let Some(castling) = fields.get_mut(2) {
+            *castling = "-";
+        }
+
+        Ok(fields.join(" "))
+    }
+}
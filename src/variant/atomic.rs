@@ -0,0 +1,111 @@
+use crate::{Board, Move, Outcome, PieceType, Side, Termination};
+
+use super::Variant;
+
+/// Atomic, every capture explodes the capturing piece and all pieces next to the capture square
+/// apart from pawns
+///
+/// Kings are not allowed to capture, and a move that explodes the own king is illegal. Exploding
+/// the king of the opponent wins right away, even if the own king is left in check. Kings next to
+/// each other are never in check, since capturing one of them would explode both.
+///
+/// # Example:
+/// ```
+/// let mut chess = Chess::new_variant(Arc::new(Atomic)).unwrap();
+/// for san in ["Nf3", "f6", "Ne5", "a6", "Nxd7"] {
+///     chess.play_move(chess.parse_san(san).unwrap());
+/// }
+/// assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+/// ```
+pub struct Atomic;
+
+impl Variant for Atomic {
+    fn get_name(&self) -> &str {
+        "Atomic"
+    }
+
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        let side = board.get_playing_side();
+        if !has_king(board, side) {
+            return vec![];
+        }
+
+        board.generate_pseudo_legal_moves().into_iter().filter(|chess_move| {
+            if board.is_capture(*chess_move) && board.get_piece_type_at_pos(chess_move.get_start()) == PieceType::King {
+                return false;
+            }
+
+            let mut after = board.clone();
+            self.play_move(&mut after, *chess_move);
+            has_king(&after, side) && (!has_king(&after, side.get_opposite()) || !is_attacked(&after, side))
+        }).collect()
+    }
+
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        self.get_legal_moves(board).contains(&chess_move)
+    }
+
+    fn play_move(&self, board: &mut Board, chess_move: Move) {
+        let is_capture = board.is_capture(chess_move);
+        board.apply_move(chess_move);
+        if !is_capture {
+            return;
+        }
+
+        // the capturing piece explodes with everything around it except pawns
+        let end = chess_move.get_end();
+        board.remove_piece(end);
+        let dimensions = board.get_dimensions();
+        for (dx, dy) in [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)] {
+            if let Some(index) = dimensions.get_offset(end, dx, dy) {
+                if board.get_piece_type_at_pos(index) != PieceType::Pawn {
+                    board.remove_piece(index);
+                }
+            }
+        }
+
+        board.update_all_calculations();
+    }
+
+    fn is_touching_kings_safe(&self) -> bool {
+        true
+    }
+
+    fn is_in_check(&self, board: &Board) -> bool {
+        let side = board.get_playing_side();
+        has_king(board, side) && has_king(board, side.get_opposite()) && is_attacked(board, side)
+    }
+
+    fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+        [Side::White, Side::Black].into_iter()
+            .find(|side| !has_king(board, *side))
+            .map(|side| Outcome::loss(side, Termination::Variant))
+    }
+
+    // a lone king is not able to capture anything
+    fn has_insufficient_material(&self, board: &Board, side: Side) -> bool {
+        board.get_sides_board(side).count() == 1
+    }
+
+    fn is_insufficient_material(&self, board: &Board) -> bool {
+        self.has_insufficient_material(board, Side::White) && self.has_insufficient_material(board, Side::Black)
+    }
+}
+
+fn has_king(board: &Board, side: Side) -> bool {
+    board.get_piece_board(PieceType::King, side).count() != 0
+}
+
+/// Check if the king of side is attacked by a piece of the opponent other than the king, kings
+/// next to each other are never in check
+fn is_attacked(board: &Board, side: Side) -> bool {
+    let king = board.get_king(side);
+    let ((x, y), (other_x, other_y)) = (board.get_coordinates(king), board.get_coordinates(board.get_king(side.get_opposite())));
+    if x.abs_diff(other_x) <= 1 && y.abs_diff(other_y) <= 1 {
+        return false;
+    }
+
+    let opponent = side.get_opposite();
+    let attackers = board.get_sides_board(opponent) & !board.get_piece_board(PieceType::King, opponent);
+    board.is_attacked_by(king, opponent, board.all_pieces_bitboard(), attackers)
+}
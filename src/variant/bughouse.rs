@@ -0,0 +1,48 @@
+use crate::{Board, Move, Side};
+
+use super::{crazyhouse, Crazyhouse, Variant, VariantState};
+
+/// One board of a Bughouse game, played like Crazyhouse except that captured pieces go to the
+/// partner on the other board
+///
+/// The board itself only drops pieces out of its pockets, passing captured pieces on is done by
+/// the game that links both boards.
+pub struct Bughouse;
+
+impl Variant for Bughouse {
+    fn get_name(&self) -> &str {
+        "Bughouse"
+    }
+
+    fn get_start_fen(&self) -> String {
+        Crazyhouse.get_start_fen()
+    }
+
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        Crazyhouse.get_legal_moves(board)
+    }
+
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        Crazyhouse.is_legal(board, chess_move)
+    }
+
+    fn play_move(&self, board: &mut Board, chess_move: Move) {
+        crazyhouse::play_move(board, chess_move, false);
+    }
+
+    fn has_insufficient_material(&self, board: &Board, side: Side) -> bool {
+        Crazyhouse.has_insufficient_material(board, side)
+    }
+
+    fn is_insufficient_material(&self, board: &Board) -> bool {
+        Crazyhouse.is_insufficient_material(board)
+    }
+
+    fn parse_fen(&self, fen: &str, state: &mut Option<Box<dyn VariantState>>) -> Result<String, String> {
+        Crazyhouse.parse_fen(fen, state)
+    }
+
+    fn write_fen(&self, board: &Board, fen: String) -> String {
+        Crazyhouse.write_fen(board, fen)
+    }
+}
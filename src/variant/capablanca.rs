@@ -0,0 +1,39 @@
+use crate::{Dimensions, PieceType};
+
+use super::Variant;
+
+/// Piece types a pawn promotes to on the boards with an archbishop and a chancellor
+pub(super) const PROMOTIONS: [PieceType; 6] = [
+    PieceType::Queen, PieceType::Chancellor, PieceType::Archbishop, PieceType::Rook, PieceType::Bishop, PieceType::Knight,
+];
+
+/// Capablanca Chess, played on a 10x8 board with an archbishop and a chancellor next to the
+/// bishops
+///
+/// The king starts on the f-file and castles three squares to the c- or i-file, the rook ends
+/// next to it. Pawns are able to promote to an archbishop or chancellor as well.
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new_variant(Arc::new(Capablanca)).unwrap();
+/// assert!(chess.get_legal_moves().len() == 28);
+/// ```
+pub struct Capablanca;
+
+impl Variant for Capablanca {
+    fn get_name(&self) -> &str {
+        "Capablanca"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1".to_string()
+    }
+
+    fn get_dimensions(&self) -> Dimensions {
+        Dimensions::new(10, 8).unwrap()
+    }
+
+    fn get_promotion_pieces(&self) -> &[PieceType] {
+        &PROMOTIONS
+    }
+}
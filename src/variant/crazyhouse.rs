@@ -0,0 +1,267 @@
+use crate::board::{get_pocket_key, get_promoted_key};
+use crate::{BitBoard, Board, Move, PieceType, Side};
+
+use super::{Variant, VariantState};
+
+// order of the pieces in the pocket of a FEN
+const POCKET_ORDER: [PieceType; 5] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn];
+
+/// Crazyhouse, captured pieces go into the pocket of the capturing side and are dropped back on
+/// the board instead of moving
+///
+/// Promoted pieces go back into the pocket as pawns. Pawns are not dropped on the first and last
+/// rank. The pockets are written in brackets after the piece placement of the FEN, and promoted
+/// pieces are marked with a ~ after their letter.
+///
+/// # Example:
+/// ```
+/// let mut chess = Chess::new_variant(Arc::new(Crazyhouse)).unwrap();
+/// for san in ["e4", "d5", "exd5", "Qxd5"] {
+///     chess.play_move(chess.parse_san(san).unwrap());
+/// }
+/// assert!(chess.get_fen() == "rnb1kbnr/ppp1pppp/8/3q4/8/8/PPPP1PPP/RNBQKBNR[Pp] w KQkq - 0 3");
+/// ```
+pub struct Crazyhouse;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+/// Pieces in hand and promoted pieces of a Crazyhouse or Bughouse position
+pub struct CrazyhouseState {
+    pockets: [[u8; 6]; 2], // pieces in hand indexed by side and then by piece type value
+    promoted: u128,        // squares of pieces that were pawns before they promoted
+}
+
+impl CrazyhouseState {
+    /// Check if the piece on index was a pawn before it promoted
+    pub fn is_promoted(&self, index: usize) -> bool {
+        self.promoted & (1 << index) != 0
+    }
+
+    fn set_promoted(&mut self, index: usize, promoted: bool) {
+        match promoted {
+            true => self.promoted |= 1 << index,
+            false => self.promoted &= !(1 << index),
+        }
+    }
+
+    /// Put one more piece into the pocket of side, fairy pieces have no place in it
+    pub(crate) fn add_to_pocket(&mut self, side: Side, piece: PieceType) {
+        if let Some(count) = self.pockets[side.to_index()].get_mut(piece.to_value()) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    fn remove_from_pocket(&mut self, side: Side, piece: PieceType) {
+        if let Some(count) = self.pockets[side.to_index()].get_mut(piece.to_value()) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+impl VariantState for CrazyhouseState {
+    fn clone_state(&self) -> Box<dyn VariantState> {
+        Box::new(*self)
+    }
+
+    fn get_pocket(&self, side: Side) -> [u8; 6] {
+        self.pockets[side.to_index()]
+    }
+
+    fn get_zobrist_key(&self) -> u64 {
+        let mut key = 0;
+        for side in [Side::White, Side::Black] {
+            for piece in POCKET_ORDER {
+                key ^= get_pocket_key(side, piece, self.pockets[side.to_index()][piece.to_value()]);
+            }
+        }
+        // a promoted piece goes back into the pocket as a pawn, so it is not the same position
+        for index in BitBoard::from_number(self.promoted) {
+            key ^= get_promoted_key(index);
+        }
+
+        key
+    }
+}
+
+impl Variant for Crazyhouse {
+    fn get_name(&self) -> &str {
+        "Crazyhouse"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1".to_string()
+    }
+
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        let mut moves = board.generate_legal_moves();
+        moves.extend(board.generate_drops());
+        moves
+    }
+
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        match chess_move.get_drop() {
+            Some(_) => board.generate_drops().contains(&chess_move),
+            None => board.is_standard_legal(chess_move),
+        }
+    }
+
+    fn play_move(&self, board: &mut Board, chess_move: Move) {
+        play_move(board, chess_move, true);
+    }
+
+    // pieces in hand are always able to come back, so there is no lack of material
+    fn has_insufficient_material(&self, _board: &Board, _side: Side) -> bool {
+        false
+    }
+
+    fn is_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+
+    fn parse_fen(&self, fen: &str, state: &mut Option<Box<dyn VariantState>>) -> Result<String, String> {
+        let mut data = CrazyhouseState::default();
+        let fen = fen.trim();
+        let (placement, rest) = fen.split_once(' ').unwrap_or((fen, ""));
+
+        // the pocket is either in brackets or written as a ninth rank
+        let (placement, pocket) = match placement.split_once('[') {
+            Some((placement, pocket)) => (placement, pocket.strip_suffix(']').ok_or(format!("Invalid pocket: [{}", pocket))?),
+            None if placement.split('/').count() == 9 => placement.rsplit_once('/').unwrap(),
+            None => (placement, ""),
+        };
+
+        for c in pocket.chars().filter(|c| *c != '-') {
+            let piece = match PieceType::from_char(c) {
+                piece if POCKET_ORDER.contains(&piece) => piece,
+                _ => return Err(format!("Invalid pocket piece: {}", c)),
+            };
+            let side = if c.is_ascii_uppercase() { Side::White } else { Side::Black };
+            data.add_to_pocket(side, piece);
+        }
+
+        let dimensions = self.get_dimensions();
+        let mut standard = String::new();
+        for (rank, line) in placement.split('/').enumerate() {
+            if rank != 0 {
+                standard.push('/');
+            }
+
+            // empty squares of wide boards take more than one digit
+            let (mut file, mut empty) = (0, 0);
+            for c in line.chars() {
+                standard.extend(Some(c).filter(|c| *c != '~'));
+                if let Some(digit) = c.to_digit(10) {
+                    empty = empty * 10 + digit as usize;
+                    continue;
+                }
+
+                file += std::mem::take(&mut empty);
+                match c {
+                    '~' => match dimensions.get_height().checked_sub(rank + 1) {
+                        Some(y) if (1..=dimensions.get_width()).contains(&file) => data.set_promoted(dimensions.get_index(file - 1, y), true),
+                        _ => return Err(format!("Invalid promoted piece marker on rank {}", dimensions.get_height() as i32 - rank as i32)),
+                    },
+                    _ => file += 1,
+                }
+            }
+        }
+
+        *state = Some(Box::new(data));
+        Ok(format!("{} {}", standard, rest))
+    }
+
+    fn write_fen(&self, board: &Board, fen: String) -> String {
+        let (placement, rest) = fen.split_once(' ').unwrap_or((&fen, ""));
+        let data = board.get_variant_state::<CrazyhouseState>().copied().unwrap_or_default();
+
+        let dimensions = board.get_dimensions();
+        let mut marked = String::new();
+        let (mut y, mut x, mut empty) = (dimensions.get_height() - 1, 0, 0);
+        for c in placement.chars() {
+            marked.push(c);
+            if let Some(digit) = c.to_digit(10) {
+                empty = empty * 10 + digit as usize;
+                continue;
+            }
+
+            x += std::mem::take(&mut empty);
+            match c {
+                '/' => {
+                    y -= 1;
+                    x = 0;
+                },
+                _ => {
+                    if data.is_promoted(dimensions.get_index(x, y)) {
+                        marked.push('~');
+                    }
+                    x += 1;
+                },
+            }
+        }
+
+        let mut pocket = String::new();
+        for side in [Side::White, Side::Black] {
+            for piece in POCKET_ORDER {
+                let c = match side {
+                    Side::White => piece.to_char().to_ascii_uppercase(),
+                    Side::Black => piece.to_char(),
+                };
+                pocket.extend(std::iter::repeat_n(c, data.pockets[side.to_index()][piece.to_value()] as usize));
+            }
+        }
+
+        format!("{}[{}] {}", marked, pocket, rest)
+    }
+}
+
+/// Play a move with drops, a captured piece goes into the pocket of the capturing side only if
+/// keep_captured is set
+pub(super) fn play_move(board: &mut Board, chess_move: Move, keep_captured: bool) {
+    let side = board.get_playing_side();
+    let captured = get_captured(board, chess_move);
+    let Some(data) = board.get_variant_state_mut::<CrazyhouseState>() else {
+        board.apply_move(chess_move);
+        return;
+    };
+
+    match chess_move.get_drop() {
+        Some(piece) => data.remove_from_pocket(side, piece),
+        None => {
+            if let Some((index, piece)) = captured {
+                let piece = if data.is_promoted(index) { PieceType::Pawn } else { piece };
+                if keep_captured {
+                    data.add_to_pocket(side, piece);
+                }
+                data.set_promoted(index, false);
+            }
+
+            let promoted = data.is_promoted(chess_move.get_start()) || chess_move.get_promotion().is_some();
+            data.set_promoted(chess_move.get_start(), false);
+            data.set_promoted(chess_move.get_end(), promoted);
+        },
+    }
+
+    board.apply_move(chess_move);
+}
+
+/// Get the piece a move puts into a pocket, promoted pieces go back as pawns
+pub(crate) fn get_pocket_piece(board: &Board, chess_move: Move) -> Option<PieceType> {
+    let is_promoted = |index| board.get_variant_state::<CrazyhouseState>().is_some_and(|data| data.is_promoted(index));
+    get_captured(board, chess_move).map(|(index, piece)| match is_promoted(index) {
+        true => PieceType::Pawn,
+        false => piece,
+    })
+}
+
+/// Get the index and type of the piece that a move captures
+fn get_captured(board: &Board, chess_move: Move) -> Option<(usize, PieceType)> {
+    if !board.is_capture(chess_move) {
+        return None;
+    }
+
+    let (start, end) = (chess_move.get_start(), chess_move.get_end());
+    match board.get_piece_type_at_pos(end) {
+        // en passant takes the pawn next to the moving pawn
+        PieceType::Empty => Some((board.get_index(board.get_coordinates(end).0, board.get_coordinates(start).1), PieceType::Pawn)),
+        piece => Some((end, piece)),
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{Board, Dimensions, Move, PieceType, Side};
+
+use super::capablanca::PROMOTIONS;
+use super::Variant;
+
+/// Grand Chess, played on a 10x10 board with an archbishop and a chancellor and without castling
+///
+/// The rooks start in the corners and the other pieces one rank higher, the pawns start on the
+/// third rank and may move two squares from there. A pawn may promote on the eighth and ninth
+/// rank and has to promote on the last rank, but only to a piece type of which its side has
+/// fewer on the board than it started with, so a pawn with nothing to promote to is not able to
+/// move to the last rank.
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new_variant(Arc::new(Grand)).unwrap();
+/// assert!(chess.get_legal_moves().len() == 65);
+/// ```
+pub struct Grand;
+
+impl Variant for Grand {
+    fn get_name(&self) -> &str {
+        "Grand"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "r8r/1nbqkcabn1/pppppppppp/10/10/10/10/PPPPPPPPPP/1NBQKCABN1/R8R w - - 0 1".to_string()
+    }
+
+    fn get_dimensions(&self) -> Dimensions {
+        Dimensions::new(10, 10).unwrap()
+    }
+
+    fn get_pawn_rank(&self) -> usize {
+        2
+    }
+
+    fn get_promotion_pieces(&self) -> &[PieceType] {
+        &PROMOTIONS
+    }
+
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        let side = board.get_playing_side();
+        let available: Vec<_> = PROMOTIONS.iter().filter(|piece| is_available(board, side, **piece)).collect();
+        let height = board.get_dimensions().get_height();
+
+        let mut moves = vec![];
+        for chess_move in board.generate_legal_moves() {
+            if let Some(promotion) = chess_move.get_promotion() {
+                if available.contains(&&promotion) {
+                    moves.push(chess_move);
+                }
+                continue;
+            }
+
+            moves.push(chess_move);
+            // the two ranks before the last one are optional promotion squares
+            let y = board.get_coordinates(chess_move.get_end()).1;
+            let relative_rank = match side {
+                Side::White => y,
+                Side::Black => height - 1 - y,
+            };
+            if board.get_piece_type_at_pos(chess_move.get_start()) == PieceType::Pawn && relative_rank + 3 >= height {
+                moves.extend(available.iter().map(|piece| Move::new(chess_move.get_start(), chess_move.get_end(), Some(**piece))));
+            }
+        }
+
+        moves
+    }
+
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        match chess_move.get_promotion() {
+            None => board.is_standard_legal(chess_move),
+            Some(_) => self.get_legal_moves(board).contains(&chess_move),
+        }
+    }
+}
+
+/// Check if side has fewer pieces of a type on the board than it started with
+fn is_available(board: &Board, side: Side, piece: PieceType) -> bool {
+    let start = match piece {
+        PieceType::Queen | PieceType::Chancellor | PieceType::Archbishop => 1,
+        _ => 2,
+    };
+
+    board.get_piece_board(piece, side).count() < start
+}
@@ -0,0 +1,40 @@
+use crate::{Board, Outcome, Side, Termination};
+
+use super::Variant;
+
+/// Horde, white has 36 pawns and no king against the normal black army
+///
+/// White wins by checkmating the black king and black wins by capturing every white piece. White
+/// pawns on the first rank may move two squares, like pawns on the second rank.
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new_variant(Arc::new(Horde)).unwrap();
+/// assert!(chess.get_legal_moves().len() == 8);
+/// ```
+pub struct Horde;
+
+impl Variant for Horde {
+    fn get_name(&self) -> &str {
+        "Horde"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1".to_string()
+    }
+
+    fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+        [Side::White, Side::Black].into_iter()
+            .find(|side| board.get_sides_board(*side).count() == 0)
+            .map(|side| Outcome::loss(side, Termination::Variant))
+    }
+
+    // black wins by capturing every piece, which even a lone king is able to do
+    fn has_insufficient_material(&self, board: &Board, side: Side) -> bool {
+        side == Side::White && board.has_insufficient_material(side)
+    }
+
+    fn is_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{Board, Outcome, PieceType, Side, Termination};
+
+use super::Variant;
+
+// d4, e4, d5 and e5
+const HILL: [usize; 4] = [27, 28, 35, 36];
+
+/// King of the Hill, a side also wins by bringing its king to one of the four center squares
+///
+/// # Example:
+/// ```
+/// let mut chess = Chess::new_variant(Arc::new(KingOfTheHill)).unwrap();
+/// for san in ["e4", "e5", "Ke2", "Ke7", "Ke3", "Ke6", "Kd4"] {
+///     chess.play_move(chess.parse_san(san).unwrap());
+/// }
+/// assert!(chess.get_outcome() == Some(Outcome::loss(Side::Black, Termination::Variant)));
+/// ```
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+    fn get_name(&self) -> &str {
+        "King of the Hill"
+    }
+
+    fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+        [Side::White, Side::Black].into_iter()
+            .find(|side| board.get_piece_board(PieceType::King, *side).into_iter().any(|index| HILL.contains(&index)))
+            .map(|side| Outcome::loss(side.get_opposite(), Termination::Variant))
+    }
+
+    // a lone king is still able to walk to the center
+    fn has_insufficient_material(&self, _board: &Board, _side: Side) -> bool {
+        false
+    }
+
+    fn is_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+}
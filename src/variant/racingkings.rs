@@ -0,0 +1,71 @@
+use crate::{Board, Move, Outcome, PieceType, Side, Termination};
+
+use super::Variant;
+
+/// Racing Kings, the first king to reach the eighth rank wins and no side is ever in check
+///
+/// Moves that give check are illegal. If the white king reaches the eighth rank, black still has
+/// one move to reach it as well, which draws the game.
+///
+/// # Example:
+/// ```
+/// let chess = Chess::new_variant(Arc::new(RacingKings)).unwrap();
+/// assert!(chess.get_legal_moves().len() == 21);
+/// ```
+pub struct RacingKings;
+
+impl Variant for RacingKings {
+    fn get_name(&self) -> &str {
+        "Racing Kings"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1".to_string()
+    }
+
+    fn get_legal_moves(&self, board: &Board) -> Vec<Move> {
+        board.generate_legal_moves().into_iter().filter(|chess_move| {
+            let mut after = board.clone();
+            after.apply_move(*chess_move);
+            !after.is_standard_check()
+        }).collect()
+    }
+
+    fn is_legal(&self, board: &Board, chess_move: Move) -> bool {
+        self.get_legal_moves(board).contains(&chess_move)
+    }
+
+    fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+        match (is_on_goal(board, Side::White), is_on_goal(board, Side::Black)) {
+            (true, true) => Some(Outcome::draw(Termination::Variant)),
+            (false, true) => Some(Outcome::loss(Side::White, Termination::Variant)),
+            // black gets one more move to reach the goal as well
+            (true, false) if board.get_playing_side() == Side::Black => {
+                let king = board.get_piece_board(PieceType::King, Side::Black);
+                let catches_up = self.get_legal_moves(board).into_iter()
+                    .any(|chess_move| king.get(chess_move.get_start()) && is_goal(board, chess_move.get_end()));
+                (!catches_up).then(|| Outcome::loss(Side::Black, Termination::Variant))
+            },
+            (true, false) => Some(Outcome::loss(Side::Black, Termination::Variant)),
+            (false, false) => None,
+        }
+    }
+
+    // kings are always able to race, so there is no lack of material
+    fn has_insufficient_material(&self, _board: &Board, _side: Side) -> bool {
+        false
+    }
+
+    fn is_insufficient_material(&self, _board: &Board) -> bool {
+        false
+    }
+}
+
+fn is_on_goal(board: &Board, side: Side) -> bool {
+    board.get_piece_board(PieceType::King, side).into_iter().any(|index| is_goal(board, index))
+}
+
+/// Check if index is on the last rank
+fn is_goal(board: &Board, index: usize) -> bool {
+    board.get_coordinates(index).1 == board.get_dimensions().get_height() - 1
+}
@@ -0,0 +1,112 @@
+use crate::board::get_checks_key;
+use crate::{Board, Move, Outcome, Side, Termination};
+
+use super::{Variant, VariantState};
+
+// number of checks that wins the game
+const WINNING_CHECKS: u8 = 3;
+
+/// Three-check, a side also wins by giving the third check
+///
+/// The checks given by each side are written after the move counters of the FEN as `+2+1`, white
+/// first. The X-FEN form with the checks each side still has to give, like `1+2` before the move
+/// counters, is read as well.
+///
+/// # Example:
+/// ```
+/// let mut chess = Chess::new_variant(Arc::new(ThreeCheck)).unwrap();
+/// for san in ["e4", "e5", "Bc4", "Nc6", "Bxf7+"] {
+///     chess.play_move(chess.parse_san(san).unwrap());
+/// }
+/// assert!(chess.get_checks(Side::White) == 1);
+/// ```
+pub struct ThreeCheck;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+/// Checks given by each side in a Three-check position
+pub struct ThreeCheckState {
+    checks: [u8; 2],
+}
+
+impl ThreeCheckState {
+    /// Get the number of checks side has given
+    pub fn get_checks(&self, side: Side) -> u8 {
+        self.checks[side.to_index()]
+    }
+}
+
+impl VariantState for ThreeCheckState {
+    fn clone_state(&self) -> Box<dyn VariantState> {
+        Box::new(*self)
+    }
+
+    fn get_zobrist_key(&self) -> u64 {
+        get_checks_key(Side::White, self.checks[0]) ^ get_checks_key(Side::Black, self.checks[1])
+    }
+}
+
+impl Variant for ThreeCheck {
+    fn get_name(&self) -> &str {
+        "Three-check"
+    }
+
+    fn get_start_fen(&self) -> String {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0".to_string()
+    }
+
+    fn play_move(&self, board: &mut Board, chess_move: Move) {
+        let side = board.get_playing_side();
+        board.apply_move(chess_move);
+        if !board.is_standard_check() {
+            return;
+        }
+        if let Some(state) = board.get_variant_state_mut::<ThreeCheckState>() {
+            let checks = &mut state.checks[side.to_index()];
+            *checks = checks.saturating_add(1);
+        }
+    }
+
+    fn get_outcome(&self, board: &Board) -> Option<Outcome> {
+        [Side::White, Side::Black].into_iter()
+            .find(|side| board.get_variant_state::<ThreeCheckState>().is_some_and(|state| state.get_checks(*side) >= WINNING_CHECKS))
+            .map(|side| Outcome::loss(side.get_opposite(), Termination::Variant))
+    }
+
+    // every piece apart from the king is able to give checks
+    fn has_insufficient_material(&self, board: &Board, side: Side) -> bool {
+        board.get_sides_board(side).count() == 1
+    }
+
+    fn is_insufficient_material(&self, board: &Board) -> bool {
+        self.has_insufficient_material(board, Side::White) && self.has_insufficient_material(board, Side::Black)
+    }
+
+    fn parse_fen(&self, fen: &str, state: &mut Option<Box<dyn VariantState>>) -> Result<String, String> {
+        let mut data = ThreeCheckState::default();
+        let mut fields: Vec<_> = fen.split_whitespace().collect();
+        let invalid = |field: &str| format!("Invalid check counter: {}", field);
+        let count = |count: &str, field: &str| count.parse::<u8>().map_err(|_| invalid(field));
+
+        // checks given, as the last field
+        if let Some(field) = fields.last().copied().filter(|field| field.starts_with('+')) {
+            let (white, black) = field[1..].split_once('+').ok_or(invalid(field))?;
+            data.checks = [count(white, field)?, count(black, field)?];
+            fields.pop();
+        }
+        // checks remaining, between the en passant square and the move counters
+        else if let Some(field) = fields.get(4).copied().filter(|field| field.contains('+')) {
+            let (white, black) = field.split_once('+').ok_or(invalid(field))?;
+            let remaining = |remaining| WINNING_CHECKS.checked_sub(remaining).ok_or(invalid(field));
+            data.checks = [remaining(count(white, field)?)?, remaining(count(black, field)?)?];
+            fields.remove(4);
+        }
+
+        *state = Some(Box::new(data));
+        Ok(fields.join(" "))
+    }
+
+    fn write_fen(&self, board: &Board, fen: String) -> String {
+        let data = board.get_variant_state::<ThreeCheckState>().copied().unwrap_or_default();
+        format!("{} +{}+{}", fen, data.get_checks(Side::White), data.get_checks(Side::Black))
+    }
+}
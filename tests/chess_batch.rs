@@ -0,0 +1,56 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run_batch(args: &[&str], script: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chess"))
+        .arg("--batch")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn get_lines(output: &Output) -> Vec<String> {
+    String::from_utf8(output.stdout.clone()).unwrap().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn scripted_game() {
+    let output = run_batch(&[], "# fool's mate\nf3\ne7e5\n\ng4\nQh4\n");
+    let lines = get_lines(&output);
+
+    assert!(output.status.success());
+    assert!(lines.len() == 4);
+    assert!(lines[1].starts_with(r#"{"command":"e7e5","ok":true,"output":"e5","error":null,"#));
+    assert!(lines[3].contains(r#""fen":"rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3""#));
+    assert!(lines[3].contains(r#""state":"Checkmate","result":"0-1","moves":[]"#));
+}
+
+#[test]
+fn reports_errors() {
+    let output = run_batch(&["-"], "e4\nKe2\nperft\nnew \"odd\"\nquit\ne5\n");
+    let lines = get_lines(&output);
+
+    assert!(output.status.code() == Some(1));
+    assert!(lines.len() == 5);
+    assert!(lines[4].starts_with(r#"{"command":"quit","ok":true"#));
+    assert!(lines[1].contains(r#""ok":false,"output":null,"error":"Illegal move: Ke2""#));
+    assert!(lines[1].contains(r#""turn":"Black""#));
+    assert!(lines[2].contains(r#""error":"Usage: perft <depth>""#));
+    assert!(lines[3].contains(r#""error":"Unknown variant: \"odd\"""#));
+}
+
+#[test]
+fn reads_file() {
+    let path = std::env::temp_dir().join(format!("chess_batch_{}.txt", std::process::id()));
+    std::fs::write(&path, "fen 8/8/8/8/8/8/k7/7K w - - 0 1\nstate\n").unwrap();
+    let output = run_batch(&[path.to_str().unwrap()], "");
+    std::fs::remove_file(&path).unwrap();
+    let lines = get_lines(&output);
+
+    assert!(output.status.success());
+    assert!(lines[1].contains(r#""output":"Draw\nGame over: 1/2-1/2 by InsufficientMaterial""#));
+}
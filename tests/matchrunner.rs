@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use chesslib::pgn;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("matchrunner_{}_{}", std::process::id(), name))
+}
+
+/// Play a match of the uci binary against itself from openings, returning the output and the PGN
+fn run_match(name: &str, openings: &str, extra: &[&str]) -> (String, String) {
+    let (epd, pgn) = (temp_path(&format!("{}.epd", name)), temp_path(&format!("{}.pgn", name)));
+    std::fs::write(&epd, openings).unwrap();
+
+    let engine = env!("CARGO_BIN_EXE_uci");
+    let output = Command::new(env!("CARGO_BIN_EXE_matchrunner"))
+        .args(["--engine1", engine, "--engine2", engine, "--name1", "first", "--name2", "second", "--depth", "2"])
+        .args(["--openings", epd.to_str().unwrap(), "--pgn", pgn.to_str().unwrap()])
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let games = std::fs::read_to_string(&pgn).unwrap();
+    std::fs::remove_file(&epd).unwrap();
+    std::fs::remove_file(&pgn).unwrap();
+    (String::from_utf8(output.stdout).unwrap(), games)
+}
+
+#[test]
+fn plays_openings_with_both_colors() {
+    // white mates in one, so each engine wins the game it plays white
+    let (output, games) = run_match("mate", "6k1/5ppp/8/8/8/8/8/R5K1 w - - id \"mate\";\n", &["--games", "2"]);
+    assert!(output.contains("Game 1 (first vs second): 1-0 {Checkmate}"));
+    assert!(output.contains("Game 2 (second vs first): 1-0 {Checkmate}"));
+    assert!(output.contains("Games 2: +1 =0 -1"));
+
+    let games = pgn::parse_pgn(&games).unwrap();
+    assert!(games.len() == 2);
+    assert!(games[0].get_header("White") == Some("first") && games[1].get_header("White") == Some("second"));
+    assert!(games.iter().all(|game| game.result == "1-0" && game.chess.get_history().len() == 1));
+}
+
+#[test]
+fn adjudicates_repetitions() {
+    // every position has occurred once at the start, so the games are drawn before a move
+    let opening = "6k1/5ppp/8/8/8/8/8/R5K1 w - - id \"mate\";\n";
+    let (output, games) = run_match("repetition", opening, &["--games", "2", "--repetition", "1"]);
+    assert!(output.contains("Game 1 (first vs second): 1/2-1/2 {Repetition}"));
+    assert!(output.contains("Games 2: +0 =2 -0"));
+    assert!(games.contains("[Termination \"adjudication\"]"));
+}
@@ -0,0 +1,164 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chesslib::uci::{GoLimits, Score, UciEngine};
+use chesslib::{variant, Chess, Move};
+
+fn spawn_engine() -> UciEngine {
+    UciEngine::spawn(env!("CARGO_BIN_EXE_uci"), &[]).unwrap()
+}
+
+#[test]
+fn handshake() {
+    let mut engine = spawn_engine();
+    assert!(engine.get_name() == Some("chesslib"));
+    assert!(engine.get_author().is_some());
+    engine.set_option("Hash", "4").unwrap();
+    engine.new_game().unwrap();
+    engine.quit();
+}
+
+#[test]
+fn finds_mate_from_history() {
+    let mut engine = spawn_engine();
+    let mut chess = Chess::from_fen("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1".to_string());
+    for notation in ["e1f1", "g8h8", "f1g1", "h8g8"] {
+        assert!(chess.play_move(Move::from_uci(notation).unwrap()));
+    }
+
+    engine.set_position(&chess).unwrap();
+    let mut lines = 0;
+    let result = engine.go_with_info(GoLimits { depth: Some(3), ..Default::default() }, |_| lines += 1).unwrap();
+
+    assert!(result.best_move == Some(Move::from_uci("a1a8").unwrap()));
+    assert!(result.info[0].score == Some(Score::Mate(1)));
+    assert!(lines >= 1);
+}
+
+#[test]
+fn stops_engine_after_timeout() {
+    let mut engine = spawn_engine();
+    engine.set_timeout(Duration::from_millis(200));
+    engine.set_position(&Chess::new()).unwrap();
+
+    // the depth is far out of reach, so the engine only answers after it is told to stop
+    let result = engine.go(GoLimits { depth: Some(60), ..Default::default() }).unwrap();
+    let chess = Chess::new();
+    assert!(chess.get_legal_moves().contains(&result.best_move.unwrap()));
+}
+
+#[test]
+fn ponders_until_ponderhit() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci")).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let best_move = || loop {
+        let line = lines.recv_timeout(Duration::from_secs(10)).unwrap();
+        if line.starts_with("bestmove") {
+            break line;
+        }
+    };
+
+    writeln!(stdin, "position startpos moves e2e4\ngo ponder movetime 200").unwrap();
+    thread::sleep(Duration::from_millis(500));
+    assert!(lines.try_iter().all(|line| !line.starts_with("bestmove")));
+
+    // the move time only starts with ponderhit
+    let start = Instant::now();
+    writeln!(stdin, "ponderhit").unwrap();
+    let reply = best_move();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    let mut chess = Chess::new();
+    assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+    let reply = Move::from_uci(reply.split_whitespace().nth(1).unwrap()).unwrap();
+    assert!(chess.get_legal_moves().contains(&reply));
+
+    // stop ends a ponder search that never gets its ponderhit
+    writeln!(stdin, "go ponder movetime 200").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    writeln!(stdin, "stop").unwrap();
+    best_move();
+    writeln!(stdin, "quit").unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[cfg(unix)]
+#[test]
+fn reads_info_after_stop() {
+    // an engine that only answers stop, with an info line some time before its best move
+    let script = r#"
+        while read line; do
+            case "$line" in
+                uci) echo "id name fake"; echo uciok ;;
+                isready) echo readyok ;;
+                stop) echo "info depth 1 score cp 5 pv e2e4"; sleep 0.1; echo "bestmove e2e4" ;;
+                quit) exit 0 ;;
+            esac
+        done
+    "#;
+    let mut engine = UciEngine::spawn("sh", &["-c", script]).unwrap();
+    assert!(engine.get_name() == Some("fake"));
+    engine.set_timeout(Duration::from_millis(300));
+    engine.set_position(&Chess::new()).unwrap();
+
+    let result = engine.go(GoLimits { depth: Some(60), ..Default::default() }).unwrap();
+    assert!(result.best_move == Some(Move::from_uci("e2e4").unwrap()));
+    assert!(result.info[0].score == Some(Score::Centipawns(5)));
+    engine.quit();
+}
+
+#[cfg(unix)]
+#[test]
+fn sets_chess960_and_variant_options() {
+    // an engine that writes every command it gets to a file
+    let path = std::env::temp_dir().join(format!("uci_options_{}.txt", std::process::id()));
+    let script = r#"
+        while read line; do
+            echo "$line" >> "$0"
+            case "$line" in
+                uci) echo uciok ;;
+                isready) echo readyok ;;
+                quit) exit 0 ;;
+            esac
+        done
+    "#;
+    let mut engine = UciEngine::spawn("sh", &["-c", script, path.to_str().unwrap()]).unwrap();
+
+    engine.set_position(&Chess::new()).unwrap();
+    let chess960 = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+    let chess960 = Chess::from_fen(chess960.to_string());
+    engine.set_position(&chess960).unwrap();
+    let crazyhouse = Chess::new_variant(variant::from_name("crazyhouse").unwrap()).unwrap();
+    engine.set_position(&crazyhouse).unwrap();
+    engine.set_position(&crazyhouse).unwrap();
+    engine.quit();
+
+    let commands = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let expected = [
+        "uci",
+        "position startpos",
+        "setoption name UCI_Chess960 value true",
+        "isready",
+        &format!("position fen {}", chess960.get_fen()),
+        "setoption name UCI_Chess960 value false",
+        "isready",
+        "setoption name UCI_Variant value crazyhouse",
+        "isready",
+        "position startpos",
+        "position startpos",
+        "quit",
+    ];
+    assert!(commands.lines().collect::<Vec<_>>() == expected);
+}
@@ -0,0 +1,177 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chesslib::{Chess, Move};
+
+/// The xboard binary, driven through its stdin and stdout like a GUI would
+struct XBoard {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl XBoard {
+    fn spawn() -> XBoard {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_xboard"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        XBoard { child, stdin, lines }
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.stdin, "{}", line).unwrap();
+    }
+
+    /// Read lines until one starts with prefix
+    ///
+    /// # Return: The lines read before it, and the line itself
+    fn read_until(&self, prefix: &str) -> (Vec<String>, String) {
+        let mut before = vec![];
+        loop {
+            let line = self.lines.recv_timeout(Duration::from_secs(10)).unwrap();
+            if line.starts_with(prefix) {
+                return (before, line);
+            }
+            before.push(line);
+        }
+    }
+
+    fn quit(mut self) {
+        self.send("quit");
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+#[test]
+fn handshake() {
+    let mut xboard = XBoard::spawn();
+    xboard.send("xboard");
+    xboard.send("protover 2");
+    let (_, features) = xboard.read_until("feature");
+    assert!(features.contains("usermove=1") && features.contains("setboard=1") && features.ends_with("done=1"));
+
+    xboard.send("accepted usermove");
+    xboard.send("nonsense");
+    xboard.send("ping 7");
+    let (before, pong) = xboard.read_until("pong");
+    assert!(before == vec!["Error (unknown command): nonsense".to_string()]);
+    assert!(pong == "pong 7");
+    xboard.quit();
+}
+
+#[test]
+fn answers_user_moves() {
+    let mut xboard = XBoard::spawn();
+    xboard.send("protover 2");
+    xboard.send("new");
+    xboard.send("sd 2");
+    xboard.send("usermove e2e4");
+    let (_, reply) = xboard.read_until("move");
+
+    let mut chess = Chess::new();
+    assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+    let reply = Move::from_uci(reply.strip_prefix("move ").unwrap()).unwrap();
+    assert!(chess.get_legal_moves().contains(&reply));
+
+    // the pawn already left e2, so the move is rejected and the engine keeps waiting
+    xboard.send("usermove e2e5");
+    xboard.send("ping 1");
+    let (before, _) = xboard.read_until("pong");
+    assert!(before == vec!["Illegal move: e2e5".to_string()]);
+    xboard.quit();
+}
+
+#[test]
+fn go_plays_for_the_side_to_move() {
+    let mut xboard = XBoard::spawn();
+    xboard.send("protover 2");
+    xboard.send("force");
+    xboard.send("setboard 6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1");
+    xboard.send("sd 3");
+    xboard.send("post");
+    xboard.send("go");
+
+    // thinking lines are ply score time nodes pv, with a mate in one scored as 100001
+    let (thinking, best_move) = xboard.read_until("move");
+    assert!(best_move == "move a1a8");
+    assert!(thinking.iter().any(|line| line.split_whitespace().nth(1) == Some("100001")));
+    let (_, result) = xboard.read_until("1-0");
+    assert!(result == "1-0 {White mates}");
+
+    xboard.send("setboard 8/8/8/8/8/8/8/8 w - - 0 1");
+    let (_, error) = xboard.read_until("tellusererror");
+    assert!(error == "tellusererror Illegal position");
+    xboard.quit();
+}
+
+#[test]
+fn takes_back_moves() {
+    let mut xboard = XBoard::spawn();
+    xboard.send("protover 2");
+    xboard.send("new");
+    xboard.send("sd 2");
+    xboard.send("usermove e2e4");
+    xboard.read_until("move");
+
+    // remove takes back the reply and e2e4, so go plays the first move of the game for white
+    xboard.send("remove");
+    xboard.send("go");
+    let (_, reply) = xboard.read_until("move");
+    let reply = Move::from_uci(reply.strip_prefix("move ").unwrap()).unwrap();
+    assert!(Chess::new().get_legal_moves().contains(&reply));
+
+    // undo takes back a single move, black to move after 1. e4 again
+    xboard.send("force");
+    xboard.send("undo");
+    xboard.send("usermove e2e4");
+    xboard.send("go");
+    let (_, reply) = xboard.read_until("move");
+    let mut chess = Chess::new();
+    assert!(chess.play_move(Move::from_uci("e2e4").unwrap()));
+    let reply = Move::from_uci(reply.strip_prefix("move ").unwrap()).unwrap();
+    assert!(chess.get_legal_moves().contains(&reply));
+    xboard.quit();
+}
+
+#[test]
+fn level_and_st_set_the_search_time() {
+    let mut xboard = XBoard::spawn();
+    xboard.send("protover 2");
+    xboard.send("force");
+    xboard.send("level 40 5 0");
+
+    // on move 40 the last move of the period is left, so all of the second on the clock but the
+    // reserve is used, instead of a fortieth of it
+    xboard.send("setboard r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 40");
+    xboard.send("time 100");
+    let start = Instant::now();
+    xboard.send("go");
+    xboard.read_until("move");
+    assert!(start.elapsed() >= Duration::from_millis(800));
+
+    xboard.send("force");
+    xboard.send("st 0.5");
+    let start = Instant::now();
+    xboard.send("go");
+    xboard.read_until("move");
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(500) && elapsed < Duration::from_secs(5));
+    xboard.quit();
+}